@@ -0,0 +1,89 @@
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body, Bytes, Frame, SizeHint};
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A type-erased response body, so that the handlers of a router can respond with the different body types, e.g. a
+/// buffered [`Full`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html) body from one route and an
+/// [`SseBody`](./struct.SseBody.html) from another.
+///
+/// It's meant to be used as the response body type `B` of a `Router<E, B>`, as it can be created from `Bytes` for the
+/// responses generated by the router itself, e.g. the default 404 one.
+///
+/// # Examples
+///
+/// ```
+/// use futures::stream;
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::body::{BoxBody, Event, Sse};
+/// use routerify_ng::Router;
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible, BoxBody> {
+///     let router = Router::builder()
+///         .get("/", |_| async move { Ok(Response::new(BoxBody::new(Full::new(Bytes::from("Home page"))))) })
+///         .get("/events", |_| async move {
+///             let events = stream::iter(vec![Event::default().data("Hello")]);
+///             Ok(Sse::new(events).into_response().map(BoxBody::new))
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub struct BoxBody {
+    inner: UnsyncBoxBody<Bytes, BoxError>,
+}
+
+impl BoxBody {
+    /// Wraps the `body`, converting its error into a boxed one.
+    pub fn new<B>(body: B) -> BoxBody
+    where
+        B: Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        BoxBody {
+            inner: body.map_err(Into::into).boxed_unsync(),
+        }
+    }
+}
+
+impl Body for BoxBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.get_mut().inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl From<Bytes> for BoxBody {
+    fn from(bytes: Bytes) -> BoxBody {
+        BoxBody::new(Full::new(bytes))
+    }
+}
+
+impl Default for BoxBody {
+    fn default() -> BoxBody {
+        BoxBody::from(Bytes::new())
+    }
+}
+
+impl Debug for BoxBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "BoxBody {{ .. }}")
+    }
+}
@@ -0,0 +1,12 @@
+//! The body types and helpers complementing the [`Full`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html)
+//! bodies used by the route handlers.
+
+pub use boxed::BoxBody;
+pub use multipart::{MultipartBody, MultipartResponse};
+pub use reader::{ReaderBody, from_reader};
+pub use sse::{Event, Sse, SseBody};
+
+mod boxed;
+mod multipart;
+mod reader;
+mod sse;
@@ -0,0 +1,289 @@
+use bytes::BytesMut;
+use http_body_util::BodyExt;
+use http_body_util::combinators::UnsyncBoxBody;
+use hyper::body::{Body, Bytes, Frame};
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Response, StatusCode};
+use std::collections::VecDeque;
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Builds a `multipart/mixed` or a `multipart/form-data` response whose parts are streamed one after another, e.g. to
+/// download several files in one request without holding them in memory.
+///
+/// The boundary is generated randomly, so it won't occur in the part bodies in practice.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::{BodyExt, Full};
+/// use hyper::body::Bytes;
+/// use hyper::header::{self, HeaderMap};
+/// use routerify_ng::body::{self, MultipartResponse};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let mut headers = HeaderMap::new();
+/// headers.insert(header::CONTENT_TYPE, "text/csv".parse()?);
+///
+/// let report: &[u8] = b"id,name\n1,Alice\n";
+/// let multipart = MultipartResponse::mixed()
+///     .part(headers, body::from_reader(report))
+///     .part(HeaderMap::new(), Full::new(Bytes::from("Generated daily")));
+/// let closing = format!("\r\n--{}--\r\n", multipart.boundary());
+///
+/// let content = multipart.into_response().into_body().collect().await?.to_bytes();
+/// assert!(content.ends_with(closing.as_bytes()));
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultipartResponse {
+    subtype: &'static str,
+    boundary: String,
+    parts: VecDeque<(HeaderMap, UnsyncBoxBody<Bytes, BoxError>)>,
+}
+
+impl MultipartResponse {
+    /// Creates a `multipart/mixed` response, the parts of which are independent entities, e.g. the downloaded files.
+    pub fn mixed() -> MultipartResponse {
+        MultipartResponse::new("mixed")
+    }
+
+    /// Creates a `multipart/form-data` response, each part of which is expected to have a `Content-Disposition`
+    /// header naming its field, e.g. `form-data; name="avatar"; filename="me.png"`.
+    pub fn form_data() -> MultipartResponse {
+        MultipartResponse::new("form-data")
+    }
+
+    fn new(subtype: &'static str) -> MultipartResponse {
+        let random = || RandomState::new().build_hasher().finish();
+        MultipartResponse {
+            subtype,
+            boundary: format!("routerify-{:016x}{:016x}", random(), random()),
+            parts: VecDeque::new(),
+        }
+    }
+
+    /// Returns the boundary delimiting the parts.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Appends a part with the specified headers, e.g. `Content-Type`, and body. The body is polled only when the
+    /// preceding parts have been sent.
+    pub fn part<B>(mut self, headers: HeaderMap, body: B) -> Self
+    where
+        B: Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        self.parts.push_back((headers, body.map_err(Into::into).boxed_unsync()));
+        self
+    }
+
+    /// Creates the `200 OK` response with the `multipart/*` content type streaming the parts.
+    pub fn into_response(self) -> Response<MultipartBody> {
+        let content_type = format!("multipart/{}; boundary={}", self.subtype, self.boundary);
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(
+                header::CONTENT_TYPE,
+                HeaderValue::from_str(&content_type)
+                    .expect("The multipart content type is always a valid header value"),
+            )
+            .body(MultipartBody {
+                boundary: self.boundary,
+                parts: self.parts,
+                current: None,
+                started: false,
+                done: false,
+            })
+            .expect("Couldn't create the multipart response")
+    }
+}
+
+impl Debug for MultipartResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MultipartResponse {{ subtype: {:?}, boundary: {:?}, parts: {} }}",
+            self.subtype,
+            self.boundary,
+            self.parts.len()
+        )
+    }
+}
+
+/// A body streaming the parts of a [`MultipartResponse`](./struct.MultipartResponse.html) framed by its boundary.
+///
+/// An error of a part body is surfaced as the body error and ends the body.
+pub struct MultipartBody {
+    boundary: String,
+    parts: VecDeque<(HeaderMap, UnsyncBoxBody<Bytes, BoxError>)>,
+    current: Option<UnsyncBoxBody<Bytes, BoxError>>,
+    started: bool,
+    done: bool,
+}
+
+impl MultipartBody {
+    // Returns the delimiter preceding the next part, along with its headers, or the closing delimiter if there are
+    // no more parts.
+    fn next_delimiter(&mut self) -> Bytes {
+        let mut buf = BytesMut::new();
+        if self.started {
+            buf.extend_from_slice(b"\r\n");
+        }
+        self.started = true;
+
+        buf.extend_from_slice(b"--");
+        buf.extend_from_slice(self.boundary.as_bytes());
+
+        match self.parts.pop_front() {
+            Some((headers, body)) => {
+                buf.extend_from_slice(b"\r\n");
+                for (name, val) in headers.iter() {
+                    buf.extend_from_slice(name.as_str().as_bytes());
+                    buf.extend_from_slice(b": ");
+                    buf.extend_from_slice(val.as_bytes());
+                    buf.extend_from_slice(b"\r\n");
+                }
+                buf.extend_from_slice(b"\r\n");
+                self.current = Some(body);
+            }
+            None => {
+                buf.extend_from_slice(b"--\r\n");
+                self.done = true;
+            }
+        }
+
+        buf.freeze()
+    }
+}
+
+impl Body for MultipartBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(body) = this.current.as_mut() {
+                match Pin::new(body).poll_frame(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    // The trailers of the parts can't be represented, so they're skipped along with the empty chunks.
+                    Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                        Ok(data) if !data.is_empty() => return Poll::Ready(Some(Ok(Frame::data(data)))),
+                        _ => continue,
+                    },
+                    Poll::Ready(Some(Err(err))) => {
+                        this.current = None;
+                        this.parts.clear();
+                        this.done = true;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    Poll::Ready(None) => this.current = None,
+                }
+            }
+
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            return Poll::Ready(Some(Ok(Frame::data(this.next_delimiter()))));
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done && self.current.is_none()
+    }
+}
+
+impl Debug for MultipartBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MultipartBody {{ boundary: {:?}, remaining_parts: {}, done: {:?} }}",
+            self.boundary,
+            self.parts.len(),
+            self.done
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::SinkExt;
+    use futures::channel::mpsc;
+    use http_body_util::{Full, StreamBody};
+
+    fn content_type(val: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(val));
+        headers
+    }
+
+    #[tokio::test]
+    async fn frames_parts_with_boundary() {
+        let chunks = futures::stream::iter(
+            ["id,name\n", "1,Alice\n"]
+                .map(|chunk| Ok::<_, std::io::Error>(Frame::data(Bytes::from_static(chunk.as_bytes())))),
+        );
+
+        let multipart = MultipartResponse::mixed()
+            .part(content_type("text/csv"), StreamBody::new(chunks))
+            .part(content_type("text/plain"), Full::new(Bytes::from("Generated daily")));
+        let boundary = multipart.boundary().to_owned();
+
+        let res = multipart.into_response();
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            format!("multipart/mixed; boundary={}", boundary)
+        );
+
+        let content = res.into_body().collect().await.unwrap().to_bytes();
+        let expected = format!(
+            "--{b}\r\ncontent-type: text/csv\r\n\r\nid,name\n1,Alice\n\r\n--{b}\r\ncontent-type: text/plain\r\n\r\nGenerated daily\r\n--{b}--\r\n",
+            b = boundary
+        );
+        assert_eq!(content, expected);
+    }
+
+    #[tokio::test]
+    async fn streams_parts_lazily() {
+        let (mut tx, rx) = mpsc::channel::<Result<Frame<Bytes>, std::io::Error>>(1);
+        let mut body = MultipartResponse::form_data()
+            .part(HeaderMap::new(), StreamBody::new(rx))
+            .into_response()
+            .into_body();
+
+        let delimiter = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        assert!(delimiter.ends_with(b"\r\n\r\n"));
+
+        tx.send(Ok(Frame::data(Bytes::from("first")))).await.unwrap();
+        assert_eq!(body.frame().await.unwrap().unwrap().into_data().unwrap(), "first");
+
+        drop(tx);
+        let closing = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        assert!(closing.ends_with(b"--\r\n"));
+        assert!(body.frame().await.is_none());
+        assert!(body.is_end_stream());
+    }
+
+    #[tokio::test]
+    async fn surfaces_part_errors() {
+        let failing = futures::stream::iter([Err::<Frame<Bytes>, _>(std::io::Error::other("disk failure"))]);
+        let err = MultipartResponse::mixed()
+            .part(HeaderMap::new(), StreamBody::new(failing))
+            .into_response()
+            .into_body()
+            .collect()
+            .await
+            .unwrap_err();
+        assert_eq!("disk failure", err.to_string());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::constants;
+use hyper::body::{Body, Bytes, Frame};
+use std::fmt::{self, Debug, Formatter};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Creates a body which reads its chunks lazily from the `reader`, e.g. to proxy the generated content without
+/// holding it in memory.
+///
+/// A read error is surfaced as the body error and ends the body.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::BodyExt;
+/// use routerify_ng::body;
+///
+/// # #[tokio::main]
+/// # async fn main() -> std::io::Result<()> {
+/// let reader: &[u8] = b"Hello world";
+/// let content = body::from_reader(reader).collect().await?.to_bytes();
+/// assert_eq!(content, "Hello world");
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_reader<R: AsyncRead + Send + 'static>(reader: R) -> ReaderBody<R> {
+    ReaderBody {
+        reader: Box::pin(reader),
+        buf: vec![0; constants::READER_BODY_CHUNK_SIZE],
+        done: false,
+    }
+}
+
+/// A body reading its chunks lazily from an [`AsyncRead`](https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html),
+/// see [`from_reader`](./fn.from_reader.html).
+pub struct ReaderBody<R> {
+    reader: Pin<Box<R>>,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: AsyncRead> Body for ReaderBody<R> {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let mut read_buf = ReadBuf::new(&mut this.buf);
+        match this.reader.as_mut().poll_read(cx, &mut read_buf) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(())) if read_buf.filled().is_empty() => {
+                this.done = true;
+                Poll::Ready(None)
+            }
+            Poll::Ready(Ok(())) => Poll::Ready(Some(Ok(Frame::data(Bytes::copy_from_slice(read_buf.filled()))))),
+            Poll::Ready(Err(err)) => {
+                this.done = true;
+                Poll::Ready(Some(Err(err)))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
+
+impl<R> Debug for ReaderBody<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "ReaderBody {{ done: {:?} }}", self.done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    struct FailingReader;
+
+    impl AsyncRead for FailingReader {
+        fn poll_read(self: Pin<&mut Self>, _: &mut Context<'_>, _: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Err(io::Error::other("disk failure")))
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_whole_reader_content() {
+        let source = (0..100_000u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+        let mut body = from_reader(io::Cursor::new(source.clone()));
+        let mut frames = 0;
+        let mut collected = Vec::new();
+        while let Some(frame) = body.frame().await {
+            collected.extend_from_slice(&frame.unwrap().into_data().unwrap());
+            frames += 1;
+        }
+
+        assert_eq!(source, collected);
+        assert!(frames > 1);
+        assert!(body.is_end_stream());
+    }
+
+    #[tokio::test]
+    async fn surfaces_reader_errors() {
+        let err = from_reader(FailingReader).collect().await.unwrap_err();
+        assert_eq!("disk failure", err.to_string());
+    }
+}
@@ -0,0 +1,263 @@
+use futures_core::Stream;
+use hyper::body::{Body, Bytes, Frame};
+use hyper::header::{self, HeaderValue};
+use hyper::{Response, StatusCode};
+use std::convert::Infallible;
+use std::fmt::{self, Debug, Formatter, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{Instant, Sleep};
+
+type EventStream = Pin<Box<dyn Stream<Item = Event> + Send>>;
+
+/// Builds a `text/event-stream` response pushing the [`Event`](./struct.Event.html)s of a stream to the client as
+/// [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html), e.g. to an `EventSource` in the
+/// browser.
+///
+/// The response ends when the stream does.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::BodyExt;
+/// use routerify_ng::body::{Event, Sse};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let events = futures::stream::iter(vec![
+///     Event::default().event("greeting").data("Hello"),
+///     Event::default().id("2").data("first line\nsecond line"),
+/// ]);
+///
+/// let res = Sse::new(events).keep_alive(Duration::from_secs(15)).into_response();
+/// let content = res.into_body().collect().await.unwrap().to_bytes();
+/// assert_eq!(
+///     content,
+///     "event: greeting\ndata: Hello\n\nid: 2\ndata: first line\ndata: second line\n\n"
+/// );
+/// # }
+/// ```
+pub struct Sse {
+    events: EventStream,
+    keep_alive: Option<Duration>,
+}
+
+impl Sse {
+    /// Creates the response streaming the events.
+    pub fn new<S>(events: S) -> Sse
+    where
+        S: Stream<Item = Event> + Send + 'static,
+    {
+        Sse {
+            events: Box::pin(events),
+            keep_alive: None,
+        }
+    }
+
+    /// Sends a comment line whenever no event has been sent for the specified interval, so that the proxies don't time
+    /// out the idle connection. It's disabled by default.
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
+    /// Creates the `200 OK` response with the `text/event-stream` content type, which isn't cached.
+    pub fn into_response(self) -> Response<SseBody> {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, HeaderValue::from_static("text/event-stream"))
+            .header(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"))
+            .body(SseBody {
+                events: self.events,
+                keep_alive: self
+                    .keep_alive
+                    .map(|interval| (interval, Box::pin(tokio::time::sleep(interval)))),
+                done: false,
+            })
+            .expect("Couldn't create the event stream response")
+    }
+}
+
+impl Debug for Sse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Sse {{ keep_alive: {:?} }}", self.keep_alive)
+    }
+}
+
+/// A single Server-Sent Event, see [`Sse`](./struct.Sse.html).
+///
+/// The line breaks in the event name and the id are removed, as they would end the field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    comment: Option<String>,
+    event: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+    data: Option<String>,
+}
+
+impl Event {
+    /// Sets the data of the event, which is sent as a `data:` line per line of it.
+    pub fn data<D: Into<String>>(mut self, data: D) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Sets the event name, which is dispatched by the `EventSource` as the event type instead of `message`.
+    pub fn event<N: Into<String>>(mut self, name: N) -> Self {
+        self.event = Some(single_line(name.into()));
+        self
+    }
+
+    /// Sets the event id, which the client sends back via the `Last-Event-ID` header when it reconnects.
+    pub fn id<I: Into<String>>(mut self, id: I) -> Self {
+        self.id = Some(single_line(id.into()));
+        self
+    }
+
+    /// Sets the time the client waits for before reconnecting when the connection is lost, in whole milliseconds.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Sets a comment, which is ignored by the client, sent as a `:` line per line of it.
+    pub fn comment<C: Into<String>>(mut self, comment: C) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    // Frames the event as its field lines followed by the blank line dispatching it.
+    fn encode(&self) -> Bytes {
+        let mut buf = String::new();
+        if let Some(ref comment) = self.comment {
+            for line in split_lines(comment) {
+                let _ = writeln!(buf, ":{}", line);
+            }
+        }
+        if let Some(ref event) = self.event {
+            let _ = writeln!(buf, "event: {}", event);
+        }
+        if let Some(ref id) = self.id {
+            let _ = writeln!(buf, "id: {}", id);
+        }
+        if let Some(retry) = self.retry {
+            let _ = writeln!(buf, "retry: {}", retry.as_millis());
+        }
+        if let Some(ref data) = self.data {
+            for line in split_lines(data) {
+                let _ = writeln!(buf, "data: {}", line);
+            }
+        }
+        buf.push('\n');
+        Bytes::from(buf)
+    }
+}
+
+fn split_lines(val: &str) -> impl Iterator<Item = &str> {
+    val.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+fn single_line(val: String) -> String {
+    if val.contains(['\r', '\n']) {
+        val.replace(['\r', '\n'], "")
+    } else {
+        val
+    }
+}
+
+/// A body streaming the events of an [`Sse`](./struct.Sse.html) response.
+pub struct SseBody {
+    events: EventStream,
+    keep_alive: Option<(Duration, Pin<Box<Sleep>>)>,
+    done: bool,
+}
+
+impl Body for SseBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        match this.events.as_mut().poll_next(cx) {
+            Poll::Ready(Some(event)) => {
+                if let Some((interval, ref mut sleep)) = this.keep_alive {
+                    sleep.as_mut().reset(Instant::now() + interval);
+                }
+                Poll::Ready(Some(Ok(Frame::data(event.encode()))))
+            }
+            Poll::Ready(None) => {
+                this.done = true;
+                Poll::Ready(None)
+            }
+            Poll::Pending => {
+                if let Some((interval, ref mut sleep)) = this.keep_alive
+                    && sleep.as_mut().poll(cx).is_ready()
+                {
+                    sleep.as_mut().reset(Instant::now() + interval);
+                    return Poll::Ready(Some(Ok(Frame::data(Bytes::from_static(b":\n\n")))));
+                }
+                Poll::Pending
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
+
+impl Debug for SseBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SseBody {{ done: {:?} }}", self.done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::SinkExt;
+    use futures::channel::mpsc;
+    use http_body_util::BodyExt;
+
+    #[test]
+    fn frames_event_fields() {
+        let event = Event::default()
+            .comment("hello\nworld")
+            .event("up\r\ndate")
+            .id("7")
+            .retry(Duration::from_secs(3))
+            .data("a\r\nb\n");
+        assert_eq!(
+            event.encode(),
+            ":hello\n:world\nevent: update\nid: 7\nretry: 3000\ndata: a\ndata: b\ndata: \n\n"
+        );
+        assert_eq!(Event::default().data("").encode(), "data: \n\n");
+    }
+
+    #[tokio::test]
+    async fn sends_keep_alive_comments_when_idle() {
+        let (mut tx, rx) = mpsc::channel(1);
+        let res = Sse::new(rx).keep_alive(Duration::from_millis(20)).into_response();
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "text/event-stream");
+        let mut body = res.into_body();
+
+        assert_eq!(body.frame().await.unwrap().unwrap().into_data().unwrap(), ":\n\n");
+
+        tx.send(Event::default().data("tick")).await.unwrap();
+        assert_eq!(
+            body.frame().await.unwrap().unwrap().into_data().unwrap(),
+            "data: tick\n\n"
+        );
+
+        drop(tx);
+        assert!(body.frame().await.is_none());
+        assert!(body.is_end_stream());
+    }
+}
@@ -1,4 +1,5 @@
 use hyper::Method;
+use std::time::Duration;
 
 pub(crate) const ALL_POSSIBLE_HTTP_METHODS: [Method; 9] = [
     Method::GET,
@@ -11,3 +12,9 @@ pub(crate) const ALL_POSSIBLE_HTTP_METHODS: [Method; 9] = [
     Method::OPTIONS,
     Method::TRACE,
 ];
+
+// The delay before asking a long-poll producer for data again after it yielded nothing.
+pub(crate) const LONG_POLL_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+// The size of the chunks read by a `ReaderBody`.
+pub(crate) const READER_BODY_CHUNK_SIZE: usize = 8 * 1024;
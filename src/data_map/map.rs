@@ -19,4 +19,16 @@ impl DataMap {
     pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
         self.inner.get::<T>()
     }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.inner.remove::<T>()
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
 }
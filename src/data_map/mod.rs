@@ -1,7 +1,9 @@
 pub(crate) use map::DataMap;
+pub(crate) use private::PrivateDataMaps;
 pub(crate) use scoped::ScopedDataMap;
 pub(crate) use shared::SharedDataMap;
 
 mod map;
+mod private;
 mod scoped;
 mod shared;
@@ -0,0 +1,6 @@
+use crate::data_map::SharedDataMap;
+
+// The private data maps of the route or the pre middleware being executed. It's replaced on every
+// step, so that the private data of a scope is never seen by the handlers outside of it.
+#[derive(Debug, Clone)]
+pub(crate) struct PrivateDataMaps(pub(crate) Vec<SharedDataMap>);
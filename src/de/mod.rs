@@ -0,0 +1,155 @@
+use crate::Error;
+use crate::types::RouteParams;
+use serde::de::{self, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt::Display;
+use std::str::FromStr;
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::new(msg.to_string())
+    }
+}
+
+/// Deserializes the route parameters as a map of the parameter names to their values, so that
+/// they can be deserialized into a struct having a field per route parameter.
+pub(crate) struct RouteParamsDeserializer<'a> {
+    params: &'a RouteParams,
+}
+
+impl<'a> RouteParamsDeserializer<'a> {
+    pub(crate) fn new(params: &'a RouteParams) -> Self {
+        RouteParamsDeserializer { params }
+    }
+}
+
+impl<'de> Deserializer<'de> for RouteParamsDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(RouteParamsMapAccess {
+            iter: self.params.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct RouteParamsMapAccess<'de, I> {
+    iter: I,
+    value: Option<(&'de str, &'de str)>,
+}
+
+impl<'de, I> MapAccess<'de> for RouteParamsMapAccess<'de, I>
+where
+    I: Iterator<Item = (&'de String, &'de String)>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.iter.next() {
+            Some((name, value)) => {
+                self.value = Some((name.as_str(), value.as_str()));
+                seed.deserialize(name.as_str().into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (name, value) = self
+            .value
+            .take()
+            .expect("`next_value_seed` must be called after `next_key_seed`");
+        seed.deserialize(ParamValueDeserializer { name, value })
+    }
+}
+
+/// Deserializes a single route parameter value by parsing it into the requested primitive type.
+///
+/// The errors mention the name of the parameter which couldn't be parsed.
+pub(crate) struct ParamValueDeserializer<'de> {
+    pub(crate) name: &'de str,
+    pub(crate) value: &'de str,
+}
+
+impl ParamValueDeserializer<'_> {
+    fn parse<T>(&self, type_name: &str) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value.parse::<T>().map_err(|e| {
+            Error::new(format!(
+                "Couldn't parse the route parameter `{}` as {}: {}",
+                self.name, type_name, e
+            ))
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident: $ty:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.parse::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ParamValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.value.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,126 @@
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Response, StatusCode};
+use std::io::{self, Read};
+
+// Decodes the buffered request body in place according to its `Content-Encoding` header and
+// updates the headers to describe the decoded body. The bodies with an unsupported coding are
+// passed through untouched, so that the handlers can still decode them on their own. It returns
+// the rejection response if the body can't be decoded within the limit.
+pub(crate) fn decompress<B: From<Bytes>>(
+    headers: &mut HeaderMap,
+    body: &mut Bytes,
+    max_size: usize,
+) -> Option<Response<B>> {
+    let codings = content_codings(headers)?;
+    if codings.is_empty() {
+        return None;
+    }
+
+    let decoded = match decode_all(&codings, body, max_size) {
+        Ok(decoded) => decoded,
+        Err(err) if err.kind() == io::ErrorKind::FileTooLarge => {
+            return Some(rejection(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+        Err(_) => return Some(rejection(StatusCode::BAD_REQUEST)),
+    };
+
+    headers.remove(header::CONTENT_ENCODING);
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(decoded.len()));
+    *body = decoded;
+    None
+}
+
+// The decoding of a request body which is read only when it's accessed, see `RequestBody::unread_decompressed`.
+#[derive(Debug, Clone)]
+pub(crate) struct Decompression {
+    codings: Vec<String>,
+    max_size: usize,
+}
+
+impl Decompression {
+    // Takes over the decoding of the yet unread body from its headers, which then describe the decoded body. It
+    // returns `None` if there's nothing to decode or the body has an unsupported coding.
+    pub(crate) fn from_headers(headers: &mut HeaderMap, max_size: usize) -> Option<Self> {
+        let codings = content_codings(headers)?;
+        if codings.is_empty() {
+            return None;
+        }
+
+        // The decoded size isn't known until the body is read.
+        headers.remove(header::CONTENT_ENCODING);
+        headers.remove(header::CONTENT_LENGTH);
+        Some(Decompression { codings, max_size })
+    }
+
+    pub(crate) fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    // Decodes the body, failing with `io::ErrorKind::FileTooLarge` if it expands beyond the limit.
+    pub(crate) fn decode(&self, body: &Bytes) -> io::Result<Bytes> {
+        decode_all(&self.codings, body, self.max_size)
+    }
+}
+
+// The codings are listed in the order they were applied, so that they're decoded in reverse.
+fn decode_all(codings: &[String], body: &Bytes, max_size: usize) -> io::Result<Bytes> {
+    let mut decoded = body.clone();
+    for coding in codings.iter().rev() {
+        decoded = decode(coding, &decoded, max_size)?;
+    }
+    Ok(decoded)
+}
+
+// Returns the content codings of the body to decode, or `None` if any of them isn't supported.
+fn content_codings(headers: &HeaderMap) -> Option<Vec<String>> {
+    let mut codings = Vec::new();
+
+    for value in headers.get_all(header::CONTENT_ENCODING) {
+        for coding in value.to_str().ok()?.split(',') {
+            match coding.trim().to_ascii_lowercase().as_str() {
+                "" | "identity" => {}
+                coding @ ("gzip" | "x-gzip" | "deflate") => codings.push(coding.to_owned()),
+                _ => return None,
+            }
+        }
+    }
+
+    Some(codings)
+}
+
+fn decode(coding: &str, body: &[u8], max_size: usize) -> io::Result<Bytes> {
+    match coding {
+        "deflate" => {
+            // The `deflate` coding is zlib-wrapped, but some clients send the raw deflate stream.
+            read_limited(ZlibDecoder::new(body), max_size).or_else(|err| match err.kind() {
+                io::ErrorKind::FileTooLarge => Err(err),
+                _ => read_limited(DeflateDecoder::new(body), max_size),
+            })
+        }
+        _ => read_limited(GzDecoder::new(body), max_size),
+    }
+}
+
+// Reads at most `max_size` decoded bytes, so that a small compressed body can't expand unbounded.
+fn read_limited<R: Read>(decoder: R, max_size: usize) -> io::Result<Bytes> {
+    let mut buf = Vec::new();
+    decoder.take(max_size as u64 + 1).read_to_end(&mut buf)?;
+
+    if buf.len() > max_size {
+        return Err(io::Error::new(
+            io::ErrorKind::FileTooLarge,
+            "The decompressed body exceeds the limit",
+        ));
+    }
+    Ok(Bytes::from(buf))
+}
+
+fn rejection<B: From<Bytes>>(status: StatusCode) -> Response<B> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(B::from(Bytes::new()))
+        .expect("Couldn't create the request decompression rejection response")
+}
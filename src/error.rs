@@ -1,9 +1,208 @@
+use hyper::StatusCode;
 use std::error::Error as StdError;
 use std::fmt::{self, Debug, Display, Formatter};
 
 /// The error type used by the error handlers.
 pub type RouteError = Box<dyn StdError + Send + Sync + 'static>;
 
+/// Maps an error type to the HTTP status code of the response which should be sent for it.
+///
+/// When no custom error handler is added, the default error handler responds with the status code returned by this trait
+/// for the error types registered via the [`RouterBuilder`](./struct.RouterBuilder.html) method
+/// [`err_status`](./struct.RouterBuilder.html#method.err_status), and for [`ParamError`](./enum.ParamError.html),
+/// `QueryError` and `BodyError`. Any other error falls back to `500 Internal Server Error`.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::StatusCode;
+/// use routerify_ng::ErrorStatus;
+/// use std::fmt;
+///
+/// #[derive(Debug)]
+/// enum ApiError {
+///     NotFound,
+///     Unauthorized,
+/// }
+///
+/// impl fmt::Display for ApiError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{:?}", self)
+///     }
+/// }
+///
+/// impl std::error::Error for ApiError {}
+///
+/// impl ErrorStatus for ApiError {
+///     fn status(&self) -> StatusCode {
+///         match self {
+///             ApiError::NotFound => StatusCode::NOT_FOUND,
+///             ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+///         }
+///     }
+/// }
+/// ```
+pub trait ErrorStatus {
+    /// Returns the status code of the response for this error.
+    fn status(&self) -> StatusCode;
+}
+
+// Resolves the status code of a boxed error if it's of the type `T`.
+pub(crate) type ErrStatusMapper = fn(&RouteError) -> Option<StatusCode>;
+
+pub(crate) fn err_status_of<T: ErrorStatus + StdError + 'static>(err: &RouteError) -> Option<StatusCode> {
+    err.downcast_ref::<T>().map(ErrorStatus::status)
+}
+
+/// The error of parsing a route parameter via [`RequestExt::param_as`](./ext/trait.RequestExt.html#tymethod.param_as),
+/// carrying the parameter name.
+///
+/// The default error handler responds to it with `400 Bad Request` if the parameter couldn't be parsed, and with
+/// `500 Internal Server Error` if it's missing, as that means the route path doesn't declare it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    /// The route path has no parameter with this name.
+    Missing {
+        /// The parameter name.
+        name: String,
+    },
+    /// The captured segment couldn't be parsed into the target type.
+    Invalid {
+        /// The parameter name.
+        name: String,
+        /// The captured segment.
+        value: String,
+        /// The `Display` output of the `FromStr` error.
+        reason: String,
+    },
+}
+
+impl ParamError {
+    /// Returns the name of the parameter.
+    pub fn name(&self) -> &str {
+        match self {
+            ParamError::Missing { name } | ParamError::Invalid { name, .. } => name,
+        }
+    }
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Missing { name } => write!(f, "The route parameter `{}` is missing", name),
+            ParamError::Invalid { name, value, reason } => {
+                write!(
+                    f,
+                    "Couldn't parse the route parameter `{}` from {:?}: {}",
+                    name, value, reason
+                )
+            }
+        }
+    }
+}
+
+impl StdError for ParamError {}
+
+impl ErrorStatus for ParamError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ParamError::Missing { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ParamError::Invalid { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// The error of deserializing the query string via [`RequestExt::query_as`](./ext/trait.RequestExt.html#tymethod.query_as).
+///
+/// It's available with the `serde` feature. The default error handler responds to it with `400 Bad Request`.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// The request URI has no query string.
+    Missing,
+
+    /// The query string couldn't be deserialized into the target type.
+    Invalid {
+        /// The deserialization error message, e.g. naming the missing field.
+        reason: String,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Missing => write!(f, "The request has no query string"),
+            QueryError::Invalid { reason } => write!(f, "Couldn't deserialize the query string: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl StdError for QueryError {}
+
+#[cfg(feature = "serde")]
+impl ErrorStatus for QueryError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// The error of reading the JSON request body via [`RequestExt::json`](./ext/trait.RequestExt.html#tymethod.json).
+///
+/// It's available with the `json` feature. The default error handler responds to it with `413 Payload Too Large` if
+/// the body exceeds the limit, and with `400 Bad Request` otherwise.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BodyError {
+    /// The request body is empty.
+    Empty,
+
+    /// The request body exceeds the limit set via
+    /// [`RouterBuilder::body_limit`](./struct.RouterBuilder.html#method.body_limit).
+    TooLarge {
+        /// The limit in bytes.
+        limit: usize,
+    },
+
+    /// The request body isn't valid UTF-8 or couldn't be deserialized from JSON into the target type.
+    Invalid {
+        /// The deserialization error message, e.g. naming the missing field.
+        reason: String,
+    },
+
+    /// The request body couldn't be read, e.g. the client disconnected.
+    Read {
+        /// The reading error message.
+        reason: String,
+    },
+}
+
+#[cfg(feature = "json")]
+impl Display for BodyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::Empty => write!(f, "The request body is empty"),
+            BodyError::TooLarge { limit } => write!(f, "The request body exceeds the limit of {} bytes", limit),
+            BodyError::Invalid { reason } => write!(f, "Couldn't deserialize the request body: {}", reason),
+            BodyError::Read { reason } => write!(f, "Couldn't read the request body: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl StdError for BodyError {}
+
+#[cfg(feature = "json")]
+impl ErrorStatus for BodyError {
+    fn status(&self) -> StatusCode {
+        match self {
+            BodyError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            BodyError::Empty | BodyError::Invalid { .. } | BodyError::Read { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 /// Simple string error for compatibility with Routerify v1.
 /// Can be used in return types of handlers and middleware.
 pub struct Error {
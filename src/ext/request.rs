@@ -1,7 +1,30 @@
-use crate::data_map::SharedDataMap;
-use crate::types::{RequestContext, RequestMeta, RouteParams};
-use hyper::Request;
-use std::net::SocketAddr;
+#[cfg(feature = "serde")]
+use crate::QueryError;
+use crate::data_map::{PrivateDataMaps, SharedDataMap};
+#[cfg(feature = "serde")]
+use crate::de::RouteParamsDeserializer;
+use crate::helpers;
+#[cfg(feature = "client-cert")]
+use crate::types::ClientCert;
+use crate::types::{
+    BufferedBody, Cookie, CookieCache, CookieJar, DeferredTasks, QueryCache, RemoteAddr, RequestBody,
+    RequestCancellation, RequestContext, RequestMeta, RouteParams, TraceContext, TrustConfig, parse_queries,
+};
+#[cfg(feature = "json")]
+use crate::{BodyError, types::ReadError};
+use crate::{Error, ParamError};
+use hyper::body::Bytes;
+use hyper::header::{self, AsHeaderName};
+use hyper::{HeaderMap, Request, Uri};
+#[cfg(any(feature = "serde", feature = "json"))]
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::{Future, pending};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::SystemTime;
 
 /// A extension trait which extends the [`hyper::Request`](https://docs.rs/hyper/0.14.4/hyper/struct.Request.html) and [`http::Parts`](https://docs.rs/http/0.2.4/http/request/struct.Parts.html) types with some helpful methods.
 pub trait RequestExt {
@@ -67,8 +90,770 @@ pub trait RequestExt {
     /// ```
     fn param<P: Into<String>>(&self, param_name: P) -> Option<&String>;
 
+    /// It parses the route parameter value mapped with the specified key into the target type, e.g. a numeric ID.
+    ///
+    /// The returned [`ParamError`](../enum.ParamError.html) carries the parameter name, and the default error handler
+    /// responds to it with `400 Bad Request` if the value couldn't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{ParamError, Router};
+    ///
+    /// fn run() -> Router<ParamError> {
+    ///     let router = Router::builder()
+    ///         .get("/users/:userId", |req| async move {
+    ///             let user_id = req.param_as::<u64>("userId")?;
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("User ID: {}", user_id)))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn param_as<T>(&self, param_name: &str) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display;
+
+    /// It deserializes all the route parameters at once into a type implementing [`serde::Deserialize`](https://docs.rs/serde/1/serde/trait.Deserialize.html),
+    /// mapping each parameter to the field with the same name.
+    ///
+    /// It's available with the `serde` feature. It returns an error mentioning the parameter name if a field is missing or a parameter value couldn't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct BookParams {
+    ///     user_id: u64,
+    ///     book: String,
+    /// }
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .get("/users/:user_id/books/:book", |req| async move {
+    ///             let params: BookParams = req.path_params()?;
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!(
+    ///                 "User ID: {}, Book: {}",
+    ///                 params.user_id, params.book
+    ///             )))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    fn path_params<T: DeserializeOwned>(&self) -> Result<T, Error>;
+
+    /// It returns the route parameters merged with the query string parameters, for the handlers treating them alike.
+    ///
+    /// A route parameter takes precedence over the query parameter with the same name, and the first occurrence of a
+    /// repeated query parameter is used. The query values are percent-decoded, with `+` standing for a space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         // GET /users/42/books?sort=title
+    ///         .get("/users/:user_id/books", |req| async move {
+    ///             let params = req.all_params();
+    ///             let user_id = params.get("user_id").unwrap();
+    ///             let sort = params.get("sort").map(String::as_str).unwrap_or("id");
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!(
+    ///                 "User ID: {}, Sort: {}",
+    ///                 user_id, sort
+    ///             )))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn all_params(&self) -> RouteParams;
+
+    /// It returns the first value of the specified query string parameter, percent-decoded with `+` standing for a
+    /// space.
+    ///
+    /// The query string is parsed on the first call and the result is cached in the request extensions, so the
+    /// repeated calls don't parse it again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         // GET /search?q=hyper%20router
+    ///         .get("/search", |req| async move {
+    ///             let q = req.query("q").unwrap_or_default();
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Searching for: {}", q)))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn query(&self, key: &str) -> Option<String>;
+
+    /// It returns all the query string parameters, along with all the values of the repeated ones in their order, e.g.
+    /// `["1", "2"]` for `a` in `?a=1&a=2`. The names and the values are percent-decoded like in
+    /// [`query`](#tymethod.query).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         // GET /posts?tag=rust&tag=http
+    ///         .get("/posts", |req| async move {
+    ///             let tags = req.queries().remove("tag").unwrap_or_default();
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Tags: {}", tags.join(", "))))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn queries(&self) -> HashMap<String, Vec<String>>;
+
+    /// It deserializes the query string into `T`, e.g. a struct of the filters or the pagination options.
+    ///
+    /// It's available with the `serde` feature. It fails with [`QueryError::Missing`](../enum.QueryError.html#variant.Missing)
+    /// if the request URI has no query string, and with [`QueryError::Invalid`](../enum.QueryError.html#variant.Invalid)
+    /// carrying the serde message if it couldn't be deserialized. The default error handler responds to both with
+    /// `400 Bad Request`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{QueryError, Router};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Default)]
+    /// struct Pagination {
+    ///     page: u32,
+    ///     per_page: u32,
+    /// }
+    ///
+    /// fn run() -> Router<QueryError> {
+    ///     let router = Router::builder()
+    ///         // GET /posts?page=2&per_page=20
+    ///         .get("/posts", |req| async move {
+    ///             let pagination = match req.query_as::<Pagination>() {
+    ///                 Err(QueryError::Missing) => Pagination::default(),
+    ///                 pagination => pagination?,
+    ///             };
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!(
+    ///                 "Page {} of {} posts",
+    ///                 pagination.page, pagination.per_page
+    ///             )))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    fn query_as<T: DeserializeOwned>(&self) -> Result<T, QueryError>;
+
+    /// It returns the first cookie with the specified name sent via the `Cookie` headers, with its value percent-decoded.
+    ///
+    /// The `Cookie` headers are parsed on the first call and the result is cached in the request extensions, so the
+    /// repeated calls don't parse them again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response, StatusCode};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{Middleware, Router};
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+    ///             let Some(session) = req.cookie("session") else {
+    ///                 return Err(routerify_ng::Error::new("Not signed in"));
+    ///             };
+    ///             req.set_context(session.value().to_owned());
+    ///
+    ///             Ok(req)
+    ///         }))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn cookie(&self, name: &str) -> Option<Cookie>;
+
+    /// It returns all the cookies sent via the `Cookie` headers as a [`CookieJar`](../struct.CookieJar.html). Please
+    /// refer to [`cookie`](#tymethod.cookie) for the parsing rules.
+    fn cookies(&self) -> CookieJar;
+
+    /// It returns the index of the route which is handling the request, in the order the routes were added to the
+    /// root router, with the routes of the scoped routers counted at the position they were mounted.
+    ///
+    /// It helps to debug which of the overlapping routes was selected. It's `None` outside of the route handlers,
+    /// e.g. in the pre middlewares.
+    fn matched_route_index(&self) -> Option<usize>;
+
+    /// It returns the path template of the route which is handling the request, e.g. `/users/:id` for `/users/42`,
+    /// to aggregate the logs and the metrics by route instead of by the unique request paths.
+    ///
+    /// It's set before the pre middlewares run, so they can read it too. It's `None` in the pre middlewares for the
+    /// requests matched only by the catch-all `/*` routes or the
+    /// [`not_found`](../struct.RouterBuilder.html#method.not_found) handlers. Please refer to
+    /// [`RequestInfo::matched_path`](../struct.RequestInfo.html#method.matched_path) for the post middlewares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+    ///             println!("{} {}", req.method(), req.matched_path().unwrap_or("unmatched"));
+    ///             Ok(req)
+    ///         }))
+    ///         .get("/users/:id", |req| async move {
+    ///             // It's `/users/:id` for `/users/42`.
+    ///             let route = req.matched_path().unwrap().to_owned();
+    ///             Ok(Response::new(Full::new(Bytes::from(route))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn matched_path(&self) -> Option<&str>;
+
     /// It returns the remote address of the incoming request.
     ///
+    /// It's the unspecified `0.0.0.0:0` address for a client connected over a Unix domain socket, see
+    /// [`peer_addr`](#tymethod.peer_addr).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    /// use hyper::body::Incoming;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/hello", |req| async move {
+    ///             let remote_addr = req.remote_addr();
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!(
+    ///                 "Hello from : {}",
+    ///                 remote_addr
+    ///             )))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn remote_addr(&self) -> SocketAddr;
+
+    /// It returns the address of the client the request came from, which tells the clients connected over TCP and over
+    /// a Unix domain socket apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{RemoteAddr, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/hello", |req| async move {
+    ///             let from = match req.peer_addr() {
+    ///                 RemoteAddr::Tcp(addr) => addr.ip().to_string(),
+    ///                 RemoteAddr::Unix(_) => "a local client".to_owned(),
+    ///             };
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Hello from {}", from)))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn peer_addr(&self) -> RemoteAddr;
+
+    /// It returns the IP address of the client, resolved from the `X-Forwarded-For` or the `Forwarded` header, as set
+    /// via [`TrustConfig::header`](../struct.TrustConfig.html#method.header), if the
+    /// request came through a proxy trusted by the [`RouterBuilder`](../struct.RouterBuilder.html) option
+    /// [`trust_proxy`](../struct.RouterBuilder.html#method.trust_proxy). Otherwise, it's the IP address of
+    /// [`remote_addr`](#tymethod.remote_addr).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{Router, TrustConfig};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .trust_proxy(TrustConfig::new().proxy("127.0.0.1"))
+    ///         .get("/whoami", |req| async move {
+    ///             Ok(Response::new(Full::new(Bytes::from(req.client_addr().to_string()))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn client_addr(&self) -> IpAddr;
+
+    /// It returns the local address the request arrived on, or `None` if it isn't known, e.g. when the request service
+    /// was created from the remote address only.
+    ///
+    /// It can be used to serve some routes only on a specific port.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get_guarded(
+    ///             "/admin",
+    ///             |req| req.local_addr().map(|addr| addr.port()) == Some(9000),
+    ///             |_| async move { Ok(Response::new(Full::new(Bytes::from("Admin panel")))) },
+    ///         )
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn local_addr(&self) -> Option<SocketAddr>;
+
+    /// It returns `true` if the request arrived over a secure connection, e.g. TLS, which can be used to set the `Secure`
+    /// cookies or to enforce HTTPS.
+    ///
+    /// A connection is secure if its request service was built by a
+    /// [`RequestServiceBuilder`](../struct.RequestServiceBuilder.html) marked as [`secure`](../struct.RequestServiceBuilder.html#method.secure),
+    /// or, with the [`RouterBuilder`](../struct.RouterBuilder.html) option
+    /// [`trust_forwarded_proto`](../struct.RouterBuilder.html#method.trust_forwarded_proto), if the proxy forwarded it
+    /// with the `X-Forwarded-Proto: https` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{redirect_permanent, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .trust_forwarded_proto(true)
+    ///         .get("/account", |req| async move {
+    ///             if !req.is_secure() {
+    ///                 return Ok(redirect_permanent("https://example.com/account"));
+    ///             }
+    ///             Ok(Response::new(Full::new(Bytes::from("Account"))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn is_secure(&self) -> bool;
+
+    /// It returns the value of the specified header as a string, or `None` if it's missing or not visible ASCII.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/hello", |req| async move {
+    ///             let user_agent = req.header("user-agent").unwrap_or("unknown");
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Hello, {}", user_agent)))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn header<K: AsHeaderName>(&self, name: K) -> Option<&str>;
+
+    /// It returns the value of the specified header parsed into `T`, or `None` if it's missing or couldn't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .post("/upload", |req| async move {
+    ///             let len = req.header_typed::<u64, _>("content-length").unwrap_or(0);
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Uploading {} bytes", len)))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn header_typed<T: FromStr, K: AsHeaderName>(&self, name: K) -> Option<T>;
+
+    /// It returns all the values of a multi-valued header as strings, skipping the ones which aren't visible ASCII.
+    fn header_all<K: AsHeaderName>(&self, name: K) -> Vec<&str>;
+
+    /// It returns the best of the offered languages according to the `Accept-Language` header and its quality values, or
+    /// `None` if none of them is acceptable. A regional range matches its base language, e.g. `en-US` matches an `en` offer.
+    ///
+    /// If the header is missing, the first offered language is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/hello", |req| async move {
+    ///             let greeting = match req.preferred_language(&["en", "fr", "de"]) {
+    ///                 Some("fr") => "Bonjour",
+    ///                 Some("de") => "Hallo",
+    ///                 _ => "Hello",
+    ///             };
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(greeting))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn preferred_language<'a>(&self, offered: &[&'a str]) -> Option<&'a str>;
+
+    /// It returns whether the client's copy of the content modified at `last_modified` is still fresh according to the
+    /// `If-Modified-Since` header, i.e. whether the handler can respond with [`not_modified`](../fn.not_modified.html)
+    /// instead of generating the body.
+    ///
+    /// It's `false` for the methods other than `GET` and `HEAD`, if the header is missing or invalid, or if the
+    /// `If-None-Match` header is present, as it takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{not_modified, Router};
+    /// use std::convert::Infallible;
+    /// use std::time::SystemTime;
+    ///
+    /// # fn report_updated_at() -> SystemTime { SystemTime::UNIX_EPOCH }
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/report", |req| async move {
+    ///             let updated_at = report_updated_at();
+    ///             if req.not_modified_since(updated_at) {
+    ///                 return Ok(not_modified(updated_at));
+    ///             }
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from("The report"))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn not_modified_since(&self, last_modified: SystemTime) -> bool;
+
+    /// Access data which was shared by the [`RouterBuilder`](../struct.RouterBuilder.html) method
+    /// [`data`](../struct.RouterBuilder.html#method.data) or privately by [`data_private`](../struct.RouterBuilder.html#method.data_private).
+    ///
+    /// Please refer to the [Data and State Sharing](../index.html#data-and-state-sharing) for more info.
+    fn data<T: Send + Sync + 'static>(&self) -> Option<&T>;
+
+    /// Access data in the request context.
+    fn context<T: Send + Sync + Clone + 'static>(&self) -> Option<T>;
+
+    /// Put data into the request context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+    ///             req.set_context("example".to_string());
+    ///
+    ///             Ok(req)
+    ///         }))
+    ///         .get("/hello", |req| async move {
+    ///             let text = req.context::<String>().unwrap();
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Hello from : {}", text)))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn set_context<T: Send + Sync + Clone + 'static>(&self, val: T);
+
+    /// Access the request context itself, e.g. to remove a value from it or to clear it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// #[derive(Clone)]
+    /// struct SessionToken(String);
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+    ///             // The token must not be visible to the handlers.
+    ///             req.request_context().remove::<SessionToken>();
+    ///             Ok(req)
+    ///         }))
+    ///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn request_context(&self) -> &RequestContext;
+
+    /// It returns the W3C trace context of the request stored by the
+    /// [`Middleware::trace_context`](../enum.Middleware.html#method.trace_context) middleware, or `None` if the
+    /// middleware isn't registered. Please refer to [`TraceContext`](../struct.TraceContext.html) for an example.
+    fn trace_context(&self) -> Option<TraceContext>;
+
+    /// It returns the certificate the client authenticated the mutual TLS connection with, or `None` if the request
+    /// service of the connection wasn't given one via
+    /// [`RequestService::with_client_cert`](../struct.RequestService.html#method.with_client_cert).
+    ///
+    /// Please refer to [`ClientCert`](../struct.ClientCert.html) for an example.
+    #[cfg(feature = "client-cert")]
+    fn client_cert(&self) -> Option<&ClientCert>;
+
+    /// It returns the full request body as bytes.
+    ///
+    /// When the router defers body reading via [`RouterBuilder::defer_body_reading`](../struct.RouterBuilder.html#method.defer_body_reading),
+    /// the body is read from the connection on the first call and cached for the later ones. Otherwise, it's the body
+    /// which was already buffered before the request was routed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .defer_body_reading(true)
+    ///         .post("/echo", |req| async move {
+    ///             let body = req.body_bytes().await?;
+    ///
+    ///             Ok(Response::new(Full::new(body)))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn body_bytes(&self) -> impl Future<Output = Result<Bytes, Error>> + Send + 'static;
+
+    /// It returns the full request body as a UTF-8 string, like [`body_bytes`](#tymethod.body_bytes) does as bytes.
+    ///
+    /// It fails if the body can't be read or isn't valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .post("/shout", |req| async move {
+    ///             let body = req.body_string().await?;
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(body.to_uppercase()))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn body_string(&self) -> impl Future<Output = Result<String, Error>> + Send + 'static;
+
+    /// It consumes the request and deserializes its body from JSON into `T`, e.g. the payload of a `POST` request.
+    ///
+    /// It's available with the `json` feature. The body is read up to the limit set via
+    /// [`RouterBuilder::body_limit`](../struct.RouterBuilder.html#method.body_limit), if any. It fails with
+    /// [`BodyError::TooLarge`](../enum.BodyError.html#variant.TooLarge) beyond it, with
+    /// [`BodyError::Empty`](../enum.BodyError.html#variant.Empty) for an empty body and with
+    /// [`BodyError::Invalid`](../enum.BodyError.html#variant.Invalid) if it isn't valid UTF-8 or JSON of the expected
+    /// shape.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{BodyError, Router};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct NewUser {
+    ///     name: String,
+    /// }
+    ///
+    /// fn run() -> Router<BodyError> {
+    ///     let router = Router::builder()
+    ///         .body_limit(64 * 1024)
+    ///         .post("/users", |req| async move {
+    ///             let user = req.json::<NewUser>().await?;
+    ///
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Created {}", user.name)))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    #[cfg(feature = "json")]
+    fn json<T: DeserializeOwned>(self) -> impl Future<Output = Result<T, BodyError>> + Send
+    where
+        Self: Sized;
+
+    /// It returns the request body buffered before the request was routed, without consuming it, or `None` if the body
+    /// isn't buffered, e.g. with the [`defer_body_reading`](../struct.RouterBuilder.html#method.defer_body_reading)
+    /// option.
+    ///
+    /// It lets a pre middleware inspect the body, e.g. to verify its signature, while leaving it intact for the handler,
+    /// even if a previous pre middleware replaced the request body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{Middleware, Router};
+    ///
+    /// # fn verify(_: &[u8], _: Option<&str>) -> bool { true }
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::pre(|req| async move {
+    ///             let body = req.peek_body().map(|body| body.as_ref()).unwrap_or_default();
+    ///             if !verify(body, req.header("x-signature")) {
+    ///                 return Err(routerify_ng::Error::new("Invalid signature"));
+    ///             }
+    ///             Ok(req)
+    ///         }))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    fn peek_body(&self) -> Option<&Bytes>;
+
+    /// It returns a future which resolves when the request is cancelled, i.e. the client disconnects before the
+    /// response is sent. It never resolves for a request which completes normally.
+    ///
+    /// Hyper drops the handler future itself on disconnect, so this is meant for the work spawned off the handler,
+    /// e.g. a long-running database query, which should be aborted along with the request.
+    ///
     /// # Examples
     ///
     /// ```
@@ -77,63 +862,61 @@ pub trait RequestExt {
     /// use routerify_ng::ext::RequestExt;
     /// use routerify_ng::Router;
     /// use std::convert::Infallible;
-    /// use hyper::body::Incoming;
     ///
+    /// # async fn run_report() -> String { String::new() }
     /// fn run() -> Router<Infallible> {
     ///     let router = Router::builder()
-    ///         .get("/hello", |req| async move {
-    ///             let remote_addr = req.remote_addr();
+    ///         .get("/report", |req| async move {
+    ///             let cancelled = req.cancelled();
+    ///             let report = tokio::spawn(async move {
+    ///                 tokio::select! {
+    ///                     report = run_report() => Some(report),
+    ///                     _ = cancelled => None,
+    ///                 }
+    ///             });
     ///
-    ///             Ok(Response::new(Full::new(Bytes::from(format!(
-    ///                 "Hello from : {}",
-    ///                 remote_addr
-    ///             )))))
+    ///             let report = report.await.unwrap().unwrap_or_default();
+    ///             Ok(Response::new(Full::new(Bytes::from(report))))
     ///         })
     ///         .build()
     ///         .unwrap();
     ///     router
     /// }
     /// ```
-    fn remote_addr(&self) -> SocketAddr;
+    fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static;
 
-    /// Access data which was shared by the [`RouterBuilder`](../struct.RouterBuilder.html) method
-    /// [`data`](../struct.RouterBuilder.html#method.data).
+    /// It schedules the `task` to run once the response of the request is produced, without delaying the response,
+    /// e.g. for the analytics or the cache warming.
     ///
-    /// Please refer to the [Data and State Sharing](../index.html#data-and-state-sharing) for more info.
-    fn data<T: Send + Sync + 'static>(&self) -> Option<&T>;
-
-    /// Access data in the request context.
-    fn context<T: Send + Sync + Clone + 'static>(&self) -> Option<T>;
-
-    /// Put data into the request context.
+    /// The tasks are spawned detached on the Tokio runtime after the post middlewares run, i.e. possibly before the
+    /// response is fully written to the client. They are dropped without running if the request is abandoned before,
+    /// e.g. when the client disconnects mid-request, so they run at most once. Outside the request service, e.g. in
+    /// the tests calling the handlers directly, the task is spawned right away.
     ///
     /// # Examples
     ///
     /// ```
     /// use http_body_util::Full;
-    /// use hyper::{body::Bytes, Request, Response};
+    /// use hyper::{body::Bytes, Response};
     /// use routerify_ng::ext::RequestExt;
-    /// use routerify_ng::{Middleware, Router};
+    /// use routerify_ng::Router;
     /// use std::convert::Infallible;
     ///
+    /// # async fn record_view(_: String) {}
     /// fn run() -> Router<Infallible> {
     ///     let router = Router::builder()
-    ///         .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
-    ///             req.set_context("example".to_string());
-    ///
-    ///             Ok(req)
-    ///         }))
-    ///         .get("/hello", |req| async move {
-    ///             let text = req.context::<String>().unwrap();
+    ///         .get("/articles/:id", |req| async move {
+    ///             let id = req.param("id").unwrap().to_owned();
+    ///             req.spawn_after_response(record_view(id));
     ///
-    ///             Ok(Response::new(Full::new(Bytes::from(format!("Hello from : {}", text)))))
+    ///             Ok(Response::new(Full::new(Bytes::from("Article"))))
     ///         })
     ///         .build()
     ///         .unwrap();
     ///     router
     /// }
     /// ```
-    fn set_context<T: Send + Sync + Clone + 'static>(&self, val: T);
+    fn spawn_after_response<F: Future<Output = ()> + Send + 'static>(&self, task: F);
 }
 
 fn params(ext: &http::Extensions) -> &RouteParams {
@@ -146,6 +929,81 @@ fn param<P: Into<String>>(ext: &http::Extensions, param_name: P) -> Option<&Stri
     params(ext).get(param_name.into())
 }
 
+#[cfg(feature = "serde")]
+fn path_params<T: DeserializeOwned>(ext: &http::Extensions) -> Result<T, Error> {
+    T::deserialize(RouteParamsDeserializer::new(params(ext)))
+}
+
+fn all_params(ext: &http::Extensions, uri: &Uri) -> RouteParams {
+    let mut all_params = params(ext).clone();
+    for (name, val) in helpers::query_pairs(uri.query().unwrap_or_default()) {
+        if !all_params.has(name.as_str()) {
+            all_params.set(name, val);
+        }
+    }
+    all_params
+}
+
+fn query(ext: &http::Extensions, uri: &Uri, key: &str) -> Option<String> {
+    let query = uri.query().unwrap_or_default();
+    match ext.get::<QueryCache>() {
+        Some(cache) => cache
+            .get_or_parse(query)
+            .get(key)
+            .and_then(|vals| vals.first())
+            .cloned(),
+        None => helpers::query_pairs(query)
+            .into_iter()
+            .find_map(|(name, val)| (name == key).then_some(val)),
+    }
+}
+
+fn queries(ext: &http::Extensions, uri: &Uri) -> HashMap<String, Vec<String>> {
+    let query = uri.query().unwrap_or_default();
+    match ext.get::<QueryCache>() {
+        Some(cache) => cache.get_or_parse(query).as_ref().clone(),
+        None => parse_queries(query),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn query_as<T: DeserializeOwned>(uri: &Uri) -> Result<T, QueryError> {
+    let query = uri.query().ok_or(QueryError::Missing)?;
+    serde_urlencoded::from_str(query).map_err(|e| QueryError::Invalid { reason: e.to_string() })
+}
+
+fn cookie_jar(ext: &http::Extensions, headers: &HeaderMap) -> Arc<CookieJar> {
+    let cookie_headers = helpers::header_all(headers, header::COOKIE);
+    match ext.get::<CookieCache>() {
+        Some(cache) => cache.get_or_parse(&cookie_headers),
+        None => Arc::new(CookieJar::parse(cookie_headers)),
+    }
+}
+
+fn cookie(ext: &http::Extensions, headers: &HeaderMap, name: &str) -> Option<Cookie> {
+    cookie_jar(ext, headers).get(name).cloned()
+}
+
+fn cookies(ext: &http::Extensions, headers: &HeaderMap) -> CookieJar {
+    cookie_jar(ext, headers).as_ref().clone()
+}
+
+fn matched_route_index(ext: &http::Extensions) -> Option<usize> {
+    ext.get::<RequestMeta>().and_then(|meta| meta.matched_route_index())
+}
+
+fn matched_path(ext: &http::Extensions) -> Option<&str> {
+    ext.get::<RequestMeta>().and_then(|meta| meta.matched_path())
+}
+
+fn local_addr(ext: &http::Extensions) -> Option<SocketAddr> {
+    ext.get::<RequestMeta>().and_then(|meta| meta.local_addr()).copied()
+}
+
+fn is_secure(ext: &http::Extensions) -> bool {
+    ext.get::<RequestMeta>().is_some_and(|meta| meta.is_secure())
+}
+
 fn remote_addr(ext: &http::Extensions) -> SocketAddr {
     ext.get::<RequestMeta>()
         .and_then(|meta| meta.remote_addr())
@@ -153,7 +1011,30 @@ fn remote_addr(ext: &http::Extensions) -> SocketAddr {
         .expect("Routerify: No remote address added while processing request")
 }
 
+fn peer_addr(ext: &http::Extensions) -> RemoteAddr {
+    ext.get::<RequestMeta>()
+        .and_then(|meta| meta.peer_addr())
+        .cloned()
+        .expect("Routerify: No remote address added while processing request")
+}
+
+fn client_addr(ext: &http::Extensions, headers: &HeaderMap) -> IpAddr {
+    let peer = remote_addr(ext).ip();
+    match ext.get::<Arc<TrustConfig>>() {
+        Some(trust_proxy) => trust_proxy.client_addr(peer, headers),
+        None => peer,
+    }
+}
+
 fn data<T: Send + Sync + 'static>(ext: &http::Extensions) -> Option<&T> {
+    if let Some(PrivateDataMaps(private_data_maps)) = ext.get::<PrivateDataMaps>() {
+        for private_data_map in private_data_maps.iter() {
+            if let Some(data) = private_data_map.inner.get::<T>() {
+                return Some(data);
+            }
+        }
+    }
+
     let shared_data_maps = ext.get::<Vec<SharedDataMap>>();
 
     if let Some(shared_data_maps) = shared_data_maps {
@@ -168,13 +1049,88 @@ fn data<T: Send + Sync + 'static>(ext: &http::Extensions) -> Option<&T> {
 }
 
 fn context<T: Send + Sync + Clone + 'static>(ext: &http::Extensions) -> Option<T> {
-    let ctx = ext.get::<RequestContext>().expect("Context must be present");
-    ctx.get::<T>()
+    request_context(ext).get::<T>()
+}
+
+fn request_context(ext: &http::Extensions) -> &RequestContext {
+    ext.get::<RequestContext>().expect("Context must be present")
 }
 
 fn set_context<T: Send + Sync + Clone + 'static>(ext: &http::Extensions, val: T) {
-    let ctx = ext.get::<RequestContext>().expect("Context must be present");
-    ctx.set(val)
+    request_context(ext).set(val)
+}
+
+#[cfg(feature = "client-cert")]
+fn client_cert(ext: &http::Extensions) -> Option<&ClientCert> {
+    ext.get::<Arc<ClientCert>>().map(|cert| cert.as_ref())
+}
+
+fn trace_context(ext: &http::Extensions) -> Option<TraceContext> {
+    ext.get::<RequestContext>().and_then(|ctx| ctx.get::<TraceContext>())
+}
+
+fn body_bytes(ext: &http::Extensions) -> impl Future<Output = Result<Bytes, Error>> + Send + 'static {
+    let body = ext.get::<RequestBody>().cloned();
+
+    async move {
+        match body {
+            Some(body) => body.bytes().await.map_err(|e| Error::new(e.to_string())),
+            None => Err(Error::new("Routerify: No request body added while processing request")),
+        }
+    }
+}
+
+fn body_string(ext: &http::Extensions) -> impl Future<Output = Result<String, Error>> + Send + 'static {
+    let body = body_bytes(ext);
+
+    async move {
+        let body = body.await?;
+        String::from_utf8(body.to_vec())
+            .map_err(|e| Error::new(format!("Couldn't decode the request body as UTF-8: {}", e)))
+    }
+}
+
+#[cfg(feature = "json")]
+async fn json<T: DeserializeOwned>(body: Option<RequestBody>) -> Result<T, BodyError> {
+    let Some(body) = body else {
+        return Err(BodyError::Read {
+            reason: "No request body added while processing request".to_owned(),
+        });
+    };
+
+    let body = body.bytes().await.map_err(|err| match err {
+        ReadError::TooLarge(limit) => BodyError::TooLarge { limit },
+        ReadError::Failed(reason) => BodyError::Read { reason },
+    })?;
+    if body.is_empty() {
+        return Err(BodyError::Empty);
+    }
+
+    serde_json::from_slice(&body).map_err(|e| BodyError::Invalid { reason: e.to_string() })
+}
+
+fn peek_body(ext: &http::Extensions) -> Option<&Bytes> {
+    ext.get::<BufferedBody>().map(|body| &body.0)
+}
+
+fn cancelled(ext: &http::Extensions) -> impl Future<Output = ()> + Send + 'static {
+    let cancellation = ext.get::<RequestCancellation>().cloned();
+
+    async move {
+        match cancellation {
+            Some(cancellation) => cancellation.cancelled().await,
+            None => pending::<()>().await,
+        }
+    }
+}
+
+fn spawn_after_response<F: Future<Output = ()> + Send + 'static>(ext: &http::Extensions, task: F) {
+    match ext.get::<DeferredTasks>() {
+        Some(deferred_tasks) => deferred_tasks.push(task),
+        None => {
+            tokio::spawn(task);
+        }
+    }
 }
 
 impl<B> RequestExt for Request<B> {
@@ -186,10 +1142,92 @@ impl<B> RequestExt for Request<B> {
         param(self.extensions(), param_name)
     }
 
+    fn param_as<T>(&self, param_name: &str) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        params(self.extensions()).get_as(param_name)
+    }
+
+    #[cfg(feature = "serde")]
+    fn path_params<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        path_params(self.extensions())
+    }
+
+    fn all_params(&self) -> RouteParams {
+        all_params(self.extensions(), self.uri())
+    }
+
+    fn query(&self, key: &str) -> Option<String> {
+        query(self.extensions(), self.uri(), key)
+    }
+
+    fn queries(&self) -> HashMap<String, Vec<String>> {
+        queries(self.extensions(), self.uri())
+    }
+
+    #[cfg(feature = "serde")]
+    fn query_as<T: DeserializeOwned>(&self) -> Result<T, QueryError> {
+        query_as(self.uri())
+    }
+
+    fn cookie(&self, name: &str) -> Option<Cookie> {
+        cookie(self.extensions(), self.headers(), name)
+    }
+
+    fn cookies(&self) -> CookieJar {
+        cookies(self.extensions(), self.headers())
+    }
+
+    fn matched_route_index(&self) -> Option<usize> {
+        matched_route_index(self.extensions())
+    }
+
+    fn matched_path(&self) -> Option<&str> {
+        matched_path(self.extensions())
+    }
+
     fn remote_addr(&self) -> SocketAddr {
         remote_addr(self.extensions())
     }
 
+    fn peer_addr(&self) -> RemoteAddr {
+        peer_addr(self.extensions())
+    }
+
+    fn client_addr(&self) -> IpAddr {
+        client_addr(self.extensions(), self.headers())
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        local_addr(self.extensions())
+    }
+
+    fn is_secure(&self) -> bool {
+        is_secure(self.extensions())
+    }
+
+    fn header<K: AsHeaderName>(&self, name: K) -> Option<&str> {
+        helpers::header(self.headers(), name)
+    }
+
+    fn header_typed<T: FromStr, K: AsHeaderName>(&self, name: K) -> Option<T> {
+        helpers::header_typed(self.headers(), name)
+    }
+
+    fn header_all<K: AsHeaderName>(&self, name: K) -> Vec<&str> {
+        helpers::header_all(self.headers(), name)
+    }
+
+    fn preferred_language<'a>(&self, offered: &[&'a str]) -> Option<&'a str> {
+        helpers::preferred_language(self.headers(), offered)
+    }
+
+    fn not_modified_since(&self, last_modified: SystemTime) -> bool {
+        helpers::not_modified_since(self.method(), self.headers(), last_modified)
+    }
+
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
         data(self.extensions())
     }
@@ -201,6 +1239,44 @@ impl<B> RequestExt for Request<B> {
     fn set_context<T: Send + Sync + Clone + 'static>(&self, val: T) {
         set_context(self.extensions(), val)
     }
+
+    fn request_context(&self) -> &RequestContext {
+        request_context(self.extensions())
+    }
+
+    fn trace_context(&self) -> Option<TraceContext> {
+        trace_context(self.extensions())
+    }
+
+    #[cfg(feature = "client-cert")]
+    fn client_cert(&self) -> Option<&ClientCert> {
+        client_cert(self.extensions())
+    }
+
+    fn body_bytes(&self) -> impl Future<Output = Result<Bytes, Error>> + Send + 'static {
+        body_bytes(self.extensions())
+    }
+
+    fn body_string(&self) -> impl Future<Output = Result<String, Error>> + Send + 'static {
+        body_string(self.extensions())
+    }
+
+    #[cfg(feature = "json")]
+    fn json<T: DeserializeOwned>(self) -> impl Future<Output = Result<T, BodyError>> + Send {
+        json(self.extensions().get::<RequestBody>().cloned())
+    }
+
+    fn peek_body(&self) -> Option<&Bytes> {
+        peek_body(self.extensions())
+    }
+
+    fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        cancelled(self.extensions())
+    }
+
+    fn spawn_after_response<F: Future<Output = ()> + Send + 'static>(&self, task: F) {
+        spawn_after_response(self.extensions(), task)
+    }
 }
 
 impl RequestExt for http::request::Parts {
@@ -212,10 +1288,92 @@ impl RequestExt for http::request::Parts {
         param(&self.extensions, param_name)
     }
 
+    fn param_as<T>(&self, param_name: &str) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        params(&self.extensions).get_as(param_name)
+    }
+
+    #[cfg(feature = "serde")]
+    fn path_params<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        path_params(&self.extensions)
+    }
+
+    fn all_params(&self) -> RouteParams {
+        all_params(&self.extensions, &self.uri)
+    }
+
+    fn query(&self, key: &str) -> Option<String> {
+        query(&self.extensions, &self.uri, key)
+    }
+
+    fn queries(&self) -> HashMap<String, Vec<String>> {
+        queries(&self.extensions, &self.uri)
+    }
+
+    #[cfg(feature = "serde")]
+    fn query_as<T: DeserializeOwned>(&self) -> Result<T, QueryError> {
+        query_as(&self.uri)
+    }
+
+    fn cookie(&self, name: &str) -> Option<Cookie> {
+        cookie(&self.extensions, &self.headers, name)
+    }
+
+    fn cookies(&self) -> CookieJar {
+        cookies(&self.extensions, &self.headers)
+    }
+
+    fn matched_route_index(&self) -> Option<usize> {
+        matched_route_index(&self.extensions)
+    }
+
+    fn matched_path(&self) -> Option<&str> {
+        matched_path(&self.extensions)
+    }
+
     fn remote_addr(&self) -> SocketAddr {
         remote_addr(&self.extensions)
     }
 
+    fn peer_addr(&self) -> RemoteAddr {
+        peer_addr(&self.extensions)
+    }
+
+    fn client_addr(&self) -> IpAddr {
+        client_addr(&self.extensions, &self.headers)
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        local_addr(&self.extensions)
+    }
+
+    fn is_secure(&self) -> bool {
+        is_secure(&self.extensions)
+    }
+
+    fn header<K: AsHeaderName>(&self, name: K) -> Option<&str> {
+        helpers::header(&self.headers, name)
+    }
+
+    fn header_typed<T: FromStr, K: AsHeaderName>(&self, name: K) -> Option<T> {
+        helpers::header_typed(&self.headers, name)
+    }
+
+    fn header_all<K: AsHeaderName>(&self, name: K) -> Vec<&str> {
+        helpers::header_all(&self.headers, name)
+    }
+
+    fn preferred_language<'a>(&self, offered: &[&'a str]) -> Option<&'a str> {
+        helpers::preferred_language(&self.headers, offered)
+    }
+
+    fn not_modified_since(&self, last_modified: SystemTime) -> bool {
+        helpers::not_modified_since(&self.method, &self.headers, last_modified)
+    }
+
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
         data(&self.extensions)
     }
@@ -227,4 +1385,42 @@ impl RequestExt for http::request::Parts {
     fn set_context<T: Send + Sync + Clone + 'static>(&self, val: T) {
         set_context(&self.extensions, val)
     }
+
+    fn request_context(&self) -> &RequestContext {
+        request_context(&self.extensions)
+    }
+
+    fn trace_context(&self) -> Option<TraceContext> {
+        trace_context(&self.extensions)
+    }
+
+    #[cfg(feature = "client-cert")]
+    fn client_cert(&self) -> Option<&ClientCert> {
+        client_cert(&self.extensions)
+    }
+
+    fn body_bytes(&self) -> impl Future<Output = Result<Bytes, Error>> + Send + 'static {
+        body_bytes(&self.extensions)
+    }
+
+    fn body_string(&self) -> impl Future<Output = Result<String, Error>> + Send + 'static {
+        body_string(&self.extensions)
+    }
+
+    #[cfg(feature = "json")]
+    fn json<T: DeserializeOwned>(self) -> impl Future<Output = Result<T, BodyError>> + Send {
+        json(self.extensions.get::<RequestBody>().cloned())
+    }
+
+    fn peek_body(&self) -> Option<&Bytes> {
+        peek_body(&self.extensions)
+    }
+
+    fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        cancelled(&self.extensions)
+    }
+
+    fn spawn_after_response<F: Future<Output = ()> + Send + 'static>(&self, task: F) {
+        spawn_after_response(&self.extensions, task)
+    }
 }
@@ -1,7 +1,10 @@
 use crate::Error;
 use crate::types::RequestMeta;
-use http::Extensions;
+use http::header::AsHeaderName;
+use http::{Extensions, HeaderMap, Method};
 use percent_encoding::percent_decode_str;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub(crate) fn update_req_meta_in_extensions(ext: &mut Extensions, new_req_meta: RequestMeta) {
     if let Some(existing_req_meta) = ext.get_mut::<RequestMeta>() {
@@ -18,10 +21,266 @@ pub(crate) fn percent_decode_request_path(val: &str) -> crate::Result<String> {
         .map(|val| val.to_string())
 }
 
+// The header getters shared by `RequestExt` and `RequestInfo`. The values which aren't visible ASCII are skipped.
+pub(crate) fn header<K: AsHeaderName>(headers: &HeaderMap, name: K) -> Option<&str> {
+    headers.get(name).and_then(|val| val.to_str().ok())
+}
+
+pub(crate) fn header_typed<T: FromStr, K: AsHeaderName>(headers: &HeaderMap, name: K) -> Option<T> {
+    header(headers, name).and_then(|val| val.trim().parse::<T>().ok())
+}
+
+pub(crate) fn header_all<K: AsHeaderName>(headers: &HeaderMap, name: K) -> Vec<&str> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|val| val.to_str().ok())
+        .collect()
+}
+
+// Parses the `application/x-www-form-urlencoded` pairs of a query string, in their order. A pair without `=` has an
+// empty value, and the invalid UTF-8 sequences are replaced.
+pub(crate) fn query_pairs(query: &str) -> Vec<(String, String)> {
+    let decode = |val: &str| {
+        percent_decode_str(&val.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, val) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(val))
+        })
+        .collect()
+}
+
+// Picks the best of the offered languages according to the `Accept-Language` header. A language range matches an offer
+// if they're equal or one of them is a prefix of the other at a `-` boundary, e.g. `en-US` matches an `en` offer, and the
+// most specific matching range gives the offer its quality. The ties are broken by the order of the offers.
+pub(crate) fn preferred_language<'a>(headers: &HeaderMap, offered: &[&'a str]) -> Option<&'a str> {
+    let ranges = header_all(headers, http::header::ACCEPT_LANGUAGE)
+        .into_iter()
+        .flat_map(|val| val.split(','))
+        .filter_map(parse_language_range)
+        .collect::<Vec<_>>();
+
+    if ranges.is_empty() {
+        return offered.first().copied();
+    }
+
+    let mut best: Option<(&'a str, f32)> = None;
+    for offer in offered {
+        let quality = ranges
+            .iter()
+            .filter_map(|(range, q)| language_match_specificity(range, offer).map(|specificity| (specificity, *q)))
+            .max_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+            .map(|(_, q)| q)
+            .unwrap_or(0.0);
+
+        if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+            best = Some((offer, quality));
+        }
+    }
+
+    best.map(|(offer, _)| offer)
+}
+
+fn parse_language_range(entry: &str) -> Option<(&str, f32)> {
+    let mut parts = entry.split(';');
+    let range = parts.next()?.trim();
+    if range.is_empty() {
+        return None;
+    }
+
+    let quality = parts
+        .filter_map(|param| param.trim().strip_prefix("q="))
+        .find_map(|q| q.trim().parse::<f32>().ok())
+        .unwrap_or(1.0);
+
+    Some((range, quality.clamp(0.0, 1.0)))
+}
+
+// Returns how specific the match is, or `None` if the range doesn't match the offer.
+fn language_match_specificity(range: &str, offer: &str) -> Option<usize> {
+    if range == "*" {
+        return Some(0);
+    }
+
+    let is_prefix_of = |prefix: &str, tag: &str| {
+        tag.len() > prefix.len()
+            && tag.as_bytes()[prefix.len()] == b'-'
+            && tag[..prefix.len()].eq_ignore_ascii_case(prefix)
+    };
+
+    if range.eq_ignore_ascii_case(offer) {
+        Some(2)
+    } else if is_prefix_of(range, offer) || is_prefix_of(offer, range) {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+// Evaluates the `If-Modified-Since` precondition of a `GET` or `HEAD` request. The header is ignored if it's invalid or
+// if `If-None-Match` is present, which takes precedence. The HTTP dates have a one second precision, so the sub-second
+// part of the last modification time is dropped.
+pub(crate) fn not_modified_since(method: &Method, headers: &HeaderMap, last_modified: SystemTime) -> bool {
+    if (method != Method::GET && method != Method::HEAD) || headers.contains_key(http::header::IF_NONE_MATCH) {
+        return false;
+    }
+
+    let Some(since) =
+        header(headers, http::header::IF_MODIFIED_SINCE).and_then(|val| httpdate::parse_http_date(val).ok())
+    else {
+        return false;
+    };
+
+    let last_modified = last_modified
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| UNIX_EPOCH + std::time::Duration::from_secs(elapsed.as_secs()))
+        .unwrap_or(last_modified);
+    last_modified <= since
+}
+
+// Escapes a string to be embedded in a JSON string literal.
+pub(crate) fn escape_json(val: &str) -> String {
+    let mut escaped = String::with_capacity(val.len());
+    for c in val.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Computes the edit distance between two strings, it's used to find the closest matching routes.
+pub(crate) fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<_>>();
+    let mut prev_row = (0..=b_chars.len()).collect::<Vec<_>>();
+    let mut curr_row = vec![0; b_chars.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        curr_row[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            curr_row[j + 1] = (prev_row[j] + substitution_cost)
+                .min(prev_row[j + 1] + 1)
+                .min(curr_row[j] + 1);
+        }
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+
+    prev_row[b_chars.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_levenshtein_distance() {
+        assert_eq!(levenshtein_distance("/users", "/users"), 0);
+        assert_eq!(levenshtein_distance("/users", "/usres"), 2);
+        assert_eq!(levenshtein_distance("/users", "/user"), 1);
+        assert_eq!(levenshtein_distance("", "/abc"), 4);
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_not_modified_since() {
+        let last_modified = UNIX_EPOCH + std::time::Duration::from_millis(1_000_000_000_500);
+        let headers = |val: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(http::header::IF_MODIFIED_SINCE, val.parse().unwrap());
+            headers
+        };
+
+        let same = headers(&httpdate::fmt_http_date(last_modified));
+        assert!(not_modified_since(&Method::GET, &same, last_modified));
+        assert!(not_modified_since(&Method::HEAD, &same, last_modified));
+        assert!(!not_modified_since(&Method::POST, &same, last_modified));
+
+        let earlier = headers(&httpdate::fmt_http_date(
+            UNIX_EPOCH + std::time::Duration::from_secs(999_999_999),
+        ));
+        assert!(!not_modified_since(&Method::GET, &earlier, last_modified));
+
+        assert!(!not_modified_since(&Method::GET, &headers("yesterday"), last_modified));
+        assert!(!not_modified_since(&Method::GET, &HeaderMap::new(), last_modified));
+
+        let mut with_etag = same.clone();
+        with_etag.insert(http::header::IF_NONE_MATCH, "\"abc\"".parse().unwrap());
+        assert!(!not_modified_since(&Method::GET, &with_etag, last_modified));
+    }
+
+    #[test]
+    fn test_escape_json() {
+        assert_eq!(escape_json("plain"), "plain");
+        assert_eq!(escape_json("a \"quoted\" \\ path"), "a \\\"quoted\\\" \\\\ path");
+        assert_eq!(escape_json("line\nbreak\u{1}"), "line\\nbreak\\u0001");
+    }
+
+    #[test]
+    fn test_query_pairs() {
+        assert_eq!(
+            query_pairs("q=rust+router&tag=a%26b&flag&&empty="),
+            vec![
+                ("q".to_owned(), "rust router".to_owned()),
+                ("tag".to_owned(), "a&b".to_owned()),
+                ("flag".to_owned(), String::new()),
+                ("empty".to_owned(), String::new()),
+            ]
+        );
+        assert!(query_pairs("").is_empty());
+    }
+
+    fn accept_language(val: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ACCEPT_LANGUAGE, val.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_preferred_language_quality_ordering() {
+        let headers = accept_language("fr;q=0.5, de;q=0.9, en;q=0.1");
+        assert_eq!(preferred_language(&headers, &["en", "fr", "de"]), Some("de"));
+
+        let headers = accept_language("fr, de;q=0.9");
+        assert_eq!(preferred_language(&headers, &["en", "de", "fr"]), Some("fr"));
+
+        let headers = accept_language("fr;q=0, *;q=0.1");
+        assert_eq!(preferred_language(&headers, &["fr", "de"]), Some("de"));
+
+        let headers = accept_language("ja");
+        assert_eq!(preferred_language(&headers, &["en", "fr"]), None);
+
+        assert_eq!(preferred_language(&HeaderMap::new(), &["en", "fr"]), Some("en"));
+    }
+
+    #[test]
+    fn test_preferred_language_region_fallback() {
+        let headers = accept_language("en-US, fr;q=0.8");
+        assert_eq!(preferred_language(&headers, &["fr", "en"]), Some("en"));
+
+        let headers = accept_language("EN-us;q=0.9, en;q=0.2");
+        assert_eq!(preferred_language(&headers, &["en-GB", "en-US"]), Some("en-US"));
+
+        let headers = accept_language("en");
+        assert_eq!(preferred_language(&headers, &["fr", "en-GB"]), Some("en-GB"));
+
+        let headers = accept_language("eng");
+        assert_eq!(preferred_language(&headers, &["en"]), None);
+    }
+
     #[test]
     fn test_percent_decode_request_path() {
         let val = "/Alice%20John/do something";
@@ -0,0 +1,50 @@
+use crate::ext::RequestExt;
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Request, Response, StatusCode, header};
+use jsonschema::Validator;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+pub(crate) fn compile(schema: &Value) -> crate::Result<Validator> {
+    jsonschema::validator_for(schema).map_err(|err| crate::Error::new(format!("Invalid JSON schema: {}", err)).into())
+}
+
+// Reads the request body, validates it against the schema and deserializes it. The returned
+// error is the response to send instead of running the handler.
+pub(crate) async fn extract<T: DeserializeOwned, B: From<Bytes>>(
+    req: &Request<Full<Bytes>>,
+    validator: &Validator,
+) -> Result<T, Response<B>> {
+    let body = req
+        .body_bytes()
+        .await
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, vec![error_entry("", err)]))?;
+
+    let value: Value = serde_json::from_slice(&body)
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, vec![error_entry("", err)]))?;
+
+    let errors = validator
+        .iter_errors(&value)
+        .map(|err| error_entry(err.instance_path.as_str(), &err))
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(error_response(StatusCode::UNPROCESSABLE_ENTITY, errors));
+    }
+
+    // The schema may accept values which don't fit into the target type.
+    serde_json::from_value(value)
+        .map_err(|err| error_response(StatusCode::UNPROCESSABLE_ENTITY, vec![error_entry("", err)]))
+}
+
+fn error_entry<M: ToString>(path: &str, msg: M) -> Value {
+    json!({ "path": path, "message": msg.to_string() })
+}
+
+fn error_response<B: From<Bytes>>(status: StatusCode, errors: Vec<Value>) -> Response<B> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(B::from(Bytes::from(json!({ "errors": errors }).to_string())))
+        .expect("Couldn't create the JSON validation error response")
+}
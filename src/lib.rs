@@ -6,7 +6,8 @@
 //!
 //! - 🚀 Fast route matching using [`RegexSet`](https://docs.rs/regex/1.4.3/regex/struct.RegexSet.html)
 //!
-//! - 🍺 Route handlers may return any [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html)
+//! - 🍺 Route handlers may return any [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html), e.g. the streaming
+//!   [`Sse`](./body/struct.Sse.html) responses via [`body::BoxBody`](./body/struct.BoxBody.html)
 //!
 //! - ❗ Flexible [error handling](https://github.com/routerify/routerify/blob/master/examples/error_handling_with_request_info.rs) strategy
 //!
@@ -241,10 +242,37 @@
 //! # run();
 //! ```
 //!
+//! The rest of the path matched by a glob, including its slashes, is available as the `*` route parameter. A name
+//! after the `*` captures it under that name instead:
+//!
+//! ```
+//! use http_body_util::Full;
+//! use hyper::{body::Bytes, Response};
+//! use routerify_ng::prelude::*;
+//! use routerify_ng::Router;
+//! # use std::convert::Infallible;
+//!
+//! # fn run() -> Router<Infallible> {
+//! let router = Router::builder()
+//!     .get("/assets/*path", |req| async move {
+//!         // It's `css/main.css` for `/assets/css/main.css`.
+//!         let path = req.param("path").unwrap();
+//!         Ok(Response::new(Full::new(Bytes::from(format!("Asset: {}", path)))))
+//!     })
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
 //! #### Handle 404 Pages
 //!
 //! Here is an example to handle 404 pages.
 //!
+//! Without such a route, a `404 Not Found` response is sent for the non-existent routes, and a `405 Method Not Allowed`
+//! one listing the registered methods in the `Allow` header for the existing paths requested with another method.
+//!
 //! ```
 //! use http_body_util::Full;
 //! use hyper::{body::Bytes, Response, StatusCode};
@@ -312,6 +340,61 @@
 //! # run();
 //! ```
 //!
+//! A route parameter can be constrained by a regex in parentheses right after its name, so that the requests whose
+//! value doesn't match fall through to the other routes. The regex must not have capturing groups, use `(?:...)` instead.
+//! The constraint can also be set for all the routes of a router with
+//! [`RouterBuilder::param_constraint`](./struct.RouterBuilder.html#method.param_constraint).
+//!
+//! ```
+//! use http_body_util::Full;
+//! use hyper::{body::Bytes, Response};
+//! use routerify_ng::prelude::*;
+//! use routerify_ng::Router;
+//! # use std::convert::Infallible;
+//!
+//! # fn run() -> Router<Infallible> {
+//! let router = Router::builder()
+//!     // It matches `/users/42`, but not `/users/alice`.
+//!     .get(r"/users/:userId(\d+)", |req| async move {
+//!         Ok(Response::new(Full::new(Bytes::from(format!("User ID: {}", req.param("userId").unwrap())))))
+//!     })
+//!     .get("/users/:userName", |req| async move {
+//!         Ok(Response::new(Full::new(Bytes::from(format!("Username: {}", req.param("userName").unwrap())))))
+//!     })
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
+//! A route parameter followed by `?` makes its segment optional, including the leading slash. Its value is absent
+//! when the segment is omitted.
+//!
+//! ```
+//! use http_body_util::Full;
+//! use hyper::{body::Bytes, Response};
+//! use routerify_ng::prelude::*;
+//! use routerify_ng::Router;
+//! # use std::convert::Infallible;
+//!
+//! # fn run() -> Router<Infallible> {
+//! let router = Router::builder()
+//!     // It matches both `/files` and `/files/report.pdf`.
+//!     .get("/files/:name?", |req| async move {
+//!         let body = match req.param("name") {
+//!             Some(name) => format!("File: {}", name),
+//!             None => "List of files".to_owned(),
+//!         };
+//!         Ok(Response::new(Full::new(Bytes::from(body))))
+//!     })
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
 //! ### Scoping/Mounting Router
 //!
 //! The `routerify_ng::Router` is a modular, lightweight and mountable router component. A router can be scoped in or mount to a
@@ -539,6 +622,34 @@
 //! # run();
 //! ```
 //!
+//! ### Around Middleware
+//!
+//! The around middlewares wrap the call of the route handler: they receive the request along with a [`Next`](./struct.Next.html)
+//! which runs the rest of the chain and resolves to its response. So, a value e.g. a timer or a database transaction guard can be
+//! shared between the logic before and after the handler without storing it into the request context. They run after the pre
+//! middlewares and before the post middlewares, the first registered one being the outermost.
+//!
+//! ```
+//! use routerify_ng::{Middleware, Router};
+//! use std::convert::Infallible;
+//! use std::time::Instant;
+//!
+//! # fn run() -> Router<Infallible> {
+//! let router = Router::builder()
+//!     .middleware(Middleware::around(|req, next| async move {
+//!         let started = Instant::now();
+//!         // Run the inner around middlewares and the route handler.
+//!         let res = next.run(req).await;
+//!         println!("{} in {:?}", res.status(), started.elapsed());
+//!         Ok(res)
+//!     }))
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
 //! ### The built-in Middleware
 //!
 //! Here is a list of some middlewares which are published in different crates:
@@ -764,29 +875,108 @@
 //! # }
 //! # run();
 //! ```
+//!
+//! ### Scoped Error Handlers
+//!
+//! A router mounted via [`scope`](./struct.RouterBuilder.html#method.scope) can have its own error handler, which handles
+//! the errors of its routes and middlewares, including the ones of its nested scopes without an error handler. The error
+//! of a route or a middleware is handled by the error handler of the innermost scope it was mounted from which has one,
+//! then the root router's one.
+//!
+//! ```
+//! use routerify_ng::Router;
+//! use hyper::{Response, StatusCode, body::Bytes};
+//! use http_body_util::Full;
+//!
+//! # fn run() -> Router<routerify_ng::Error> {
+//! let api = Router::builder()
+//!      .get("/users", |_| async move { Err(routerify_ng::Error::new("Database is down")) })
+//!      .err_handler(|err| async move {
+//!          Response::builder()
+//!            .status(StatusCode::INTERNAL_SERVER_ERROR)
+//!            .header("content-type", "application/json")
+//!            .body(Full::new(Bytes::from(format!("{{\"error\":\"{}\"}}", err))))
+//!            .unwrap()
+//!      })
+//!      .build()
+//!      .unwrap();
+//!
+//! let router = Router::builder()
+//!      // The errors of the `/api` routes are rendered as JSON, the other ones as HTML.
+//!      .scope("/api", api)
+//!      .err_handler(|_| async move {
+//!          Response::builder()
+//!            .status(StatusCode::INTERNAL_SERVER_ERROR)
+//!            .header("content-type", "text/html")
+//!            .body(Full::new(Bytes::from("<h1>Something went wrong</h1>")))
+//!            .unwrap()
+//!      })
+//!      .build()
+//!      .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
 
-pub use self::error::{Error, RouteError};
-pub use self::middleware::{Middleware, PostMiddleware, PreMiddleware};
+#[cfg(feature = "json")]
+pub use self::error::BodyError;
+#[cfg(feature = "serde")]
+pub use self::error::QueryError;
+pub use self::error::{Error, ErrorStatus, ParamError, RouteError};
+pub use self::maintenance::Maintenance;
+pub use self::metrics::MetricsRecorder;
+pub use self::middleware::{AroundMiddleware, Middleware, Next, PostMiddleware, PreMiddleware};
+pub use self::response::{
+    RetryAfter, add_preload, append_vary, attachment, long_poll, not_acceptable, not_modified, or_response,
+    redirect_permanent, redirect_to, set_retry_after,
+};
 pub use self::route::Route;
 pub use self::router::{Router, RouterBuilder};
 #[doc(hidden)]
 pub use self::service::RequestService;
 pub use self::service::RequestServiceBuilder;
-pub use self::service::RouterService;
-pub use self::types::{RequestInfo, RouteParams};
+pub use self::service::{IntoMakeService, RouterService, ShutdownHandle};
+pub use self::service::{serve, serve_with_graceful_shutdown};
+#[cfg(feature = "client-cert")]
+pub use self::types::ClientCert;
+pub use self::types::{
+    BuildReport, Cookie, CookieJar, ForwardedHeader, LogField, MiddlewareInfo, RemoteAddr, RequestContext, RequestInfo,
+    RequestInfoBuilder, ResponseSource, RouteInfo, RouteParams, TraceContext, TrailingSlashPolicy, TrustConfig,
+};
 
+pub mod body;
 mod constants;
 mod data_map;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "decompression")]
+mod decompression;
 mod error;
 pub mod ext;
 mod helpers;
+#[cfg(feature = "json-schema")]
+mod json_schema;
+mod macros;
+mod maintenance;
+mod method_override;
+mod metrics;
 mod middleware;
 pub mod prelude;
+mod proxy;
 mod regex_generator;
+mod response;
 mod route;
 mod router;
 mod service;
+mod static_files;
 mod types;
 
+// The items used by the exported macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::regex_generator::is_valid_route_path;
+    pub use hyper::Method;
+}
+
 /// A Result type often returned from methods that can have routerify errors.
 pub type Result<T> = std::result::Result<T, RouteError>;
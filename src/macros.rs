@@ -0,0 +1,65 @@
+/// Creates a [`RouterBuilder`](./struct.RouterBuilder.html) from a list of `METHOD "path" => handler` entries, checking
+/// at compile time that the paths are well-formed.
+///
+/// The method is any of the [`hyper::Method`](https://docs.rs/http/1/http/method/struct.Method.html) constants e.g.
+/// `GET` or `POST`, and each entry expands to a call to the [`add`](./struct.RouterBuilder.html#method.add) method. The
+/// route parameter names must match `[A-Za-z_][A-Za-z0-9_]*` and be unique within a path, and `*` must be a whole path
+/// segment, otherwise the compilation fails.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Request, Response};
+/// use routerify_ng::{routes, Router};
+/// use std::convert::Infallible;
+///
+/// async fn user_handler(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, Infallible> {
+///     Ok(Response::new(Full::new(Bytes::from("User"))))
+/// }
+///
+/// async fn create_handler(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, Infallible> {
+///     Ok(Response::new(Full::new(Bytes::from("Created"))))
+/// }
+///
+/// fn run() -> Router<Infallible> {
+///     let router = routes! {
+///         GET "/users/:id" => user_handler,
+///         POST "/users" => create_handler,
+///     }
+///     .build()
+///     .unwrap();
+///     router
+/// }
+/// ```
+///
+/// A malformed path doesn't compile:
+///
+/// ```compile_fail
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Request, Response};
+/// use routerify_ng::{routes, Router};
+/// use std::convert::Infallible;
+///
+/// async fn user_handler(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, Infallible> {
+///     Ok(Response::new(Full::new(Bytes::from("User"))))
+/// }
+///
+/// fn run() -> Router<Infallible> {
+///     routes! { GET "/users/:user-id" => user_handler }.build().unwrap()
+/// }
+/// ```
+#[macro_export]
+macro_rules! routes {
+    ($($method:ident $path:literal => $handler:expr),* $(,)?) => {{
+        $(
+            const _: () = ::std::assert!(
+                $crate::__private::is_valid_route_path($path),
+                ::std::concat!("Invalid route path: ", $path)
+            );
+        )*
+
+        $crate::Router::builder()
+            $(.add($path, ::std::vec![$crate::__private::Method::$method], $handler))*
+    }};
+}
@@ -0,0 +1,112 @@
+use crate::response::{RetryAfter, set_retry_after};
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode, header};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A handle to toggle the maintenance mode of a router at runtime. While it's enabled, the router answers every
+/// request with `503 Service Unavailable` without matching the routes, except the requests to the exempted paths,
+/// e.g. the health checks.
+///
+/// It can be set via the [`RouterBuilder`](./struct.RouterBuilder.html) method
+/// [`maintenance`](./struct.RouterBuilder.html#method.maintenance) and retrieved back from the
+/// [`RouterService`](./struct.RouterService.html) via [`maintenance`](./struct.RouterService.html#method.maintenance).
+/// The clones share the same flag.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::{Maintenance, Router, RouterService};
+/// use std::convert::Infallible;
+/// use std::time::Duration;
+///
+/// fn run() -> RouterService<Infallible> {
+///     let router = Router::builder()
+///         .maintenance(
+///             Maintenance::new()
+///                 .retry_after(Duration::from_secs(120))
+///                 .body("Down for maintenance")
+///                 .exempt("/healthz"),
+///         )
+///         .get("/healthz", |_| async move { Ok(Response::new(Full::new(Bytes::from("OK")))) })
+///         .build()
+///         .unwrap();
+///
+///     let service = RouterService::new(router).unwrap();
+///     service.maintenance().enable();
+///     service
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Maintenance {
+    enabled: Arc<AtomicBool>,
+    retry_after: Option<RetryAfter>,
+    body: Bytes,
+    exempt_paths: Vec<String>,
+}
+
+impl Maintenance {
+    /// Creates a disabled maintenance mode with an empty response body and no exempted paths.
+    pub fn new() -> Maintenance {
+        Maintenance::default()
+    }
+
+    /// Sets the `Retry-After` header of the `503 Service Unavailable` responses, either as a delay in whole seconds or as
+    /// the time the maintenance is expected to end, see [`RetryAfter`](./enum.RetryAfter.html).
+    pub fn retry_after<R: Into<RetryAfter>>(mut self, retry_after: R) -> Self {
+        self.retry_after = Some(retry_after.into());
+        self
+    }
+
+    /// Sets the body of the `503 Service Unavailable` responses, which is sent as `text/plain`.
+    pub fn body<B: Into<Bytes>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Exempts the requests to the specified path from the maintenance mode, e.g. the health checks.
+    pub fn exempt<P: Into<String>>(mut self, path: P) -> Self {
+        self.exempt_paths.push(path.into());
+        self
+    }
+
+    /// Enables the maintenance mode.
+    pub fn enable(&self) {
+        self.set_enabled(true);
+    }
+
+    /// Disables the maintenance mode.
+    pub fn disable(&self) {
+        self.set_enabled(false);
+    }
+
+    /// Enables or disables the maintenance mode.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns whether the maintenance mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    // Returns the `503 Service Unavailable` response if the request to the path must be rejected.
+    pub(crate) fn check<B: From<Bytes>>(&self, path: &str) -> Option<Response<B>> {
+        if !self.is_enabled() || self.exempt_paths.iter().any(|exempt_path| exempt_path == path) {
+            return None;
+        }
+
+        let mut res = Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(B::from(self.body.clone()))
+            .expect("Couldn't create the maintenance response");
+        if let Some(retry_after) = self.retry_after {
+            set_retry_after(&mut res, retry_after);
+        }
+
+        Some(res)
+    }
+}
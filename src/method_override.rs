@@ -0,0 +1,105 @@
+use crate::types::BufferedBody;
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderMap};
+use hyper::{Method, Request};
+use percent_encoding::percent_decode;
+
+const HEADER_NAME: &str = "x-http-method-override";
+const FORM_FIELD: &str = "_method";
+
+// Rewrites the method of a `POST` request to the one requested by the `X-HTTP-Method-Override`
+// header or, for the buffered form bodies, by the `_method` field. Only the methods an HTML form
+// can't send are accepted, the other values leave the request untouched.
+pub(crate) fn apply(req: &mut Request<Full<Bytes>>) {
+    if req.method() != Method::POST {
+        return;
+    }
+
+    let requested = header_method(req.headers()).or_else(|| {
+        let body = req.extensions().get::<BufferedBody>()?;
+        form_method(req.headers(), &body.0)
+    });
+
+    if let Some(method) = requested.and_then(allowed_method) {
+        *req.method_mut() = method;
+    }
+}
+
+fn header_method(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(HEADER_NAME)
+        .and_then(|val| val.to_str().ok())
+        .map(|val| val.trim().to_owned())
+}
+
+fn form_method(headers: &HeaderMap, body: &[u8]) -> Option<String> {
+    let is_form = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/x-www-form-urlencoded"));
+    if !is_form {
+        return None;
+    }
+
+    body.split(|b| *b == b'&')
+        .filter_map(|pair| {
+            let mut pair = pair.splitn(2, |b| *b == b'=');
+            Some((pair.next()?, pair.next()?))
+        })
+        .find(|(name, _)| *name == FORM_FIELD.as_bytes())
+        .and_then(|(_, value)| percent_decode(value).decode_utf8().ok())
+        .map(|value| value.trim().to_owned())
+}
+
+fn allowed_method(name: String) -> Option<Method> {
+    match name.to_ascii_uppercase().as_str() {
+        "PUT" => Some(Method::PUT),
+        "PATCH" => Some(Method::PATCH),
+        "DELETE" => Some(Method::DELETE),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, content_type: Option<&str>, body: &'static str) -> Request<Full<Bytes>> {
+        let mut builder = Request::builder().method(method).uri("/");
+        if let Some(content_type) = content_type {
+            builder = builder.header(header::CONTENT_TYPE, content_type);
+        }
+        let mut req = builder.body(Full::new(Bytes::from_static(body.as_bytes()))).unwrap();
+        req.extensions_mut()
+            .insert(BufferedBody(Bytes::from_static(body.as_bytes())));
+        req
+    }
+
+    #[test]
+    fn reads_form_field() {
+        let mut req = request(
+            "POST",
+            Some("application/x-www-form-urlencoded; charset=utf-8"),
+            "a=1&_method=patch",
+        );
+        apply(&mut req);
+        assert_eq!(req.method(), Method::PATCH);
+    }
+
+    #[test]
+    fn ignores_non_form_bodies() {
+        let mut req = request("POST", Some("text/plain"), "_method=DELETE");
+        apply(&mut req);
+        assert_eq!(req.method(), Method::POST);
+    }
+
+    #[test]
+    fn ignores_disallowed_methods() {
+        let mut req = request("POST", None, "");
+        req.headers_mut().insert(HEADER_NAME, "CONNECT".parse().unwrap());
+        apply(&mut req);
+        assert_eq!(req.method(), Method::POST);
+    }
+}
@@ -0,0 +1,48 @@
+use hyper::{Method, StatusCode};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Receives the request lifecycle events of a router, so that they can be recorded by any metrics backend e.g.
+/// Prometheus or statsd.
+///
+/// It can be set via the [`RouterBuilder`](./struct.RouterBuilder.html) method [`metrics`](./struct.RouterBuilder.html#method.metrics).
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{Method, StatusCode};
+/// use routerify_ng::MetricsRecorder;
+/// use std::time::Duration;
+///
+/// struct LogRecorder;
+///
+/// impl MetricsRecorder for LogRecorder {
+///     fn on_request_end(&self, status: StatusCode, duration: Duration, route: Option<&str>) {
+///         println!("{} {} in {:?}", route.unwrap_or("unmatched"), status, duration);
+///     }
+/// }
+/// ```
+pub trait MetricsRecorder: Send + Sync + 'static {
+    /// Called when the router starts processing a request, with the request method and path.
+    fn on_request_start(&self, _method: &Method, _path: &str) {}
+
+    /// Called when the router is done processing a request, with the response status, the time the request took
+    /// and the path template of the route which handled it, e.g. `/users/:id`.
+    ///
+    /// The status is `500 Internal Server Error` when the request fails without the error handler producing a response.
+    fn on_request_end(&self, status: StatusCode, duration: Duration, route: Option<&str>);
+}
+
+// The path template of the route which handled the request, filled in by the route itself.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MatchedRoute(Arc<OnceLock<String>>);
+
+impl MatchedRoute {
+    pub(crate) fn set(&self, path: &str) {
+        let _ = self.0.set(path.to_owned());
+    }
+
+    pub(crate) fn get(&self) -> Option<&str> {
+        self.0.get().map(String::as_str)
+    }
+}
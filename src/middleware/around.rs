@@ -0,0 +1,179 @@
+use crate::Error;
+use crate::data_map::{PrivateDataMaps, SharedDataMap};
+use crate::regex_generator::generate_exact_match_regex;
+use crate::router::ErrHandler;
+use http_body_util::Full;
+use hyper::body::{Body, Bytes};
+use hyper::{Request, Response, StatusCode};
+use regex::Regex;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+type Handler<E, B> = Box<dyn Fn(Request<Full<Bytes>>, Next<B>) -> HandlerReturn<E, B> + Send + Sync + 'static>;
+type HandlerReturn<E, B> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
+
+pub(crate) type RequestReceiver = oneshot::Receiver<Request<Full<Bytes>>>;
+pub(crate) type ResponseSender<B> = oneshot::Sender<Response<B>>;
+
+/// The around middleware type, which wraps the call of the route handler. Refer to
+/// [`Middleware::around`](./enum.Middleware.html#method.around) for more info.
+///
+/// This `AroundMiddleware<E, B>` type accepts two type parameters: `E` and `B`.
+///
+/// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html) trait with `Bytes` data. It defaults to
+///   [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html).
+pub struct AroundMiddleware<E, B = Full<Bytes>> {
+    pub(crate) path: String,
+    pub(crate) regex: Regex,
+    // Make it an option so that when a router is used to scope in another router,
+    // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
+    pub(crate) handler: Option<Handler<E, B>>,
+    // Scope depth with regards to the top level router.
+    pub(crate) scope_depth: u32,
+    // Middleware with lower priorities wrap the ones with higher priorities.
+    pub(crate) priority: i32,
+    // The data which is visible only within the router this middleware was added to.
+    pub(crate) private_data_maps: Vec<SharedDataMap>,
+    // The name of the middleware, see `Middleware::named`.
+    pub(crate) name: Option<String>,
+    // The label to remove the middleware by, see `RouterBuilder::middleware_labeled`.
+    pub(crate) label: Option<String>,
+    // The error handler of the innermost scope which has one, see `RouterBuilder::scope`.
+    pub(crate) scope_err_handler: Option<Arc<ErrHandler<B>>>,
+}
+
+impl<E, B> AroundMiddleware<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    pub(crate) fn new_with_boxed_handler<P: Into<String>>(
+        path: P,
+        handler: Handler<E, B>,
+        scope_depth: u32,
+    ) -> crate::Result<AroundMiddleware<E, B>> {
+        let path = path.into();
+        let (re, _) = generate_exact_match_regex(path.as_str()).map_err(|e| {
+            Error::new(format!(
+                "Could not create an exact match regex for the around middleware path: {}",
+                e
+            ))
+        })?;
+
+        Ok(AroundMiddleware {
+            path,
+            regex: re,
+            handler: Some(handler),
+            scope_depth,
+            priority: 0,
+            private_data_maps: Vec::new(),
+            name: None,
+            label: None,
+            scope_err_handler: None,
+        })
+    }
+
+    /// Creates an around middleware with a handler at the specified path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{AroundMiddleware, Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::Around(
+    ///             AroundMiddleware::new("/abc", |req, next| async move {
+    ///                 /* Do some operations */
+    ///                 Ok(next.run(req).await)
+    ///             })
+    ///             .unwrap(),
+    ///         ))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<AroundMiddleware<E, B>>
+    where
+        P: Into<String>,
+        H: Fn(Request<Full<Bytes>>, Next<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let handler: Handler<E, B> = Box::new(move |req, next| Box::new(handler(req, next)));
+        AroundMiddleware::new_with_boxed_handler(path, handler, 1)
+    }
+
+    pub(crate) fn process(
+        &self,
+        mut req: Request<Full<Bytes>>,
+        next: Next<B>,
+    ) -> impl Future<Output = crate::Result<Response<B>>> + Send + 'static {
+        req.extensions_mut()
+            .insert(PrivateDataMaps(self.private_data_maps.clone()));
+
+        let handler = self
+            .handler
+            .as_ref()
+            .expect("A router can not be used after mounting into another router");
+
+        let fut = Pin::from(handler(req, next));
+        async move { fut.await.map_err(Into::into) }
+    }
+}
+
+impl<E, B> Debug for AroundMiddleware<E, B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
+    }
+}
+
+/// The rest of the chain wrapped by an [around middleware](./struct.AroundMiddleware.html), i.e. the inner around
+/// middlewares and the route handler.
+///
+/// It's consumed by [`run`](#method.run), so the chain can be run only once per request.
+pub struct Next<B = Full<Bytes>> {
+    req_tx: oneshot::Sender<Request<Full<Bytes>>>,
+    res_rx: oneshot::Receiver<Response<B>>,
+}
+
+impl<B: From<Bytes>> Next<B> {
+    // Creates the handle passed to an around middleware along with the router's ends of it.
+    pub(crate) fn channel() -> (Next<B>, RequestReceiver, ResponseSender<B>) {
+        let (req_tx, req_rx) = oneshot::channel();
+        let (res_tx, res_rx) = oneshot::channel();
+        (Next { req_tx, res_rx }, req_rx, res_tx)
+    }
+
+    /// Runs the rest of the chain with the request and resolves to its response.
+    ///
+    /// An error raised by the route handler or by an inner around middleware is already turned into a response by the
+    /// error handler. A `500 Internal Server Error` response is returned if the chain can't be run, e.g. when it's
+    /// awaited after the around middleware has returned.
+    pub async fn run(self, req: Request<Full<Bytes>>) -> Response<B> {
+        if self.req_tx.send(req).is_err() {
+            return internal_server_error();
+        }
+
+        self.res_rx.await.unwrap_or_else(|_| internal_server_error())
+    }
+}
+
+impl<B> Debug for Next<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Next {{ .. }}")
+    }
+}
+
+pub(crate) fn internal_server_error<B: From<Bytes>>() -> Response<B> {
+    Response::builder()
+        .status(StatusCode::INTERNAL_SERVER_ERROR)
+        .body(B::from(Bytes::new()))
+        .expect("Couldn't create the internal server error response")
+}
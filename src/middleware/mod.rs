@@ -1,33 +1,46 @@
-use crate::types::RequestInfo;
-use http_body_util::Full;
-use hyper::Response;
-use hyper::body::Bytes;
+use crate::body::BoxBody;
+use crate::ext::RequestExt;
+use crate::response;
+use crate::types::{LogField, RequestInfo, TraceContext};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body, Bytes};
+use hyper::{Response, header};
 use std::future::Future;
+use std::sync::Arc;
 
+pub use self::around::{AroundMiddleware, Next};
 pub use self::post::PostMiddleware;
 pub use self::pre::PreMiddleware;
 
+pub(crate) mod around;
 mod post;
 mod pre;
 
 /// Enum type for all the middleware types. Please refer to the [Middleware](./index.html#middleware) for more info.
 ///
-/// This `Middleware<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Middleware<E, B>` type accepts two type parameters: `E` and `B`.
 ///
-/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
-///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
-///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html) trait with `Bytes` data. It defaults to
+///   [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html).
 #[derive(Debug)]
-pub enum Middleware<E> {
+pub enum Middleware<E, B = Full<Bytes>> {
     /// Variant for the pre middleware. Refer to [Pre Middleware](./index.html#pre-middleware) for more info.
-    Pre(PreMiddleware<E>),
+    Pre(PreMiddleware<E, B>),
 
     /// Variant for the post middleware. Refer to [Post Middleware](./index.html#post-middleware) for more info.
-    Post(PostMiddleware<E>),
+    Post(PostMiddleware<E, B>),
+
+    /// Variant for the around middleware. Refer to [`Middleware::around`](#method.around) for more info.
+    Around(AroundMiddleware<E, B>),
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E> {
+impl<E, B> Middleware<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     /// Creates a pre middleware with a handler at the `/*` path.
     ///
     /// # Examples
@@ -48,7 +61,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E>
     ///     router
     /// }
     /// ```
-    pub fn pre<H, R>(handler: H) -> Middleware<E>
+    pub fn pre<H, R>(handler: H) -> Middleware<E, B>
     where
         H: Fn(hyper::Request<Full<Bytes>>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<hyper::Request<Full<Bytes>>, E>> + Send + 'static,
@@ -76,14 +89,50 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E>
     ///     router
     /// }
     /// ```
-    pub fn post<H, R>(handler: H) -> Middleware<E>
+    pub fn post<H, R>(handler: H) -> Middleware<E, B>
     where
-        H: Fn(Response<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Middleware::post_with_path("/*", handler).unwrap()
     }
 
+    /// Creates an around middleware with a handler at the `/*` path, which wraps the call of the route handler. The
+    /// handler receives the request and a [`Next`](./struct.Next.html) to run the rest of the chain with, so the
+    /// values like a timer or a transaction guard can be shared between the logic before and after it.
+    ///
+    /// The around middlewares run after the pre middlewares and before the post middlewares. The first registered one
+    /// is the outermost, unless reordered by [`around_with_priority`](#method.around_with_priority). An around
+    /// middleware can respond without calling `next`, in which case the inner ones and the route handler are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    /// use std::time::Instant;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::around(|req, next| async move {
+    ///             let started = Instant::now();
+    ///             let res = next.run(req).await;
+    ///             println!("Handled with {} in {:?}", res.status(), started.elapsed());
+    ///             Ok(res)
+    ///         }))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn around<H, R>(handler: H) -> Middleware<E, B>
+    where
+        H: Fn(hyper::Request<Full<Bytes>>, Next<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        Middleware::around_with_path("/*", handler).unwrap()
+    }
+
     /// Creates a post middleware which can access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc. It should be used when the post middleware trandforms the response based on
     /// the request information.
     ///
@@ -115,10 +164,10 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E>
     ///     router
     /// }
     /// ```
-    pub fn post_with_info<H, R>(handler: H) -> Middleware<E>
+    pub fn post_with_info<H, R>(handler: H) -> Middleware<E, B>
     where
-        H: Fn(Response<Full<Bytes>>, RequestInfo) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Middleware::post_with_info_with_path("/*", handler).unwrap()
     }
@@ -146,7 +195,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E>
     ///     router
     /// }
     /// ```
-    pub fn pre_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<E>>
+    pub fn pre_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<E, B>>
     where
         P: Into<String>,
         H: Fn(hyper::Request<Full<Bytes>>) -> R + Send + Sync + 'static,
@@ -178,15 +227,47 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E>
     ///     router
     /// }
     /// ```
-    pub fn post_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<E>>
+    pub fn post_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<E, B>>
     where
         P: Into<String>,
-        H: Fn(Response<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Ok(Middleware::Post(PostMiddleware::new(path, handler)?))
     }
 
+    /// Creates an around middleware with a handler at the specified path. Please refer to [`around`](#method.around)
+    /// for more info.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(
+    ///             Middleware::around_with_path("/my-path", |req, next| async move {
+    ///                 /* Do some operations */
+    ///                 Ok(next.run(req).await)
+    ///             })
+    ///             .unwrap(),
+    ///         )
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn around_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<E, B>>
+    where
+        P: Into<String>,
+        H: Fn(hyper::Request<Full<Bytes>>, Next<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        Ok(Middleware::Around(AroundMiddleware::new(path, handler)?))
+    }
+
     /// Creates a post middleware which can access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc. It should be used when the post middleware trandforms the response based on
     /// the request information.
     ///
@@ -218,12 +299,411 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E>
     ///     router
     /// }
     /// ```
-    pub fn post_with_info_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<E>>
+    pub fn post_with_info_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<E, B>>
     where
         P: Into<String>,
-        H: Fn(Response<Full<Bytes>>, RequestInfo) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Ok(Middleware::Post(PostMiddleware::new_with_info(path, handler)?))
     }
+
+    /// Creates a pre middleware with a handler at the specified path and an explicit priority.
+    ///
+    /// The middleware with lower priorities are executed first, the ones with equal priorities are executed in the
+    /// registration order. The middleware created by the other methods have the priority `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(
+    ///             Middleware::pre_with_priority("/*", 20, |req| async move {
+    ///                 /* Rate limit the request */
+    ///                 Ok(req)
+    ///             })
+    ///             .unwrap(),
+    ///         )
+    ///         // It's executed first despite being registered after the above one.
+    ///         .middleware(
+    ///             Middleware::pre_with_priority("/*", 10, |req| async move {
+    ///                 /* Authenticate the request */
+    ///                 Ok(req)
+    ///             })
+    ///             .unwrap(),
+    ///         )
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn pre_with_priority<P, H, R>(path: P, priority: i32, handler: H) -> crate::Result<Middleware<E, B>>
+    where
+        P: Into<String>,
+        H: Fn(hyper::Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<hyper::Request<Full<Bytes>>, E>> + Send + 'static,
+    {
+        let mut middleware = PreMiddleware::new(path, handler)?;
+        middleware.priority = priority;
+        Ok(Middleware::Pre(middleware))
+    }
+
+    /// Creates a post middleware with a handler at the specified path and an explicit priority.
+    ///
+    /// The middleware with lower priorities are executed first, the ones with equal priorities are executed in the
+    /// registration order. The middleware created by the other methods have the priority `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(
+    ///             Middleware::post_with_priority("/*", -1, |res| async move {
+    ///                 /* Do some operations */
+    ///                 Ok(res)
+    ///             })
+    ///             .unwrap(),
+    ///         )
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn post_with_priority<P, H, R>(path: P, priority: i32, handler: H) -> crate::Result<Middleware<E, B>>
+    where
+        P: Into<String>,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let mut middleware = PostMiddleware::new(path, handler)?;
+        middleware.priority = priority;
+        Ok(Middleware::Post(middleware))
+    }
+
+    /// Creates a post middleware which can access [request info](./struct.RequestInfo.html) with a handler at the specified
+    /// path and an explicit priority. Please refer to [`post_with_priority`](#method.post_with_priority) for the ordering rules.
+    pub fn post_with_info_with_priority<P, H, R>(path: P, priority: i32, handler: H) -> crate::Result<Middleware<E, B>>
+    where
+        P: Into<String>,
+        H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let mut middleware = PostMiddleware::new_with_info(path, handler)?;
+        middleware.priority = priority;
+        Ok(Middleware::Post(middleware))
+    }
+
+    /// Creates an around middleware with a handler at the specified path and an explicit priority. The middleware
+    /// with lower priorities wrap the ones with higher priorities, the ones with equal priorities are nested in the
+    /// registration order. The middleware created by the other methods have the priority `0`.
+    pub fn around_with_priority<P, H, R>(path: P, priority: i32, handler: H) -> crate::Result<Middleware<E, B>>
+    where
+        P: Into<String>,
+        H: Fn(hyper::Request<Full<Bytes>>, Next<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let mut middleware = AroundMiddleware::new(path, handler)?;
+        middleware.priority = priority;
+        Ok(Middleware::Around(middleware))
+    }
+
+    /// Names the middleware, e.g. in the `Server-Timing` header enabled via the [`RouterBuilder`](./struct.RouterBuilder.html)
+    /// method [`server_timing`](./struct.RouterBuilder.html#method.server_timing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .server_timing(true)
+    ///         .middleware(Middleware::pre(|req| async move { Ok(req) }).named("mw_auth"))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn named<N: Into<String>>(mut self, name: N) -> Self {
+        let name = Some(name.into());
+        match self {
+            Middleware::Pre(ref mut middleware) => middleware.name = name,
+            Middleware::Post(ref mut middleware) => middleware.name = name,
+            Middleware::Around(ref mut middleware) => middleware.name = name,
+        }
+        self
+    }
+
+    /// Creates a post middleware at the `/*` path which adds a `Link` preload entry for each of the `(path, as_type)`
+    /// pairs to the HTML responses, i.e. the ones with a `text/html` content type. Please refer to
+    /// [`add_preload`](./fn.add_preload.html) for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::preload([("/style.css", "style"), ("/app.js", "script")]))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn preload<I, P, A>(links: I) -> Middleware<E, B>
+    where
+        I: IntoIterator<Item = (P, A)>,
+        P: Into<String>,
+        A: Into<String>,
+    {
+        let links = Arc::new(
+            links
+                .into_iter()
+                .map(|(path, as_type)| (path.into(), as_type.into()))
+                .collect::<Vec<(String, String)>>(),
+        );
+
+        Middleware::post(move |mut res: Response<B>| {
+            let links = links.clone();
+
+            async move {
+                if response::is_html(&res) {
+                    for (path, as_type) in links.iter() {
+                        response::add_preload(&mut res, path, as_type);
+                    }
+                }
+                Ok(res)
+            }
+        })
+    }
+
+    /// Creates a pre middleware at the `/*` path which parses the W3C `traceparent` and `tracestate` headers and stores
+    /// the resulting [`TraceContext`](./struct.TraceContext.html) into the request context, so that it can be accessed
+    /// as `req.trace_context()` and propagated to the outgoing calls.
+    ///
+    /// A new trace is started when the `traceparent` header is missing or malformed, and the `tracestate` header is
+    /// ignored in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::trace_context())
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn trace_context() -> Middleware<E, B> {
+        Middleware::pre(|req: hyper::Request<Full<Bytes>>| async move {
+            let parsed =
+                req.header("traceparent")
+                    .and_then(TraceContext::parse)
+                    .map(|ctx| match req.header_all("tracestate") {
+                        states if states.is_empty() => ctx,
+                        states => ctx.with_trace_state(states.join(",")),
+                    });
+
+            req.set_context(parsed.unwrap_or_else(TraceContext::new_root));
+            Ok(req)
+        })
+    }
+
+    /// Creates a post middleware at the `/*` path which emits a structured record of each request to the `sink`, as
+    /// the key/value pairs of the selected [`fields`](./enum.LogField.html) in the given order, e.g. to feed a JSON
+    /// logger. The fields which aren't available for a request, e.g. the `request_id` without the `X-Request-Id`
+    /// header, are omitted.
+    ///
+    /// The record is emitted when the post middleware runs, so the duration doesn't include the post middlewares
+    /// added after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{LogField, Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::structured_log(
+    ///             [LogField::Method, LogField::MatchedRoute, LogField::Status, LogField::Duration],
+    ///             |record| {
+    ///                 let line = record
+    ///                     .iter()
+    ///                     .map(|(key, value)| format!("{}={}", key, value))
+    ///                     .collect::<Vec<_>>();
+    ///                 println!("{}", line.join(" "));
+    ///             },
+    ///         ))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn structured_log<I, S>(fields: I, sink: S) -> Middleware<E, B>
+    where
+        I: IntoIterator<Item = LogField>,
+        S: Fn(&[(&'static str, String)]) + Send + Sync + 'static,
+    {
+        let fields = fields.into_iter().collect::<Vec<_>>();
+
+        Middleware::post_with_info(move |res: Response<B>, req_info: RequestInfo| {
+            let record = fields
+                .iter()
+                .filter_map(|field| field.value(&res, &req_info).map(|value| (field.key(), value)))
+                .collect::<Vec<_>>();
+            sink(&record);
+
+            async move { Ok(res) }
+        })
+    }
+
+    /// Creates a pre middleware at the `/*` path which lets the clients limited to `GET` and `POST`, e.g. the HTML forms,
+    /// send a `PUT`, `PATCH` or `DELETE` request. The method of a `POST` request is replaced by the one in the
+    /// `X-HTTP-Method-Override` header or, for a buffered `application/x-www-form-urlencoded` body, in the `_method`
+    /// field. The other methods are ignored.
+    ///
+    /// The method is replaced before the request is routed, so the request is handled by the routes and the
+    /// middlewares registered for the overriding method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(Middleware::method_override())
+    ///         .delete("/posts/:id", |_| async move { Ok(hyper::Response::new("Deleted".into())) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn method_override() -> Middleware<E, B> {
+        let mut middleware = PreMiddleware::new("/*", |req| async move { Ok(req) })
+            .expect("Couldn't create the method override middleware");
+        middleware.overrides_method = true;
+        Middleware::Pre(middleware)
+    }
+}
+
+impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Middleware<E> {
+    /// Creates a post middleware at the specified path which transforms the response body with the `mapper`, e.g. to
+    /// count the lines or to encrypt it.
+    ///
+    /// The `Content-Length` header set by the handler, if any, is removed as the mapped body may have a different
+    /// length, so that it's computed from the mapped body instead. The streamed bodies of the other body types are
+    /// transformed via [`post_map_frames`](#method.post_map_frames) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::body::{Body, Bytes};
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .middleware(
+    ///             Middleware::post_map_body("/shout/*", |body: Bytes| Bytes::from(body.to_ascii_uppercase())).unwrap(),
+    ///         )
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn post_map_body<P, F>(path: P, mapper: F) -> crate::Result<Middleware<E>>
+    where
+        P: Into<String>,
+        F: Fn(Bytes) -> Bytes + Send + Sync + 'static,
+    {
+        let mapper = Arc::new(mapper);
+
+        Middleware::post_with_path(path, move |res: Response<Full<Bytes>>| {
+            let mapper = mapper.clone();
+
+            async move {
+                let (mut parts, body) = res.into_parts();
+                let body = match body.collect().await {
+                    Ok(collected) => collected.to_bytes(),
+                    Err(never) => match never {},
+                };
+
+                parts.headers.remove(header::CONTENT_LENGTH);
+                Ok(Response::from_parts(parts, Full::new(mapper(body))))
+            }
+        })
+    }
+}
+
+impl<E, B> Middleware<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + From<BoxBody> + Send + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    /// Creates a post middleware at the specified path which transforms each data frame of the response body with the
+    /// `mapper` as it's streamed, so that it works with any body, e.g. a [`BoxBody`](./body/struct.BoxBody.html)
+    /// wrapping an [`SseBody`](./body/struct.SseBody.html) or a [`from_reader`](./body/fn.from_reader.html) one,
+    /// without buffering it. Unlike [`post_map_body`](#method.post_map_body), the `mapper` sees the chunks as the
+    /// handler produces them rather than the whole body.
+    ///
+    /// The `Content-Length` header set by the handler, if any, is removed as the mapped body may have a different
+    /// length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::body::Bytes;
+    /// use routerify_ng::body::BoxBody;
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible, BoxBody> {
+    ///     let router = Router::builder()
+    ///         .middleware(
+    ///             Middleware::post_map_frames("/shout/*", |chunk: Bytes| Bytes::from(chunk.to_ascii_uppercase()))
+    ///                 .unwrap(),
+    ///         )
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn post_map_frames<P, F>(path: P, mapper: F) -> crate::Result<Middleware<E, B>>
+    where
+        P: Into<String>,
+        F: Fn(Bytes) -> Bytes + Send + Sync + 'static,
+    {
+        let mapper = Arc::new(mapper);
+
+        Middleware::post_with_path(path, move |res: Response<B>| {
+            let mapper = mapper.clone();
+
+            async move {
+                let (mut parts, body) = res.into_parts();
+                let body = body.map_frame(move |frame| frame.map_data(|data| mapper(data)));
+
+                parts.headers.remove(header::CONTENT_LENGTH);
+                Ok(Response::from_parts(parts, B::from(BoxBody::new(body))))
+            }
+        })
+    }
 }
@@ -1,50 +1,67 @@
 use crate::Error;
+use crate::data_map::SharedDataMap;
 use crate::regex_generator::generate_exact_match_regex;
+use crate::router::ErrHandler;
 use crate::types::RequestInfo;
 use http_body_util::Full;
 use hyper::Response;
-use hyper::body::Bytes;
+use hyper::body::{Body, Bytes};
 use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
-type HandlerWithoutInfo<E> = Box<dyn Fn(Response<Full<Bytes>>) -> HandlerWithoutInfoReturn<E> + Send + Sync + 'static>;
-type HandlerWithoutInfoReturn<E> = Box<dyn Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static>;
+type HandlerWithoutInfo<E, B> = Box<dyn Fn(Response<B>) -> HandlerWithoutInfoReturn<E, B> + Send + Sync + 'static>;
+type HandlerWithoutInfoReturn<E, B> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
 
-type HandlerWithInfo<E> =
-    Box<dyn Fn(Response<Full<Bytes>>, RequestInfo) -> HandlerWithInfoReturn<E> + Send + Sync + 'static>;
-type HandlerWithInfoReturn<E> = Box<dyn Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static>;
+type HandlerWithInfo<E, B> =
+    Box<dyn Fn(Response<B>, RequestInfo) -> HandlerWithInfoReturn<E, B> + Send + Sync + 'static>;
+type HandlerWithInfoReturn<E, B> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
 
 /// The post middleware type. Refer to [Post Middleware](./index.html#post-middleware) for more info.
 ///
-/// This `PostMiddleware<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `PostMiddleware<E, B>` type accepts two type parameters: `E` and `B`.
 ///
-/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
-///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
-///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
-pub struct PostMiddleware<E> {
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html) trait with `Bytes` data. It defaults to
+///   [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html).
+pub struct PostMiddleware<E, B = Full<Bytes>> {
     pub(crate) path: String,
     pub(crate) regex: Regex,
     // Make it an option so that when a router is used to scope in another router,
     // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
-    pub(crate) handler: Option<Handler<E>>,
+    pub(crate) handler: Option<Handler<E, B>>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
+    // Middleware with lower priorities are executed first.
+    pub(crate) priority: i32,
+    // The data which is visible only within the router this middleware was added to.
+    pub(crate) private_data_maps: Vec<SharedDataMap>,
+    // The name reported in the `Server-Timing` header.
+    pub(crate) name: Option<String>,
+    // The label to remove the middleware by, see `RouterBuilder::middleware_labeled`.
+    pub(crate) label: Option<String>,
+    // The error handler of the innermost scope which has one, see `RouterBuilder::scope`.
+    pub(crate) scope_err_handler: Option<Arc<ErrHandler<B>>>,
 }
 
-pub(crate) enum Handler<E> {
-    WithoutInfo(HandlerWithoutInfo<E>),
-    WithInfo(HandlerWithInfo<E>),
+pub(crate) enum Handler<E, B> {
+    WithoutInfo(HandlerWithoutInfo<E, B>),
+    WithInfo(HandlerWithInfo<E, B>),
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PostMiddleware<E> {
+impl<E, B> PostMiddleware<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     pub(crate) fn new_with_boxed_handler<P: Into<String>>(
         path: P,
-        handler: Handler<E>,
+        handler: Handler<E, B>,
         scope_depth: u32,
-    ) -> crate::Result<PostMiddleware<E>> {
+    ) -> crate::Result<PostMiddleware<E, B>> {
         let path = path.into();
         let (re, _) = generate_exact_match_regex(path.as_str()).map_err(|e| {
             Error::new(format!(
@@ -58,6 +75,11 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PostMiddleware
             regex: re,
             handler: Some(handler),
             scope_depth,
+            priority: 0,
+            private_data_maps: Vec::new(),
+            name: None,
+            label: None,
+            scope_err_handler: None,
         })
     }
 
@@ -84,13 +106,13 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PostMiddleware
     ///     router
     /// }
     /// ```
-    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PostMiddleware<E>>
+    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PostMiddleware<E, B>>
     where
         P: Into<String>,
-        H: Fn(Response<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
-        let handler: HandlerWithoutInfo<E> = Box::new(move |res: Response<Full<Bytes>>| Box::new(handler(res)));
+        let handler: HandlerWithoutInfo<E, B> = Box::new(move |res: Response<B>| Box::new(handler(res)));
         PostMiddleware::new_with_boxed_handler(path, Handler::WithoutInfo(handler), 1)
     }
 
@@ -129,14 +151,14 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PostMiddleware
     ///     router
     /// }
     /// ```
-    pub fn new_with_info<P, H, R>(path: P, handler: H) -> crate::Result<PostMiddleware<E>>
+    pub fn new_with_info<P, H, R>(path: P, handler: H) -> crate::Result<PostMiddleware<E, B>>
     where
         P: Into<String>,
-        H: Fn(Response<Full<Bytes>>, RequestInfo) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
-        let handler: HandlerWithInfo<E> =
-            Box::new(move |res: Response<Full<Bytes>>, req_info: RequestInfo| Box::new(handler(res, req_info)));
+        let handler: HandlerWithInfo<E, B> =
+            Box::new(move |res: Response<B>, req_info: RequestInfo| Box::new(handler(res, req_info)));
         PostMiddleware::new_with_boxed_handler(path, Handler::WithInfo(handler), 1)
     }
 
@@ -151,11 +173,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PostMiddleware
         }
     }
 
-    pub(crate) async fn process(
-        &self,
-        res: Response<Full<Bytes>>,
-        req_info: Option<RequestInfo>,
-    ) -> crate::Result<Response<Full<Bytes>>> {
+    pub(crate) async fn process(&self, res: Response<B>, req_info: Option<RequestInfo>) -> crate::Result<Response<B>> {
         let handler = self
             .handler
             .as_ref()
@@ -163,14 +181,21 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PostMiddleware
 
         match handler {
             Handler::WithoutInfo(handler) => Pin::from(handler(res)).await.map_err(Into::into),
-            Handler::WithInfo(handler) => Pin::from(handler(res, req_info.expect("No RequestInfo is provided")))
-                .await
-                .map_err(Into::into),
+            Handler::WithInfo(handler) => {
+                let mut req_info = req_info.expect("No RequestInfo is provided");
+                // The private data is looked up before the shared one, like via `RequestExt::data`.
+                if !self.private_data_maps.is_empty() {
+                    let mut data_maps = self.private_data_maps.clone();
+                    data_maps.extend(req_info.shared_data_maps.take().unwrap_or_default());
+                    req_info.shared_data_maps = Some(data_maps);
+                }
+                Pin::from(handler(res, req_info)).await.map_err(Into::into)
+            }
         }
     }
 }
 
-impl<E> Debug for PostMiddleware<E> {
+impl<E, B> Debug for PostMiddleware<E, B> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
     }
@@ -1,12 +1,15 @@
 use crate::Error;
+use crate::data_map::{PrivateDataMaps, SharedDataMap};
 use crate::regex_generator::generate_exact_match_regex;
+use crate::router::ErrHandler;
 use http_body_util::Full;
 use hyper::Request;
-use hyper::body::Bytes;
+use hyper::body::{Body, Bytes};
 use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
 type Handler<E> = Box<dyn Fn(Request<Full<Bytes>>) -> HandlerReturn<E> + Send + Sync + 'static>;
 
@@ -14,10 +17,12 @@ type HandlerReturn<E> = Box<dyn Future<Output = Result<Request<Full<Bytes>>, E>>
 
 /// The pre middleware type. Refer to [Pre Middleware](./index.html#pre-middleware) for more info.
 ///
-/// This `PreMiddleware<E>` type accepts a single type parameter: `E`.
+/// This `PreMiddleware<E, B>` type accepts two type parameters: `E` and `B`.
 ///
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
-pub struct PreMiddleware<E> {
+/// * The `B` represents the response body type of the router, which the error handler responds with when the middleware fails. It
+///   defaults to [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html).
+pub struct PreMiddleware<E, B = Full<Bytes>> {
     pub(crate) path: String,
     pub(crate) regex: Regex,
     // Make it an option so that when a router is used to scope in another router,
@@ -25,14 +30,30 @@ pub struct PreMiddleware<E> {
     pub(crate) handler: Option<Handler<E>>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
+    // Middleware with lower priorities are executed first.
+    pub(crate) priority: i32,
+    // The data which is visible only within the router this middleware was added to.
+    pub(crate) private_data_maps: Vec<SharedDataMap>,
+    // The name reported in the `Server-Timing` header.
+    pub(crate) name: Option<String>,
+    // The label to remove the middleware by, see `RouterBuilder::middleware_labeled`.
+    pub(crate) label: Option<String>,
+    // Whether the router must apply the method override before matching the routes, see `Middleware::method_override`.
+    pub(crate) overrides_method: bool,
+    // The error handler of the innermost scope which has one, see `RouterBuilder::scope`.
+    pub(crate) scope_err_handler: Option<Arc<ErrHandler<B>>>,
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<E> {
+impl<E, B> PreMiddleware<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     pub(crate) fn new_with_boxed_handler<P: Into<String>>(
         path: P,
         handler: Handler<E>,
         scope_depth: u32,
-    ) -> crate::Result<PreMiddleware<E>> {
+    ) -> crate::Result<PreMiddleware<E, B>> {
         let path = path.into();
         let (re, _) = generate_exact_match_regex(path.as_str()).map_err(|e| {
             Error::new(format!(
@@ -46,6 +67,12 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
             regex: re,
             handler: Some(handler),
             scope_depth,
+            priority: 0,
+            private_data_maps: Vec::new(),
+            name: None,
+            label: None,
+            overrides_method: false,
+            scope_err_handler: None,
         })
     }
 
@@ -72,7 +99,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
     ///     router
     /// }
     /// ```
-    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PreMiddleware<E>>
+    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PreMiddleware<E, B>>
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
@@ -82,7 +109,10 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
         PreMiddleware::new_with_boxed_handler(path, handler, 1)
     }
 
-    pub(crate) async fn process(&self, req: Request<Full<Bytes>>) -> crate::Result<Request<Full<Bytes>>> {
+    pub(crate) async fn process(&self, mut req: Request<Full<Bytes>>) -> crate::Result<Request<Full<Bytes>>> {
+        req.extensions_mut()
+            .insert(PrivateDataMaps(self.private_data_maps.clone()));
+
         let handler = self
             .handler
             .as_ref()
@@ -92,7 +122,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
     }
 }
 
-impl<E> Debug for PreMiddleware<E> {
+impl<E, B> Debug for PreMiddleware<E, B> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
     }
@@ -0,0 +1,140 @@
+use crate::ext::RequestExt;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderMap};
+use hyper::{Request, Response, StatusCode, Uri};
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
+
+// The headers which describe a single connection, so they're never forwarded.
+const HOP_BY_HOP_HEADERS: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+// Forwards the requests to an upstream HTTP server, see `RouterBuilder::proxy_fallback`.
+pub(crate) struct Proxy {
+    scheme_and_authority: String,
+    base_path: String,
+    client: Client<HttpConnector, Full<Bytes>>,
+}
+
+impl Proxy {
+    pub(crate) fn new(upstream: &str) -> crate::Result<Proxy> {
+        let uri = upstream
+            .parse::<Uri>()
+            .map_err(|e| crate::Error::new(format!("Invalid upstream URI `{}`: {}", upstream, e)))?;
+
+        let authority = match (uri.scheme_str(), uri.authority()) {
+            (Some("http"), Some(authority)) => authority,
+            _ => {
+                return Err(crate::Error::new(format!(
+                    "The upstream URI `{}` must be an absolute `http` URI",
+                    upstream
+                ))
+                .into());
+            }
+        };
+
+        Ok(Proxy {
+            scheme_and_authority: format!("http://{}", authority),
+            base_path: uri.path().trim_end_matches('/').to_owned(),
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        })
+    }
+
+    // Sends the request to the upstream and returns its response, or `502 Bad Gateway` if it can't be reached.
+    pub(crate) async fn forward<B: From<Bytes>>(&self, req: Request<Full<Bytes>>) -> Response<B> {
+        // The body may not be buffered yet, e.g. with `RouterBuilder::defer_body_reading`.
+        let body = match req.body_bytes().await {
+            Ok(body) => body,
+            Err(_) => return rejection(StatusCode::BAD_REQUEST),
+        };
+
+        let path_and_query = req.uri().path_and_query().map(|val| val.as_str()).unwrap_or("/");
+        let uri = format!("{}{}{}", self.scheme_and_authority, self.base_path, path_and_query);
+
+        let (mut parts, _) = req.into_parts();
+        parts.uri = match uri.parse() {
+            Ok(uri) => uri,
+            Err(_) => return rejection(StatusCode::BAD_REQUEST),
+        };
+        parts.version = hyper::Version::HTTP_11;
+        parts.extensions = Default::default();
+        remove_hop_by_hop_headers(&mut parts.headers);
+        // The client sets it to the upstream's authority.
+        parts.headers.remove(header::HOST);
+
+        let res = match self.client.request(Request::from_parts(parts, Full::new(body))).await {
+            Ok(res) => res,
+            Err(_) => return rejection(StatusCode::BAD_GATEWAY),
+        };
+
+        let (mut parts, body) = res.into_parts();
+        let body = match body.collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(_) => return rejection(StatusCode::BAD_GATEWAY),
+        };
+        remove_hop_by_hop_headers(&mut parts.headers);
+
+        Response::from_parts(parts, B::from(body))
+    }
+}
+
+fn remove_hop_by_hop_headers(headers: &mut HeaderMap) {
+    // The `Connection` header may list more headers which are meant for this hop only.
+    let listed = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    for name in listed.iter().map(String::as_str).chain(HOP_BY_HOP_HEADERS) {
+        headers.remove(name);
+    }
+}
+
+fn rejection<B: From<Bytes>>(status: StatusCode) -> Response<B> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(B::from(Bytes::new()))
+        .expect("Couldn't create the proxy error response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_non_http_upstreams() {
+        assert!(Proxy::new("https://example.com").is_err());
+        assert!(Proxy::new("/relative").is_err());
+        assert!(Proxy::new("http://example.com/api/").is_ok());
+    }
+
+    #[test]
+    fn removes_hop_by_hop_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONNECTION, "keep-alive, x-hop".parse().unwrap());
+        headers.insert("x-hop", "1".parse().unwrap());
+        headers.insert(header::TRANSFER_ENCODING, "chunked".parse().unwrap());
+        headers.insert("x-end-to-end", "1".parse().unwrap());
+
+        remove_hop_by_hop_headers(&mut headers);
+        assert_eq!(
+            vec!["x-end-to-end"],
+            headers.keys().map(|key| key.as_str()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,31 +1,120 @@
-use lazy_static::lazy_static;
+use crate::Error;
 use regex::Regex;
+use std::collections::HashMap;
 
-lazy_static! {
-    static ref PATH_PARAMS_RE: Regex = Regex::new(r"(?s)(?::([^/\.]+))|(?:\*)").unwrap();
+// A parameter of a route path, i.e. `:name` optionally followed by its inline constraint `(regex)`
+// and by `?` if the segment is optional.
+struct PathParam<'a> {
+    name: &'a str,
+    constraint: Option<&'a str>,
+    // Whether the `(` after the name isn't closed, in which case the `constraint` is `None`.
+    unclosed: bool,
+    optional: bool,
+    end: usize,
+}
+
+// Parses the parameter whose `:` is at the position `colon`.
+fn parse_param(path: &str, colon: usize) -> PathParam<'_> {
+    let bytes = path.as_bytes();
+    let name_end = param_name_end(bytes, colon + 1);
+    let name = &path[colon + 1..name_end];
+
+    let mut constraint = None;
+    let mut unclosed = false;
+    let mut end = name_end;
+    if end < bytes.len() && bytes[end] == b'(' {
+        match constraint_end(bytes, end) {
+            Some(constraint_end) => {
+                constraint = Some(&path[end + 1..constraint_end - 1]);
+                end = constraint_end;
+            }
+            None => unclosed = true,
+        }
+    }
+
+    let optional = !unclosed && end < bytes.len() && bytes[end] == b'?';
+    if optional {
+        end += 1;
+    }
+
+    PathParam {
+        name,
+        constraint,
+        unclosed,
+        optional,
+        end,
+    }
 }
 
 fn generate_common_regex_str(path: &str) -> (String, Vec<String>) {
+    generate_constrained_regex_str(path, &HashMap::new())
+}
+
+// The named parameters having a constraint match its regex instead of any segment. An inline
+// constraint of the path takes precedence over the given ones. The optional parameters make their
+// leading slash optional too, except the one of the root path.
+fn generate_constrained_regex_str(path: &str, param_constraints: &HashMap<String, String>) -> (String, Vec<String>) {
+    let bytes = path.as_bytes();
     let mut regex_str = String::with_capacity(path.len());
     let mut param_names = Vec::new();
 
     let mut pos: usize = 0;
+    let mut i: usize = 0;
 
-    for caps in PATH_PARAMS_RE.captures_iter(path) {
-        let whole = caps.get(0).unwrap();
+    while i < bytes.len() {
+        match bytes[i] {
+            b':' => {
+                let param = parse_param(path, i);
+                if param.name.is_empty() {
+                    i += 1;
+                    continue;
+                }
 
-        let path_s = &path[pos..whole.start()];
-        regex_str += &regex::escape(path_s);
+                let group = match param
+                    .constraint
+                    .or_else(|| param_constraints.get(param.name).map(String::as_str))
+                {
+                    Some(constraint) => format!("((?:{}))", constraint),
+                    None => r"([^/]+)".to_owned(),
+                };
+                if param.optional && i > 1 && bytes[i - 1] == b'/' {
+                    regex_str += &regex::escape(&path[pos..i - 1]);
+                    regex_str += &format!("(?:/{})?", group);
+                } else if param.optional {
+                    regex_str += &regex::escape(&path[pos..i]);
+                    regex_str += &format!("{}?", group);
+                } else {
+                    regex_str += &regex::escape(&path[pos..i]);
+                    regex_str += &group;
+                }
+                param_names.push(param.name.to_owned());
 
-        if whole.as_str() == "*" {
-            regex_str += r"(.*)";
-            param_names.push("*".to_owned());
-        } else {
-            regex_str += r"([^/]+)";
-            param_names.push(caps.get(1).unwrap().as_str().to_owned());
-        }
+                pos = param.end;
+                i = param.end;
+            }
+            b'*' => {
+                // A named glob captures the rest of the path under its name, an unnamed one under `*`.
+                let name_end = param_name_end(bytes, i + 1);
+                let (name, end) = match &path[i + 1..name_end] {
+                    name if is_valid_param_name(name) => (name, name_end),
+                    _ => ("*", i + 1),
+                };
+
+                regex_str += &regex::escape(&path[pos..i]);
+                // The trailing slash, which is appended to the request path for matching, isn't a part
+                // of the captured path of a named glob.
+                if name != "*" && end == bytes.len() {
+                    regex_str += r"(.*?)/?";
+                } else {
+                    regex_str += r"(.*)";
+                }
+                param_names.push(name.to_owned());
 
-        pos = whole.end();
+                pos = end;
+                i = end;
+            }
+            _ => i += 1,
+        }
     }
 
     let left_over_path_s = &path[pos..];
@@ -34,8 +123,334 @@ fn generate_common_regex_str(path: &str) -> (String, Vec<String>) {
     (regex_str, param_names)
 }
 
+// Returns the inline constraints of the route path by their parameter names.
+pub(crate) fn inline_param_constraints(path: &str) -> HashMap<String, String> {
+    let mut constraints = HashMap::new();
+
+    let mut i = 0;
+    while i < path.len() {
+        if path.as_bytes()[i] == b':' {
+            let param = parse_param(path, i);
+            if let Some(constraint) = param.constraint {
+                constraints.insert(param.name.to_owned(), constraint.to_owned());
+            }
+            i = param.end.max(i + 1);
+            continue;
+        }
+        i += 1;
+    }
+
+    constraints
+}
+
+// Validates the route path before generating its regex, so that a malformed path is reported
+// with the offending position instead of a generic regex compilation error.
+pub(crate) fn validate_path(path: &str) -> crate::Result<()> {
+    let invalid = |msg: String| Error::new(format!("Invalid route path `{}`: {}", path, msg));
+    let bytes = path.as_bytes();
+    let mut param_names: Vec<&str> = Vec::new();
+
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b':' => {
+                let param = parse_param(path, pos);
+                let name = param.name;
+
+                if name.is_empty() {
+                    return Err(invalid(format!("empty parameter name at position {}", pos)).into());
+                }
+                if !is_valid_param_name(name) {
+                    return Err(invalid(format!("invalid parameter name `{}` at position {}", name, pos)).into());
+                }
+                if param_names.contains(&name) {
+                    return Err(invalid(format!("duplicate parameter name `{}` at position {}", name, pos)).into());
+                }
+                if param.unclosed {
+                    return Err(invalid(format!(
+                        "unclosed constraint of the parameter `{}` at position {}",
+                        name, pos
+                    ))
+                    .into());
+                }
+                if param.constraint == Some("") {
+                    return Err(invalid(format!(
+                        "empty constraint of the parameter `{}` at position {}",
+                        name, pos
+                    ))
+                    .into());
+                }
+                if param.optional && !is_whole_segment(bytes, pos, param.end) {
+                    return Err(invalid(format!(
+                        "optional parameter `{}` must be a whole path segment, found at position {}",
+                        name, pos
+                    ))
+                    .into());
+                }
+                if let Some(constraint) = param.constraint
+                    && let Err(msg) = check_constraint_regex(constraint)
+                {
+                    return Err(invalid(format!(
+                        "invalid constraint of the parameter `{}` at position {}: {}",
+                        name, pos, msg
+                    ))
+                    .into());
+                }
+                param_names.push(name);
+
+                pos = param.end;
+                continue;
+            }
+            b'*' => {
+                let name_end = param_name_end(bytes, pos + 1);
+                let name = &path[pos + 1..name_end];
+
+                if !is_whole_segment(bytes, pos, name_end) {
+                    return Err(invalid(format!("`*` must be a whole path segment, found at position {}", pos)).into());
+                }
+                if !name.is_empty() {
+                    if !is_valid_param_name(name) {
+                        return Err(invalid(format!("invalid parameter name `{}` at position {}", name, pos)).into());
+                    }
+                    if param_names.contains(&name) {
+                        return Err(invalid(format!("duplicate parameter name `{}` at position {}", name, pos)).into());
+                    }
+                    param_names.push(name);
+                }
+
+                pos = name_end;
+                continue;
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    Ok(())
+}
+
+// The compile time counterpart of `validate_path` used by the `routes!` macro, which can only
+// tell whether the path is valid. It can't compile the inline constraints, so it only checks
+// that they are closed and not empty.
+pub const fn is_valid_route_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b':' {
+            let name_end = param_name_end(bytes, i + 1);
+            if !is_valid_param_name_bytes(bytes, i + 1, name_end) {
+                return false;
+            }
+
+            if is_duplicate_param_name(bytes, i + 1, name_end) {
+                return false;
+            }
+
+            if name_end < bytes.len() && bytes[name_end] == b'(' {
+                match constraint_end(bytes, name_end) {
+                    Some(end) if end > name_end + 2 => {}
+                    _ => return false,
+                }
+            }
+
+            let end = param_end(bytes, name_end);
+            if bytes[end - 1] == b'?' && !is_whole_segment(bytes, i, end) {
+                return false;
+            }
+
+            i = end;
+            continue;
+        }
+
+        if bytes[i] == b'*' {
+            let name_end = param_name_end(bytes, i + 1);
+            if !is_whole_segment(bytes, i, name_end) {
+                return false;
+            }
+            if name_end > i + 1
+                && (!is_valid_param_name_bytes(bytes, i + 1, name_end)
+                    || is_duplicate_param_name(bytes, i + 1, name_end))
+            {
+                return false;
+            }
+
+            i = name_end;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    true
+}
+
+// Checks whether a parameter or a named glob before `start` has the name at `start..end`. The
+// previous names are already validated, so they don't contain `:` and `*`, but the constraints
+// may and have to be skipped.
+const fn is_duplicate_param_name(bytes: &[u8], start: usize, end: usize) -> bool {
+    let mut j = 0;
+    while j + 1 < start {
+        if bytes[j] == b':' || bytes[j] == b'*' {
+            let other_end = param_name_end(bytes, j + 1);
+            if bytes_eq(bytes, j + 1, other_end, start, end) {
+                return true;
+            }
+            j = if bytes[j] == b':' {
+                param_end(bytes, other_end)
+            } else {
+                other_end
+            };
+            continue;
+        }
+        j += 1;
+    }
+
+    false
+}
+
+// Checks whether the route path ends with a glob, which matches the trailing slash of the
+// request path too.
+pub(crate) fn ends_with_glob(path: &str) -> bool {
+    path.rsplit('/')
+        .next()
+        .and_then(|segment| segment.strip_prefix('*'))
+        .is_some_and(|name| name.is_empty() || is_valid_param_name(name))
+}
+
+const fn param_name_end(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while end < bytes.len() && bytes[end] != b'/' && bytes[end] != b'.' && bytes[end] != b'(' && bytes[end] != b'?' {
+        end += 1;
+    }
+    end
+}
+
+// Returns the end of the parameter whose name ends at `name_end`, i.e. after its inline constraint
+// if it has a closed one and after its `?` if it's optional.
+const fn param_end(bytes: &[u8], name_end: usize) -> usize {
+    let mut end = name_end;
+    if end < bytes.len() && bytes[end] == b'(' {
+        match constraint_end(bytes, end) {
+            Some(constraint_end) => end = constraint_end,
+            None => return name_end,
+        }
+    }
+
+    if end < bytes.len() && bytes[end] == b'?' {
+        end += 1;
+    }
+    end
+}
+
+const fn is_whole_segment(bytes: &[u8], start: usize, end: usize) -> bool {
+    (start == 0 || bytes[start - 1] == b'/') && (end == bytes.len() || bytes[end] == b'/')
+}
+
+// Returns the position after the `)` closing the inline constraint opened at `open`, or `None` if
+// it isn't closed. The escaped characters and the ones of the character classes are skipped, so
+// that e.g. `(\))` and `([)])` are closed by their last `)`.
+const fn constraint_end(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut class_depth = 0;
+
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => class_depth += 1,
+            b']' if class_depth > 0 => class_depth -= 1,
+            b'(' if class_depth == 0 => depth += 1,
+            b')' if class_depth == 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+const fn is_valid_param_name_bytes(bytes: &[u8], start: usize, end: usize) -> bool {
+    if start == end || !(bytes[start].is_ascii_alphabetic() || bytes[start] == b'_') {
+        return false;
+    }
+
+    let mut i = start + 1;
+    while i < end {
+        if !(bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+const fn bytes_eq(bytes: &[u8], a_start: usize, a_end: usize, b_start: usize, b_end: usize) -> bool {
+    if a_end - a_start != b_end - b_start {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a_end - a_start {
+        if bytes[a_start + i] != bytes[b_start + i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+// Validates a parameter constraint regex, which must not have its own capturing groups as they would
+// shift the positions of the following parameters.
+pub(crate) fn validate_param_constraint(name: &str, constraint: &str) -> crate::Result<()> {
+    if !is_valid_param_name(name) {
+        return Err(Error::new(format!(
+            "Invalid parameter constraint: invalid parameter name `{}`",
+            name
+        ))
+        .into());
+    }
+
+    check_constraint_regex(constraint).map_err(|msg| {
+        Error::new(format!(
+            "Invalid constraint regex for the parameter `{}`: {}",
+            name, msg
+        ))
+        .into()
+    })
+}
+
+fn check_constraint_regex(constraint: &str) -> Result<(), String> {
+    let re = Regex::new(constraint).map_err(|e| e.to_string())?;
+    if re.captures_len() > 1 {
+        return Err("capturing groups aren't allowed, use `(?:...)` instead".to_owned());
+    }
+
+    Ok(())
+}
+
+fn is_valid_param_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 pub(crate) fn generate_exact_match_regex(path: &str) -> crate::Result<(Regex, Vec<String>)> {
-    let (common_regex_str, params) = generate_common_regex_str(path);
+    generate_constrained_exact_match_regex(path, &HashMap::new())
+}
+
+pub(crate) fn generate_constrained_exact_match_regex(
+    path: &str,
+    param_constraints: &HashMap<String, String>,
+) -> crate::Result<(Regex, Vec<String>)> {
+    let (common_regex_str, params) = generate_constrained_regex_str(path, param_constraints);
     let re_str = format!("{}{}{}", r"(?s)^", common_regex_str, "$");
     let re = Regex::new(re_str.as_str())?;
     Ok((re, params))
@@ -124,4 +539,205 @@ mod tests {
         let r = generate_common_regex_str(path);
         assert_eq!(r, (r"/users/(.*)(.*)".to_owned(), vec!["*".to_owned(), "*".to_owned()]));
     }
+
+    #[test]
+    fn test_generate_common_regex_str_named_globs() {
+        let r = generate_common_regex_str("/assets/*path");
+        assert_eq!(r, (r"/assets/(.*?)/?".to_owned(), vec!["path".to_owned()]));
+
+        let r = generate_common_regex_str("/repos/:repo/*rest/raw");
+        assert_eq!(
+            r,
+            (
+                r"/repos/([^/]+)/(.*)/raw".to_owned(),
+                vec!["repo".to_owned(), "rest".to_owned()]
+            )
+        );
+    }
+
+    #[test]
+    fn test_ends_with_glob() {
+        assert!(ends_with_glob("/*"));
+        assert!(ends_with_glob("/assets/*path"));
+        assert!(!ends_with_glob("/assets/"));
+        assert!(!ends_with_glob("/assets/*path/"));
+        assert!(!ends_with_glob(r"/files/:name(a/*b)"));
+    }
+
+    #[test]
+    fn test_generate_constrained_regex_str() {
+        let constraints = HashMap::from([("org".to_owned(), "[a-z0-9-]+".to_owned())]);
+        let r = generate_constrained_regex_str("/orgs/:org/repos/:repo", &constraints);
+        assert_eq!(
+            r,
+            (
+                r"/orgs/((?:[a-z0-9-]+))/repos/([^/]+)".to_owned(),
+                vec!["org".to_owned(), "repo".to_owned()]
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_common_regex_str_inline_constraints() {
+        let r = generate_common_regex_str(r"/users/:id(\d+)/books/:book");
+        assert_eq!(
+            r,
+            (
+                r"/users/((?:\d+))/books/([^/]+)".to_owned(),
+                vec!["id".to_owned(), "book".to_owned()]
+            )
+        );
+
+        let r = generate_common_regex_str(r"/files/:name([a-z)]+\.(?:txt|md))/:rev(\(\d*\))");
+        assert_eq!(
+            r,
+            (
+                r"/files/((?:[a-z)]+\.(?:txt|md)))/((?:\(\d*\)))".to_owned(),
+                vec!["name".to_owned(), "rev".to_owned()]
+            )
+        );
+
+        let constraints = HashMap::from([("id".to_owned(), "[a-z]+".to_owned())]);
+        let r = generate_constrained_regex_str(r"/users/:id(\d+)", &constraints);
+        assert_eq!(r, (r"/users/((?:\d+))".to_owned(), vec!["id".to_owned()]));
+    }
+
+    #[test]
+    fn test_generate_common_regex_str_optional_params() {
+        let r = generate_common_regex_str("/files/:name?");
+        assert_eq!(r, (r"/files(?:/([^/]+))?".to_owned(), vec!["name".to_owned()]));
+
+        let r = generate_common_regex_str(r"/users/:id([0-9]+)?/books/:book?");
+        assert_eq!(
+            r,
+            (
+                r"/users(?:/((?:[0-9]+)))?/books(?:/([^/]+))?".to_owned(),
+                vec!["id".to_owned(), "book".to_owned()]
+            )
+        );
+
+        let r = generate_common_regex_str("/:lang?");
+        assert_eq!(r, (r"/([^/]+)?".to_owned(), vec!["lang".to_owned()]));
+    }
+
+    #[test]
+    fn test_inline_param_constraints() {
+        assert_eq!(
+            inline_param_constraints(r"/orgs/:org([a-z]+)/repos/:repo/:rev(\d+)"),
+            HashMap::from([
+                ("org".to_owned(), "[a-z]+".to_owned()),
+                ("rev".to_owned(), r"\d+".to_owned())
+            ])
+        );
+        assert!(inline_param_constraints("/orgs/:org").is_empty());
+    }
+
+    #[test]
+    fn test_validate_param_constraint() {
+        assert!(validate_param_constraint("org", "[a-z0-9-]+").is_ok());
+        assert!(validate_param_constraint("id", "[0-9]+|(?:new)").is_ok());
+        assert!(validate_param_constraint("org", "([a-z]+)").is_err());
+        assert!(validate_param_constraint("org", "[a-z").is_err());
+        assert!(validate_param_constraint("1org", "[a-z]+").is_err());
+    }
+
+    #[test]
+    fn test_validate_path_valid() {
+        for path in [
+            "/",
+            "/*",
+            "/users/:user_id/books/:bookName",
+            "/api/:id.json",
+            "/files/*/raw",
+            "/:_a1",
+            r"/users/:id(\d+)/books/:book((?:new)|[0-9]+)",
+            r"/files/:name([^/]+\.txt)/:rev(\))",
+            "/files/:name?",
+            r"/:lang([a-z]{2})?/docs/:page?",
+            "/assets/*path",
+            "/repos/:repo/*rest/raw/*",
+        ] {
+            assert!(validate_path(path).is_ok(), "{} should be valid", path);
+            assert!(is_valid_route_path(path), "{} should be valid", path);
+        }
+    }
+
+    #[test]
+    fn test_validate_path_invalid() {
+        let cases = [
+            (
+                "/users/:",
+                "Invalid route path `/users/:`: empty parameter name at position 7",
+            ),
+            (
+                "/users/:1st",
+                "Invalid route path `/users/:1st`: invalid parameter name `1st` at position 7",
+            ),
+            (
+                "/users/:user-name",
+                "Invalid route path `/users/:user-name`: invalid parameter name `user-name` at position 7",
+            ),
+            (
+                "/users/:id/books/:id",
+                "Invalid route path `/users/:id/books/:id`: duplicate parameter name `id` at position 17",
+            ),
+            (
+                r"/users/:id(\d+",
+                r"Invalid route path `/users/:id(\d+`: unclosed constraint of the parameter `id` at position 7",
+            ),
+            (
+                "/users/:id()",
+                "Invalid route path `/users/:id()`: empty constraint of the parameter `id` at position 7",
+            ),
+            (
+                "/users/:id([a-z]+)/books/:id([0-9]+)",
+                "Invalid route path `/users/:id([a-z]+)/books/:id([0-9]+)`: duplicate parameter name `id` at position 25",
+            ),
+            (
+                "/files/:name?.txt",
+                "Invalid route path `/files/:name?.txt`: optional parameter `name` must be a whole path segment, found at position 7",
+            ),
+            (
+                "/files/v:version?",
+                "Invalid route path `/files/v:version?`: optional parameter `version` must be a whole path segment, found at position 8",
+            ),
+            (
+                "/assets/*1path",
+                "Invalid route path `/assets/*1path`: invalid parameter name `1path` at position 8",
+            ),
+            (
+                "/assets/:path/*path",
+                "Invalid route path `/assets/:path/*path`: duplicate parameter name `path` at position 14",
+            ),
+            (
+                "/assets/*path/:path",
+                "Invalid route path `/assets/*path/:path`: duplicate parameter name `path` at position 14",
+            ),
+            (
+                "/files*",
+                "Invalid route path `/files*`: `*` must be a whole path segment, found at position 6",
+            ),
+            (
+                "/files/*.txt",
+                "Invalid route path `/files/*.txt`: `*` must be a whole path segment, found at position 7",
+            ),
+        ];
+
+        for (path, msg) in cases {
+            assert!(!is_valid_route_path(path), "{} should be invalid", path);
+            assert_eq!(
+                validate_path(path).unwrap_err().to_string(),
+                format!("routerify_ng::Error: {}", msg)
+            );
+        }
+
+        // The compile time check can't compile the constraints.
+        let path = r"/users/:id((\d+))";
+        assert!(is_valid_route_path(path), "{} should pass the compile time check", path);
+        assert_eq!(
+            validate_path(path).unwrap_err().to_string(),
+            "routerify_ng::Error: Invalid route path `/users/:id((\\d+))`: invalid constraint of the parameter `id` at \
+             position 7: capturing groups aren't allowed, use `(?:...)` instead"
+        );
+    }
 }
@@ -0,0 +1,92 @@
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Response, header};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+
+// The characters which are encoded in the RFC 5987 `filename*` parameter, i.e. all but the `attr-char`s.
+const FILENAME_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// Creates a `200 OK` response which the browsers download as a file with the specified name, with the
+/// `application/octet-stream` content type.
+///
+/// A non-ASCII filename is sent in the RFC 5987 `filename*` parameter along with an ASCII fallback
+/// for the clients not supporting it.
+///
+/// # Examples
+///
+/// ```
+/// use routerify_ng::{attachment, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/export", |_| async move { Ok(attachment("report.csv", "id,name\n1,Alice\n")) })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn attachment<F: AsRef<str>, B: Into<Bytes>>(filename: F, body: B) -> Response<Full<Bytes>> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_DISPOSITION, content_disposition(filename.as_ref()))
+        .body(Full::new(body.into()))
+        .expect("Couldn't create the attachment response")
+}
+
+fn content_disposition(filename: &str) -> String {
+    let fallback = filename
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => '_',
+        })
+        .collect::<String>();
+
+    if fallback == filename {
+        format!("attachment; filename=\"{}\"", filename)
+    } else {
+        format!(
+            "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+            fallback,
+            utf8_percent_encode(filename, FILENAME_ENCODE_SET)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_ascii_filename() {
+        let resp = attachment("report.csv", "id,name");
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/octet-stream");
+        assert_eq!(
+            resp.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"report.csv\""
+        );
+    }
+
+    #[test]
+    fn test_attachment_utf8_filename() {
+        let resp = attachment("résumé 2024.pdf", "");
+        assert_eq!(
+            resp.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"r_sum_ 2024.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9%202024.pdf"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::constants;
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode};
+use std::future::Future;
+use std::time::Duration;
+
+/// Holds a long-poll request open until the `producer` yields a response or the `timeout` elapses, in which case
+/// a `204 No Content` response is returned.
+///
+/// The `producer` is asked again shortly after it resolves to `None`, so it can either wait for the data itself,
+/// e.g. on a channel, or just check whether it's available yet.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::{long_poll, Router};
+/// use std::convert::Infallible;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use tokio::sync::Mutex;
+///
+/// fn run(events: Arc<Mutex<Vec<String>>>) -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/events", move |_| {
+///             let events = events.clone();
+///             async move {
+///                 Ok(long_poll(Duration::from_secs(30), || {
+///                     let events = events.clone();
+///                     async move {
+///                         let event = events.lock().await.pop()?;
+///                         Some(Response::new(Full::new(Bytes::from(event))))
+///                     }
+///                 })
+///                 .await)
+///             }
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub async fn long_poll<F, R>(timeout: Duration, mut producer: F) -> Response<Full<Bytes>>
+where
+    F: FnMut() -> R,
+    R: Future<Output = Option<Response<Full<Bytes>>>>,
+{
+    let poll = async {
+        loop {
+            if let Some(res) = producer().await {
+                return res;
+            }
+            tokio::time::sleep(constants::LONG_POLL_RETRY_INTERVAL).await;
+        }
+    };
+
+    match tokio::time::timeout(timeout, poll).await {
+        Ok(res) => res,
+        Err(_) => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Full::new(Bytes::new()))
+            .expect("Couldn't create the long-poll timeout response"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_long_poll_yields_within_timeout() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let res = long_poll(Duration::from_secs(5), || {
+            let attempts = attempts.clone();
+            async move {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return None;
+                }
+                Some(Response::new(Full::new(Bytes::from("data"))))
+            }
+        })
+        .await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_long_poll_times_out() {
+        let res = long_poll(Duration::from_millis(250), || async { None }).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    }
+}
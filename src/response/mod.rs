@@ -0,0 +1,20 @@
+pub use attachment::attachment;
+pub use long_poll::long_poll;
+pub use not_acceptable::not_acceptable;
+pub use not_modified::not_modified;
+pub use or_response::or_response;
+pub use preload::add_preload;
+pub(crate) use preload::is_html;
+pub use redirect::{redirect_permanent, redirect_to};
+pub use retry_after::{RetryAfter, set_retry_after};
+pub use vary::append_vary;
+
+mod attachment;
+mod long_poll;
+mod not_acceptable;
+mod not_modified;
+mod or_response;
+mod preload;
+mod redirect;
+mod retry_after;
+mod vary;
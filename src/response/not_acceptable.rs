@@ -0,0 +1,55 @@
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode, header};
+
+/// Creates a `406 Not Acceptable` response listing the available representations, one per line, so that the client
+/// can pick one of them.
+///
+/// It's meant for the handlers whose content negotiation found nothing acceptable, e.g. when
+/// [`preferred_language`](./ext/trait.RequestExt.html#tymethod.preferred_language) returns `None` for the offered
+/// languages.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::ext::RequestExt;
+/// use routerify_ng::{not_acceptable, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/hello", |req| async move {
+///             const LANGUAGES: [&str; 2] = ["en", "fr"];
+///
+///             let greeting = match req.preferred_language(&LANGUAGES) {
+///                 Some("fr") => "Bonjour",
+///                 Some(_) => "Hello",
+///                 None => return Ok(not_acceptable(LANGUAGES)),
+///             };
+///
+///             Ok(Response::new(Full::new(Bytes::from(greeting))))
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn not_acceptable<I, T>(available: I) -> Response<Full<Bytes>>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    let body = available.into_iter().fold(String::new(), |mut body, representation| {
+        body.push_str(representation.as_ref());
+        body.push('\n');
+        body
+    });
+
+    Response::builder()
+        .status(StatusCode::NOT_ACCEPTABLE)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Full::new(Bytes::from(body)))
+        .expect("Couldn't create the not acceptable response")
+}
@@ -0,0 +1,33 @@
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode, header};
+use std::time::SystemTime;
+
+/// Creates a `304 Not Modified` response with the `Last-Modified` header, telling the client to use its cached copy.
+///
+/// It's meant for the handlers whose [`not_modified_since`](./ext/trait.RequestExt.html#tymethod.not_modified_since)
+/// check succeeded. The headers which would describe the body, like `Cache-Control` or `Vary`, can be added to the
+/// returned response as usual.
+///
+/// # Examples
+///
+/// ```
+/// use routerify_ng::{not_modified, Router};
+/// use std::convert::Infallible;
+/// use std::time::SystemTime;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/logo", |_| async move { Ok(not_modified(SystemTime::UNIX_EPOCH)) })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn not_modified(last_modified: SystemTime) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header(header::LAST_MODIFIED, httpdate::fmt_http_date(last_modified))
+        .body(Full::new(Bytes::new()))
+        .expect("Couldn't create the not modified response")
+}
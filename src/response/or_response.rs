@@ -0,0 +1,53 @@
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Request, Response};
+use std::future::Future;
+use std::pin::Pin;
+
+type OrResponseFuture<E> = Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static>>;
+
+/// Adapts a handler returning `Result<Response, Response>` to be registered as a route handler. The `Err` response
+/// is sent as-is, without going through the [error handler](./index.html#error-handling).
+///
+/// It's handy when an "error" is just a non-2xx response, e.g. a `400 Bad Request` with a body.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response, StatusCode};
+/// use routerify_ng::ext::RequestExt;
+/// use routerify_ng::{or_response, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get(
+///             "/users/:id",
+///             or_response(|req| async move {
+///                 let id = req.param("id").unwrap().parse::<u64>().map_err(|_| {
+///                     Response::builder()
+///                         .status(StatusCode::BAD_REQUEST)
+///                         .body(Full::new(Bytes::from("Invalid user id")))
+///                         .unwrap()
+///                 })?;
+///
+///                 Ok(Response::new(Full::new(Bytes::from(format!("User {}", id)))))
+///             }),
+///         )
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn or_response<H, R, E>(handler: H) -> impl Fn(Request<Full<Bytes>>) -> OrResponseFuture<E> + Send + Sync + 'static
+where
+    H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<Response<Full<Bytes>>, Response<Full<Bytes>>>> + Send + 'static,
+    E: 'static,
+{
+    move |req| {
+        let fut = handler(req);
+        Box::pin(async move { Ok(fut.await.unwrap_or_else(|res| res)) })
+    }
+}
@@ -0,0 +1,78 @@
+use crate::response::redirect::LOCATION_ENCODE_SET;
+use hyper::Response;
+use hyper::header::{self, HeaderValue};
+use percent_encoding::utf8_percent_encode;
+
+/// Adds a `Link: <path>; rel=preload; as=as_type` entry to the response, e.g. to let the clients fetch the stylesheets
+/// of an HTML page early. The entries are merged into a single comma-separated `Link` header.
+///
+/// Any character not allowed in a header value is percent-encoded in the path.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::{add_preload, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/", |_| async move {
+///             let mut res = Response::new(Full::new(Bytes::from("<html></html>")));
+///             add_preload(&mut res, "/style.css", "style");
+///             Ok(res)
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn add_preload<B, P: AsRef<str>, A: AsRef<str>>(res: &mut Response<B>, path: P, as_type: A) {
+    let link = format!(
+        "<{}>; rel=preload; as={}",
+        utf8_percent_encode(path.as_ref(), LOCATION_ENCODE_SET)
+            .to_string()
+            .replace('>', "%3E"),
+        as_type.as_ref()
+    );
+
+    let mut links = res
+        .headers()
+        .get_all(header::LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    links.push(link);
+
+    if let Ok(value) = HeaderValue::from_str(&links.join(", ")) {
+        res.headers_mut().insert(header::LINK, value);
+    }
+}
+
+// Whether the preload links are relevant for the response, i.e. it's an HTML page.
+pub(crate) fn is_html<B>(res: &Response<B>) -> bool {
+    res.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim_start().to_ascii_lowercase().starts_with("text/html"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_preload() {
+        let mut res = Response::new(());
+        add_preload(&mut res, "/style.css", "style");
+        add_preload(&mut res, "/app.js", "script");
+        add_preload(&mut res, "/fonts/my font.woff2", "font");
+
+        assert_eq!(
+            res.headers()[header::LINK],
+            "</style.css>; rel=preload; as=style, </app.js>; rel=preload; as=script, </fonts/my%20font.woff2>; rel=preload; as=font"
+        );
+    }
+}
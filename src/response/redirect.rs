@@ -0,0 +1,91 @@
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode, header};
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+
+// The characters which can't appear in a header value as is. Non-ASCII characters are always encoded.
+pub(crate) const LOCATION_ENCODE_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
+
+/// Creates a `302 Found` response redirecting the client to the specified location.
+///
+/// Any character not allowed in a header value is percent-encoded.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::{redirect_to, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/dashboard", |req| async move {
+///             if !req.headers().contains_key("authorization") {
+///                 return Ok(redirect_to("/login"));
+///             }
+///
+///             Ok(Response::new(Full::new(Bytes::from("Dashboard"))))
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn redirect_to<L: AsRef<str>>(location: L) -> Response<Full<Bytes>> {
+    redirect(StatusCode::FOUND, location.as_ref())
+}
+
+/// Creates a `301 Moved Permanently` response redirecting the client to the specified location.
+///
+/// Any character not allowed in a header value is percent-encoded.
+///
+/// # Examples
+///
+/// ```
+/// use routerify_ng::{redirect_permanent, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/old-blog", |_| async move { Ok(redirect_permanent("/blog")) })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn redirect_permanent<L: AsRef<str>>(location: L) -> Response<Full<Bytes>> {
+    redirect(StatusCode::MOVED_PERMANENTLY, location.as_ref())
+}
+
+fn redirect(status: StatusCode, location: &str) -> Response<Full<Bytes>> {
+    let location = utf8_percent_encode(location, LOCATION_ENCODE_SET).to_string();
+
+    Response::builder()
+        .status(status)
+        .header(header::LOCATION, location)
+        .body(Full::new(Bytes::new()))
+        .expect("Couldn't create the redirect response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_to() {
+        let resp = redirect_to("/login");
+        assert_eq!(resp.status(), StatusCode::FOUND);
+        assert_eq!(resp.headers()[header::LOCATION], "/login");
+    }
+
+    #[test]
+    fn test_redirect_permanent() {
+        let resp = redirect_permanent("https://example.com/new home?q=ü");
+        assert_eq!(resp.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            resp.headers()[header::LOCATION],
+            "https://example.com/new%20home?q=%C3%BC"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use hyper::Response;
+use hyper::header::{self, HeaderValue};
+use std::time::{Duration, SystemTime};
+
+/// Represents the value of the `Retry-After` header, which is either a delay or a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryAfter {
+    /// The delay after which the request can be retried, sent in whole seconds.
+    Delay(Duration),
+    /// The time after which the request can be retried, sent as an HTTP date.
+    Date(SystemTime),
+}
+
+impl From<Duration> for RetryAfter {
+    fn from(delay: Duration) -> RetryAfter {
+        RetryAfter::Delay(delay)
+    }
+}
+
+impl From<SystemTime> for RetryAfter {
+    fn from(date: SystemTime) -> RetryAfter {
+        RetryAfter::Date(date)
+    }
+}
+
+impl RetryAfter {
+    pub(crate) fn to_header_value(self) -> HeaderValue {
+        match self {
+            RetryAfter::Delay(delay) => HeaderValue::from(delay.as_secs()),
+            RetryAfter::Date(date) => HeaderValue::from_str(&httpdate::fmt_http_date(date))
+                .expect("An HTTP date is always a valid header value"),
+        }
+    }
+}
+
+/// Sets the `Retry-After` header of the response, e.g. a `429 Too Many Requests` or a `503 Service Unavailable` one,
+/// replacing the existing value.
+///
+/// A delay is sent in whole seconds, e.g. `120`, and a point in time as an HTTP date, e.g.
+/// `Wed, 21 Oct 2015 07:28:00 GMT`.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response, StatusCode};
+/// use routerify_ng::{set_retry_after, RetryAfter, Router};
+/// use std::convert::Infallible;
+/// use std::time::Duration;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/reports", |_| async move {
+///             let mut res = Response::builder()
+///                 .status(StatusCode::TOO_MANY_REQUESTS)
+///                 .body(Full::new(Bytes::new()))
+///                 .unwrap();
+///             set_retry_after(&mut res, RetryAfter::Delay(Duration::from_secs(30)));
+///             Ok(res)
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn set_retry_after<B>(res: &mut Response<B>, retry_after: RetryAfter) {
+    res.headers_mut()
+        .insert(header::RETRY_AFTER, retry_after.to_header_value());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_set_retry_after() {
+        let mut res = Response::new(());
+
+        set_retry_after(&mut res, RetryAfter::Delay(Duration::from_millis(120_900)));
+        assert_eq!(res.headers()[header::RETRY_AFTER], "120");
+
+        let date = UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+        set_retry_after(&mut res, RetryAfter::Date(date));
+        assert_eq!(res.headers()[header::RETRY_AFTER], "Wed, 21 Oct 2015 07:28:00 GMT");
+        assert_eq!(1, res.headers().get_all(header::RETRY_AFTER).iter().count());
+    }
+}
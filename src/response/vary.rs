@@ -0,0 +1,60 @@
+use hyper::Response;
+use hyper::header::{self, HeaderValue};
+
+/// Appends a header name to the `Vary` header of the response, merging it with the values which were already added
+/// e.g. by other middlewares.
+///
+/// The header names are compared case-insensitively, so a name is listed only once. Nothing is appended if the response
+/// already varies on `*`.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::header;
+/// use routerify_ng::{append_vary, Middleware, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .middleware(Middleware::post(|mut res| async move {
+///             append_vary(&mut res, header::ACCEPT_ENCODING);
+///             Ok(res)
+///         }))
+///         .middleware(Middleware::post(|mut res| async move {
+///             append_vary(&mut res, header::ACCEPT_LANGUAGE);
+///             Ok(res)
+///         }))
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+pub fn append_vary<B, H: AsRef<str>>(res: &mut Response<B>, header_name: H) {
+    let header_name = header_name.as_ref().trim();
+
+    let mut names = Vec::new();
+    for value in res.headers().get_all(header::VARY) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if name == "*" {
+                return;
+            }
+            if !names
+                .iter()
+                .any(|existing: &String| existing.eq_ignore_ascii_case(name))
+            {
+                names.push(name.to_owned());
+            }
+        }
+    }
+
+    if !names.iter().any(|existing| existing.eq_ignore_ascii_case(header_name)) {
+        names.push(header_name.to_owned());
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&names.join(", ")) {
+        res.headers_mut().insert(header::VARY, value);
+    }
+}
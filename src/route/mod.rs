@@ -1,29 +1,42 @@
 use crate::Error;
+use crate::constants;
+use crate::data_map::{PrivateDataMaps, SharedDataMap};
 use crate::helpers;
-use crate::regex_generator::generate_exact_match_regex;
+use crate::metrics::MatchedRoute;
+use crate::regex_generator::{
+    ends_with_glob, generate_constrained_exact_match_regex, generate_exact_match_regex, inline_param_constraints,
+    validate_path,
+};
+use crate::router::ErrHandler;
 use crate::types::{RequestMeta, RouteParams};
 use http_body_util::Full;
-use hyper::body::Bytes;
-use hyper::{Method, Request, Response};
+use hyper::body::{Body, Bytes};
+use hyper::http::uri::PathAndQuery;
+use hyper::{Method, Request, Response, Uri};
 use regex::Regex;
+use std::collections::HashMap;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
-type Handler<E> = Box<dyn Fn(Request<Full<Bytes>>) -> HandlerReturn<E> + Send + Sync + 'static>;
-type HandlerReturn<E> = Box<dyn Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static>;
+type Handler<E, B> = Box<dyn Fn(Request<Full<Bytes>>) -> HandlerReturn<E, B> + Send + Sync + 'static>;
+type HandlerReturn<E, B> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
+
+pub(crate) type Guard = Box<dyn Fn(&Request<Full<Bytes>>) -> bool + Send + Sync + 'static>;
 
 /// Represents a single route.
 ///
 /// A route consists of a path, http method type(s) and a handler. It shouldn't be created directly, use [RouterBuilder](./struct.RouterBuilder.html) methods
 /// to create a route.
 ///
-/// This `Route<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Route<E, B>` type accepts two type parameters: `E` and `B`.
 ///
-/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
-///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
-///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html) trait with `Bytes` data, and be creatable from `Bytes` for the responses
+///   generated by the router itself. It defaults to [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html).
 ///
 /// # Examples
 ///
@@ -44,32 +57,64 @@ type HandlerReturn<E> = Box<dyn Future<Output = Result<Response<Full<Bytes>>, E>
 ///     router
 /// }
 /// ```
-pub struct Route<E> {
+pub struct Route<E, B = Full<Bytes>> {
     pub(crate) path: String,
     pub(crate) regex: Regex,
     route_params: Vec<String>,
     // Make it an option so that when a router is used to scope in another router,
     // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
-    pub(crate) handler: Option<Handler<E>>,
+    pub(crate) handler: Option<Handler<E, B>>,
     pub(crate) methods: Vec<Method>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
+    // The path of the scope the route was mounted at, empty for the routes of the top level router.
+    pub(crate) scope_path: String,
+    // The scope prefix which is hidden from the handler's view of the request path,
+    // see `RouterBuilder::strip_prefix_from_scope`.
+    pub(crate) strip_prefix: Option<String>,
+    // The route is skipped when the guard rejects the request.
+    pub(crate) guard: Option<Guard>,
+    // The data which is visible only to the handlers of the router this route was added to
+    // and its ancestors' private data, in the lookup order.
+    pub(crate) private_data_maps: Vec<SharedDataMap>,
+    // The deadline of the handler, overriding the router-wide one.
+    pub(crate) timeout: Option<Duration>,
+    // The constraints applied to the route params, see `RouterBuilder::param_constraint`.
+    pub(crate) param_constraints: HashMap<String, String>,
+    // Whether the request body is buffered before the handler runs, see `RouterBuilder::needs_body`.
+    pub(crate) needs_body: bool,
+    // Whether it handles the requests not matched by the other routes of its scope, see `RouterBuilder::not_found`.
+    pub(crate) not_found: bool,
+    // Whether it's matched only by its guard, see `RouterBuilder::custom_route`.
+    pub(crate) custom: bool,
+    // Whether the path was declared with a trailing slash, see `RouterBuilder::trailing_slash`.
+    pub(crate) trailing_slash: bool,
+    // The error handler of the innermost scope which has one, see `RouterBuilder::scope`.
+    pub(crate) scope_err_handler: Option<Arc<ErrHandler<B>>>,
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Route<E> {
+impl<E, B> Route<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     pub(crate) fn new_with_boxed_handler<P: Into<String>>(
         path: P,
         methods: Vec<Method>,
-        handler: Handler<E>,
+        handler: Handler<E, B>,
         scope_depth: u32,
-    ) -> crate::Result<Route<E>> {
+    ) -> crate::Result<Route<E, B>> {
         let path = path.into();
+        validate_path(path.as_str())?;
+
         let (re, params) = generate_exact_match_regex(path.as_str()).map_err(|e| {
             Error::new(format!(
                 "Could not create an exact match regex for the route path: {}",
                 e
             ))
         })?;
+        // The inline constraints take precedence over the ones of the routers.
+        let param_constraints = inline_param_constraints(path.as_str());
 
         Ok(Route {
             path,
@@ -78,30 +123,104 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Route<E> {
             handler: Some(handler),
             methods,
             scope_depth,
+            scope_path: String::new(),
+            strip_prefix: None,
+            guard: None,
+            private_data_maps: Vec::new(),
+            timeout: None,
+            param_constraints,
+            needs_body: true,
+            not_found: false,
+            custom: false,
+            trailing_slash: false,
+            scope_err_handler: None,
         })
     }
 
-    pub(crate) fn new<P, H, R>(path: P, methods: Vec<Method>, handler: H) -> crate::Result<Route<E>>
+    pub(crate) fn new<P, H, R>(path: P, methods: Vec<Method>, handler: H) -> crate::Result<Route<E, B>>
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
-        let handler: Handler<E> = Box::new(move |req: Request<Full<Bytes>>| Box::new(handler(req)));
+        let handler: Handler<E, B> = Box::new(move |req: Request<Full<Bytes>>| Box::new(handler(req)));
         Route::new_with_boxed_handler(path, methods, handler, 1)
     }
 
+    // Applies the constraints of the params which don't have one yet, i.e. the ones of the nested scopes win.
+    pub(crate) fn apply_param_constraints(&mut self, param_constraints: &HashMap<String, String>) -> crate::Result<()> {
+        let mut changed = false;
+        for name in self.route_params.iter() {
+            if let Some(constraint) = param_constraints.get(name)
+                && !self.param_constraints.contains_key(name)
+            {
+                self.param_constraints.insert(name.clone(), constraint.clone());
+                changed = true;
+            }
+        }
+
+        if changed {
+            let (re, _) =
+                generate_constrained_exact_match_regex(self.path.as_str(), &self.param_constraints).map_err(|e| {
+                    Error::new(format!(
+                        "Could not create an exact match regex for the route path: {}",
+                        e
+                    ))
+                })?;
+            self.regex = re;
+        }
+
+        Ok(())
+    }
+
+    // The path template reported to the handlers, the middlewares and the metrics, i.e. without the trailing slash
+    // appended for matching.
+    pub(crate) fn matched_path(&self) -> &str {
+        match self.path.strip_suffix('/') {
+            Some(path) if !path.is_empty() => path,
+            _ => self.path.as_str(),
+        }
+    }
+
+    // The path template as it was declared, i.e. keeping only the trailing slash which was declared.
+    pub(crate) fn declared_path(&self) -> &str {
+        if self.trailing_slash {
+            return self.path.as_str();
+        }
+        self.matched_path()
+    }
+
     pub(crate) fn is_match_method(&self, method: &Method) -> bool {
         self.methods.contains(method)
     }
 
-    pub(crate) async fn process(
-        &self,
-        target_path: &str,
-        mut req: Request<Full<Bytes>>,
-    ) -> crate::Result<Response<Full<Bytes>>> {
+    // Checks whether the request path's trailing slash agrees with the declared one, under the strict matching.
+    pub(crate) fn is_match_trailing_slash(&self, trailing_slash: bool) -> bool {
+        ends_with_glob(self.path.as_str()) || self.trailing_slash == trailing_slash
+    }
+
+    pub(crate) fn is_any_method(&self) -> bool {
+        self.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..]
+    }
+
+    pub(crate) fn is_match_guard(&self, req: &Request<Full<Bytes>>) -> bool {
+        self.guard.as_ref().is_none_or(|guard| guard(req))
+    }
+
+    pub(crate) async fn process(&self, target_path: &str, mut req: Request<Full<Bytes>>) -> crate::Result<Response<B>> {
         self.push_req_meta(target_path, &mut req);
 
+        if let Some(matched_route) = req.extensions().get::<MatchedRoute>() {
+            matched_route.set(self.matched_path());
+        }
+
+        if let Some(ref prefix) = self.strip_prefix {
+            strip_req_path_prefix(&mut req, prefix)?;
+        }
+
+        req.extensions_mut()
+            .insert(PrivateDataMaps(self.private_data_maps.clone()));
+
         let handler = self
             .handler
             .as_ref()
@@ -130,6 +249,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Route<E> {
             let mut iter = caps.iter();
             // Skip the first match because it's the whole path.
             iter.next();
+            // The groups of the absent optional params don't match, but they still keep their position.
             for param in route_params_list {
                 if let Some(Some(g)) = iter.next() {
                     route_params.set(param.clone(), g.as_str());
@@ -141,7 +261,46 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Route<E> {
     }
 }
 
-impl<E> Debug for Route<E> {
+// Removes as many leading segments from the request path as the scope prefix consists of,
+// so that it works for the prefixes having route parameters too.
+fn strip_req_path_prefix(req: &mut Request<Full<Bytes>>, prefix: &str) -> crate::Result<()> {
+    let prefix_segments = prefix.split('/').filter(|s| !s.is_empty()).count();
+
+    let path = req.uri().path();
+    let stripped_path = path.split('/').filter(|s| !s.is_empty()).skip(prefix_segments).fold(
+        String::with_capacity(path.len()),
+        |mut acc, segment| {
+            acc.push('/');
+            acc.push_str(segment);
+            acc
+        },
+    );
+
+    let mut new_path_and_query = if stripped_path.is_empty() {
+        "/".to_owned()
+    } else {
+        stripped_path
+    };
+    if path.len() > 1 && path.ends_with('/') && !new_path_and_query.ends_with('/') {
+        new_path_and_query.push('/');
+    }
+    if let Some(query) = req.uri().query() {
+        new_path_and_query.push('?');
+        new_path_and_query.push_str(query);
+    }
+
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = Some(
+        PathAndQuery::from_maybe_shared(new_path_and_query)
+            .map_err(|e| Error::new(format!("Couldn't strip the scope prefix from the request path: {}", e)))?,
+    );
+    *req.uri_mut() = Uri::from_parts(parts)
+        .map_err(|e| Error::new(format!("Couldn't strip the scope prefix from the request path: {}", e)))?;
+
+    Ok(())
+}
+
+impl<E, B> Debug for Route<E, B> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
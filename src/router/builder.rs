@@ -1,25 +1,35 @@
+use crate::body::BoxBody;
 use crate::constants;
-use crate::data_map::{DataMap, ScopedDataMap};
-use crate::middleware::{Middleware, PostMiddleware, PreMiddleware};
+use crate::data_map::{DataMap, ScopedDataMap, SharedDataMap};
+use crate::error::{ErrStatusMapper, ErrorStatus, err_status_of};
+use crate::maintenance::Maintenance;
+use crate::metrics::MetricsRecorder;
+use crate::middleware::{AroundMiddleware, Middleware, PostMiddleware, PreMiddleware};
+use crate::regex_generator::{ends_with_glob, validate_param_constraint};
 use crate::route::Route;
 use crate::router::Router;
-use crate::router::{ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo};
-use crate::types::RequestInfo;
+use crate::router::{ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo, PathCanonicalizer, ResponseMapper};
+use crate::static_files::StaticFiles;
+use crate::types::{BuildReport, RequestInfo, TrailingSlashPolicy, TrustConfig};
 use http_body_util::Full;
-use hyper::body::Bytes;
+use hyper::body::{Body, Bytes};
 use hyper::{Method, Request, Response};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::future::Future;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Builder for the [Router](./struct.Router.html) type.
 ///
-/// This `RouterBuilder<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `RouterBuilder<E, B>` type accepts two type parameters: `E` and `B`.
 ///
-/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
-///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
-///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html) trait with `Bytes` data, and be creatable from `Bytes`. It defaults to
+///   [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html).
 ///
 /// # Examples
 ///
@@ -55,27 +65,101 @@ use std::sync::Arc;
 ///     router
 /// }
 /// ```
-pub struct RouterBuilder<E> {
-    inner: crate::Result<BuilderInner<E>>,
+pub struct RouterBuilder<E, B = Full<Bytes>> {
+    inner: crate::Result<BuilderInner<E, B>>,
 }
 
-struct BuilderInner<E> {
-    pre_middlewares: Vec<PreMiddleware<E>>,
-    routes: Vec<Route<E>>,
-    post_middlewares: Vec<PostMiddleware<E>>,
+// Constructs the error handler at build time from the router's data.
+type ErrHandlerFactory<B> = Box<dyn FnOnce(Option<&DataMap>) -> crate::Result<ErrHandler<B>> + Send + 'static>;
+type Finalizer<E, B> = Box<dyn FnOnce(&mut Router<E, B>) -> crate::Result<()> + Send + 'static>;
+
+struct BuilderInner<E, B> {
+    pre_middlewares: Vec<PreMiddleware<E, B>>,
+    routes: Vec<Route<E, B>>,
+    post_middlewares: Vec<PostMiddleware<E, B>>,
+    around_middlewares: Vec<AroundMiddleware<E, B>>,
     data_maps: HashMap<String, Vec<DataMap>>,
-    err_handler: Option<ErrHandler>,
+    private_data_map: Option<DataMap>,
+    err_handler: Option<ErrHandler<B>>,
+    err_handler_factory: Option<ErrHandlerFactory<B>>,
+    err_status_mappers: Vec<ErrStatusMapper>,
+    strip_prefix_from_scope: bool,
+    debug_404: bool,
+    problem_json_errors: bool,
+    minimal: bool,
+    trailing_slash: TrailingSlashPolicy,
+    allow_trace: bool,
+    auto_content_length: bool,
+    server_timing: bool,
+    defer_body_reading: bool,
+    max_headers: Option<usize>,
+    max_header_bytes: Option<usize>,
+    body_limit: Option<usize>,
+    max_requests_per_connection: Option<usize>,
+    trust_forwarded_proto: bool,
+    trust_proxy: Option<Arc<TrustConfig>>,
+    #[cfg(feature = "decompression")]
+    decompress_requests: Option<usize>,
+    path_canonicalizer: Option<PathCanonicalizer>,
+    fallback: Option<Box<Router<E, B>>>,
+    handler_timeout: Option<Duration>,
+    regex_size_limit: Option<usize>,
+    regex_set_size_limit: Option<usize>,
+    param_constraints: HashMap<String, String>,
+    maintenance: Maintenance,
+    metrics: Option<Arc<dyn MetricsRecorder>>,
+    response_mappers: Vec<ResponseMapper<B>>,
+    regex_compilation: Duration,
+    finalizers: Vec<Finalizer<E, B>>,
+    // The routes added by the immediately preceding call, which the per-route options like `with_timeout` apply to.
+    last_routes: Range<usize>,
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<E> {
+impl<E, B> RouterBuilder<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     /// Creates a new `RouterBuilder` instance with default options.
-    pub fn new() -> RouterBuilder<E> {
+    pub fn new() -> RouterBuilder<E, B> {
         RouterBuilder::default()
     }
 
     /// Creates a new [Router](./struct.Router.html) instance from the added configuration.
-    pub fn build(self) -> crate::Result<Router<E>> {
-        self.inner.and_then(|inner| {
+    pub fn build(self) -> crate::Result<Router<E, B>> {
+        self.inner.and_then(|mut inner| {
+            if let Some(err_handler_factory) = inner.err_handler_factory.take() {
+                let data_map = inner.data_maps.get("/*").and_then(|data_map_arr| data_map_arr.first());
+                inner.err_handler = Some(err_handler_factory(data_map)?);
+            }
+
+            // The sort is stable, so the registration order is kept for the equal priorities.
+            inner.pre_middlewares.sort_by_key(|m| m.priority);
+            inner.post_middlewares.sort_by_key(|m| m.priority);
+            inner.around_middlewares.sort_by_key(|m| m.priority);
+
+            for route in inner.routes.iter_mut() {
+                route.apply_param_constraints(&inner.param_constraints)?;
+            }
+
+            // The private data is looked up after the one of the nested scopes, which was attached
+            // when they were mounted.
+            if let Some(private_data_map) = inner.private_data_map {
+                let private_data_map = SharedDataMap::new(Arc::new(private_data_map));
+                for route in inner.routes.iter_mut() {
+                    route.private_data_maps.push(private_data_map.clone());
+                }
+                for pre_middleware in inner.pre_middlewares.iter_mut() {
+                    pre_middleware.private_data_maps.push(private_data_map.clone());
+                }
+                for around_middleware in inner.around_middlewares.iter_mut() {
+                    around_middleware.private_data_maps.push(private_data_map.clone());
+                }
+                for post_middleware in inner.post_middlewares.iter_mut() {
+                    post_middleware.private_data_maps.push(private_data_map.clone());
+                }
+            }
+
             let scoped_data_maps = inner
                 .data_maps
                 .into_iter()
@@ -87,27 +171,155 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
                 })
                 .collect::<Result<Vec<ScopedDataMap>, crate::RouteError>>()?;
 
-            Ok(Router::new(
+            let mut router = Router::new(
                 inner.pre_middlewares,
                 inner.routes,
                 inner.post_middlewares,
                 scoped_data_maps,
                 inner.err_handler,
-            ))
+            );
+            router.around_middlewares = inner.around_middlewares;
+            router.err_status_mappers = inner.err_status_mappers;
+            router.strip_prefix_from_scope = inner.strip_prefix_from_scope;
+            router.debug_404 = inner.debug_404;
+            router.problem_json_errors = inner.problem_json_errors;
+            router.minimal = inner.minimal;
+            router.trailing_slash = inner.trailing_slash;
+            router.allow_trace = inner.allow_trace;
+            router.auto_content_length = inner.auto_content_length;
+            router.server_timing = inner.server_timing;
+            router.defer_body_reading = inner.defer_body_reading;
+            router.max_headers = inner.max_headers;
+            router.max_header_bytes = inner.max_header_bytes;
+            router.body_limit = inner.body_limit;
+            router.max_requests_per_connection = inner.max_requests_per_connection;
+            router.trust_forwarded_proto = inner.trust_forwarded_proto;
+            router.trust_proxy = inner.trust_proxy;
+            #[cfg(feature = "decompression")]
+            {
+                router.decompress_requests = inner.decompress_requests;
+            }
+            router.path_canonicalizer = inner.path_canonicalizer;
+            router.fallback = inner.fallback;
+            router.handler_timeout = inner.handler_timeout;
+            router.regex_size_limit = inner.regex_size_limit;
+            router.regex_set_size_limit = inner.regex_set_size_limit;
+            router.param_constraints = inner.param_constraints;
+            router.maintenance = inner.maintenance;
+            router.metrics = inner.metrics;
+            router.response_mappers = inner.response_mappers;
+
+            for finalizer in inner.finalizers {
+                finalizer(&mut router)?;
+            }
+
+            // Compile the route table eagerly, so that the oversized one is reported here instead of when serving.
+            if router.regex_size_limit.is_some() || router.regex_set_size_limit.is_some() {
+                router.init_regex_set()?;
+            }
+
+            Ok(router)
         })
     }
 
+    /// Creates a new [Router](./struct.Router.html) instance like [`build`](#method.build), prepares it for serving and
+    /// reports how long each step took along with the number of routes and middlewares.
+    ///
+    /// Preparing it adds the default `OPTIONS` and 404 routes and compiles the route matching
+    /// [`RegexSet`](https://docs.rs/regex/1/regex/struct.RegexSet.html) of the resulting table, which the
+    /// [`RouterService`](./struct.RouterService.html) then reuses instead of compiling it again. Hence the router is
+    /// meant to be served rather than mounted via [`scope`](#method.scope).
+    ///
+    /// It can be used to monitor the startup cost of large route tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let (router, report) = Router::builder()
+    ///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("home")))) })
+    ///         .build_timed()
+    ///         .unwrap();
+    ///
+    ///     println!("Built {} routes in {:?}", report.route_count(), report.total());
+    ///     router
+    /// }
+    /// ```
+    pub fn build_timed(self) -> crate::Result<(Router<E, B>, BuildReport)> {
+        let regex_compilation = self
+            .inner
+            .as_ref()
+            .map(|inner| inner.regex_compilation)
+            .unwrap_or_default();
+
+        let started = Instant::now();
+        let mut router = self.build()?;
+        let validation = started.elapsed();
+
+        let route_count = router.routes.len();
+
+        let started = Instant::now();
+        router.init()?;
+        let regex_set_construction = started.elapsed();
+
+        let report = BuildReport {
+            regex_compilation,
+            validation,
+            regex_set_construction,
+            route_count,
+            pre_middleware_count: router.pre_middlewares.len(),
+            post_middleware_count: router.post_middlewares.len(),
+            around_middleware_count: router.around_middlewares.len(),
+        };
+
+        Ok((router, report))
+    }
+
     fn and_then<F>(self, func: F) -> Self
     where
-        F: FnOnce(BuilderInner<E>) -> crate::Result<BuilderInner<E>>,
+        F: FnOnce(BuilderInner<E, B>) -> crate::Result<BuilderInner<E, B>>,
+    {
+        RouterBuilder {
+            inner: self.inner.and_then(|mut inner| {
+                inner.last_routes = 0..0;
+                func(inner)
+            }),
+        }
+    }
+
+    // Modifies the routes added by the immediately preceding call, failing if that call didn't add any route.
+    fn and_then_last_route<F>(self, option: &'static str, mut func: F) -> Self
+    where
+        F: FnMut(&mut Route<E, B>) -> crate::Result<()>,
     {
         RouterBuilder {
-            inner: self.inner.and_then(func),
+            inner: self.inner.and_then(|mut inner| {
+                if inner.last_routes.is_empty() {
+                    return Err(crate::Error::new(format!(
+                        "The `{}` option must directly follow a call adding routes, e.g. `get`",
+                        option
+                    ))
+                    .into());
+                }
+                for idx in inner.last_routes.clone() {
+                    func(&mut inner.routes[idx])?;
+                }
+                crate::Result::Ok(inner)
+            }),
         }
     }
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<E> {
+impl<E, B> RouterBuilder<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     /// Adds a new route with `GET` method and the handler at the specified path.
     ///
     /// # Examples
@@ -130,11 +342,175 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::GET], handler)
     }
 
+    /// Adds a new route with `GET` method and the same handler at each of the specified paths, e.g. for the aliases.
+    ///
+    /// The handler is shared by the routes instead of being cloned, so the state it captures is shared too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::Router;
+    ///
+    /// async fn home_handler(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, hyper::Error> {
+    ///     Ok(Response::new(Full::new(Bytes::from("home"))))
+    /// }
+    ///
+    /// fn run() -> Router<hyper::Error> {
+    ///     let router = Router::builder().get_many(["/", "/home"], home_handler).build().unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn get_many<I, P, H, R>(self, paths: I, handler: H) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+        H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+
+        let mut count = 0;
+        let builder = paths.into_iter().fold(self, |builder, path| {
+            let handler = handler.clone();
+            count += 1;
+            builder.get(path, move |req| handler(req))
+        });
+
+        // The per-route options following this call apply to all of its routes.
+        RouterBuilder {
+            inner: builder.inner.map(|mut inner| {
+                inner.last_routes = inner.routes.len() - count..inner.routes.len();
+                inner
+            }),
+        }
+    }
+
+    /// Adds a new route with `GET` method and a synchronous handler at the specified path, which runs on the Tokio
+    /// blocking thread pool via [`spawn_blocking`](https://docs.rs/tokio/1/tokio/task/fn.spawn_blocking.html), so that
+    /// the CPU-bound or blocking work doesn't stall the other requests.
+    ///
+    /// A panic of the handler is propagated as if it was raised by an async handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::Router;
+    ///
+    /// fn report_handler(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, std::io::Error> {
+    ///     let report = std::fs::read_to_string("report.txt")?;
+    ///     Ok(Response::new(Full::new(Bytes::from(report))))
+    /// }
+    ///
+    /// fn run() -> Router<std::io::Error> {
+    ///     let router = Router::builder().get_blocking("/report", report_handler).build().unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn get_blocking<P, H>(self, path: P, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<Full<Bytes>>) -> Result<Response<B>, E> + Send + Sync + 'static,
+        E: Send,
+    {
+        let handler = Arc::new(handler);
+
+        self.get(path, move |req| {
+            let handler = handler.clone();
+
+            async move {
+                match tokio::task::spawn_blocking(move || handler(req)).await {
+                    Ok(res) => res,
+                    Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                    Err(err) => panic!("The blocking handler couldn't complete: {}", err),
+                }
+            }
+        })
+    }
+
+    /// Adds a new route with `GET` method and the handler at the specified path which matches only when the `guard`
+    /// returns `true` for the request. Otherwise, the next matching route is tried.
+    ///
+    /// It allows to have multiple routes at the same path and method distinguished by the runtime conditions, e.g. a
+    /// header presence or a feature flag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::Router;
+    ///
+    /// async fn beta_handler(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, hyper::Error> {
+    ///     Ok(Response::new(Full::new(Bytes::from("beta home"))))
+    /// }
+    ///
+    /// async fn home_handler(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, hyper::Error> {
+    ///     Ok(Response::new(Full::new(Bytes::from("home"))))
+    /// }
+    ///
+    /// fn run() -> Router<hyper::Error> {
+    ///     let router = Router::builder()
+    ///         .get_guarded("/", |req| req.headers().contains_key("x-beta"), beta_handler)
+    ///         .get("/", home_handler)
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn get_guarded<P, G, H, R>(self, path: P, guard: G, handler: H) -> Self
+    where
+        P: Into<String>,
+        G: Fn(&Request<Full<Bytes>>) -> bool + Send + Sync + 'static,
+        H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.add_guarded(path, vec![Method::GET], guard, handler)
+    }
+
+    /// Adds a new route with `GET` method and the handler at the specified path, which must respond within the `timeout`,
+    /// overriding the router-wide [`handler_timeout`](#method.handler_timeout).
+    ///
+    /// If the handler takes longer, a `503 Service Unavailable` response is sent instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::Router;
+    /// use std::time::Duration;
+    ///
+    /// async fn export_handler(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, hyper::Error> {
+    ///     Ok(Response::new(Full::new(Bytes::from("export"))))
+    /// }
+    ///
+    /// fn run() -> Router<hyper::Error> {
+    ///     let router = Router::builder()
+    ///         .handler_timeout(Duration::from_secs(5))
+    ///         .get_with_timeout("/export", export_handler, Duration::from_secs(60))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn get_with_timeout<P, H, R>(self, path: P, handler: H, timeout: Duration) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.get(path, handler).with_timeout(timeout)
+    }
+
     /// Adds a new route with `GET` and `HEAD` methods and the handler at the specified path.
     ///
     /// # Examples
@@ -156,7 +532,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::GET, Method::HEAD], handler)
     }
@@ -182,11 +558,82 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::POST], handler)
     }
 
+    /// Adds a new route with `POST` method at the specified path which validates the JSON request body against the
+    /// [JSON Schema](https://json-schema.org/) before the handler runs, and passes the body deserialized as `T` to the
+    /// handler.
+    ///
+    /// A body which isn't valid JSON is answered with `400 Bad Request`, and a body violating the schema or not fitting
+    /// into `T` is answered with `422 Unprocessable Entity`, without running the handler. The error response has a JSON
+    /// body listing the errors as `{"errors": [{"path": "/age", "message": "..."}]}`. An invalid schema fails the
+    /// router build.
+    ///
+    /// It's available with the `json-schema` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::Router;
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    /// use std::convert::Infallible;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct NewUser {
+    ///     name: String,
+    /// }
+    ///
+    /// async fn create_user_handler(_: Request<Full<Bytes>>, user: NewUser) -> Result<Response<Full<Bytes>>, Infallible> {
+    ///     Ok(Response::new(Full::new(Bytes::from(format!("Created {}", user.name)))))
+    /// }
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let schema = json!({
+    ///         "type": "object",
+    ///         "properties": { "name": { "type": "string", "minLength": 1 } },
+    ///         "required": ["name"]
+    ///     });
+    ///
+    ///     let router = Router::builder()
+    ///         .post_json_validated("/users", schema, create_user_handler)
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    #[cfg(feature = "json-schema")]
+    pub fn post_json_validated<P, T, H, R>(self, path: P, schema: serde_json::Value, handler: H) -> Self
+    where
+        P: Into<String>,
+        T: serde::de::DeserializeOwned + Send + 'static,
+        H: Fn(Request<Full<Bytes>>, T) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let validator = match crate::json_schema::compile(&schema) {
+            Ok(validator) => Arc::new(validator),
+            Err(err) => return self.and_then(move |_| Err(err)),
+        };
+        let handler = Arc::new(handler);
+
+        self.post(path, move |req| {
+            let validator = validator.clone();
+            let handler = handler.clone();
+
+            async move {
+                match crate::json_schema::extract::<T, B>(&req, &validator).await {
+                    Ok(body) => handler(req, body).await,
+                    Err(resp) => Ok(resp),
+                }
+            }
+        })
+    }
+
     /// Adds a new route with `PUT` method and the handler at the specified path.
     ///
     /// # Examples
@@ -210,7 +657,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::PUT], handler)
     }
@@ -243,7 +690,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::DELETE], handler)
     }
@@ -271,7 +718,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::HEAD], handler)
     }
@@ -301,7 +748,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::TRACE], handler)
     }
@@ -331,7 +778,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::CONNECT], handler)
     }
@@ -364,13 +811,17 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::PATCH], handler)
     }
 
     /// Adds a new route with `OPTIONS` method and the handler at the specified path.
     ///
+    /// Unless an `OPTIONS` route is added at the `/*` path, the root router answers the `OPTIONS` requests of the other
+    /// paths with `204 No Content`. That global route is tried after all the routes added here, so it never overrides
+    /// them, e.g. a CORS preflight handler.
+    ///
     /// # Examples
     ///
     /// ```
@@ -394,7 +845,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, vec![Method::OPTIONS], handler)
     }
@@ -435,7 +886,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     pub fn any<H, R>(self, handler: H) -> Self
     where
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler)
     }
@@ -469,7 +920,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.add(path, constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler)
     }
@@ -504,82 +955,426 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     where
         P: Into<String>,
         H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<Full<Bytes>>, E>> + Send + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.and_then(move |mut inner| {
             let mut path = path.into();
+            let trailing_slash = path.len() > 1 && path.ends_with('/');
 
-            if !path.ends_with('/') && !path.ends_with('*') {
+            if !path.ends_with('/') && !ends_with_glob(path.as_str()) {
                 path.push('/');
             }
 
-            let route = Route::new(path, methods, handler)?;
+            let started = Instant::now();
+            let mut route = Route::new(path, methods, handler)?;
+            route.trailing_slash = trailing_slash;
+            inner.regex_compilation += started.elapsed();
             inner.routes.push(route);
+            inner.last_routes = inner.routes.len() - 1..inner.routes.len();
 
             crate::Result::Ok(inner)
         })
     }
 
-    /// It mounts a router onto another router. It can be very useful when you want to write modular routing logic.
+    /// Adds a new route with the specified method(s) and the handler at the specified path which matches only when the
+    /// `guard` returns `true` for the request. Otherwise, the next matching route is tried.
     ///
     /// # Examples
     ///
     /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Method, Request, Response};
     /// use routerify_ng::Router;
-    /// use hyper::body::Incoming;
-    /// use std::convert::Infallible;
-    ///
-    /// mod api {
-    ///     use http_body_util::Full;
-    ///     use hyper::{body::Bytes, Response};
-    ///     use routerify_ng::Router;
-    ///     use hyper::body::Incoming;
-    ///     use std::convert::Infallible;
     ///
-    ///     pub fn router() -> Router<Infallible> {
-    ///         Router::builder()
-    ///             .get("/users", |_| async move {
-    ///                 Ok(Response::new(Full::new(Bytes::from("User list"))))
-    ///             })
-    ///             .get("/books", |_| async move {
-    ///                 Ok(Response::new(Full::new(Bytes::from("Book list"))))
-    ///             })
-    ///             .build()
-    ///             .unwrap()
-    ///     }
+    /// async fn json_handler(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, hyper::Error> {
+    ///     Ok(Response::new(Full::new(Bytes::from("{}"))))
     /// }
     ///
-    /// fn run() -> Router<Infallible> {
-    ///     let router: Router<Infallible> = Router::builder()
-    ///         .scope("/api", api::router())
+    /// fn run() -> Router<hyper::Error> {
+    ///     let router = Router::builder()
+    ///         .add_guarded(
+    ///             "/items",
+    ///             vec![Method::GET, Method::POST],
+    ///             |req| req.headers().get("accept").is_some_and(|v| v == "application/json"),
+    ///             json_handler,
+    ///         )
     ///         .build()
     ///         .unwrap();
     ///     router
     /// }
     /// ```
-    ///
-    /// Now, the app can handle requests on: `/api/users` and `/api/books` paths.
-    pub fn scope<P>(self, path: P, mut router: Router<E>) -> Self
+    pub fn add_guarded<P, G, H, R>(self, path: P, methods: Vec<Method>, guard: G, handler: H) -> Self
     where
         P: Into<String>,
+        G: Fn(&Request<Full<Bytes>>) -> bool + Send + Sync + 'static,
+        H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
-        let mut path = path.into();
+        let guard = Arc::new(guard);
 
-        if path.ends_with('/') {
-            path = path[..path.len() - 1].to_string();
-        }
+        self.add(path, methods, handler)
+            .and_then_last_route("add_guarded", move |route| {
+                let guard = guard.clone();
+                route.guard = Some(Box::new(move |req: &Request<Full<Bytes>>| guard(req)));
+                Ok(())
+            })
+    }
 
-        let mut builder = self;
+    /// Adds a new route with any method type whose matching is decided entirely by the `matcher` predicate instead of
+    /// the path, e.g. to route on a combination of the headers and the query.
+    ///
+    /// The custom routes are tried after all the regular routes, in the order they were added, and before the
+    /// [`not_found`](#method.not_found) handlers. When this router is mounted via [`scope`](#method.scope), the
+    /// predicate only sees the requests under the scope path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .custom_route(
+    ///             |req| req.uri().query().is_some_and(|query| query.contains("legacy=1")),
+    ///             |_| async move { Ok(Response::new(Full::new(Bytes::from("Legacy API")))) },
+    ///         )
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn custom_route<M, H, R>(self, matcher: M, handler: H) -> Self
+    where
+        M: Fn(&Request<Full<Bytes>>) -> bool + Send + Sync + 'static,
+        H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.add_guarded("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), matcher, handler)
+            .and_then_last_route("custom_route", |route| {
+                route.custom = true;
+                Ok(())
+            })
+    }
 
-        for pre_middleware in router.pre_middlewares.iter_mut() {
-            let new_pre_middleware = PreMiddleware::new_with_boxed_handler(
-                format!("{}{}", path.as_str(), pre_middleware.path.as_str()),
-                pre_middleware
-                    .handler
-                    .take()
-                    .expect("No handler found in one of the pre-middlewares"),
+    /// Declares whether the routes added by the preceding call need the request body, which is `true` by default.
+    ///
+    /// The body of a request matching only the routes which don't need it isn't read before the route handler runs,
+    /// so that the handler can respond, e.g. reject the request, before the body arrives. The handler and the pre
+    /// middlewares see an empty body, and the body can still be read on demand via
+    /// [`RequestExt::body_bytes`](./ext/trait.RequestExt.html#tymethod.body_bytes).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response, StatusCode};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .post("/legacy-upload", |_| async move {
+    ///             Ok(Response::builder()
+    ///                 .status(StatusCode::GONE)
+    ///                 .body(Full::new(Bytes::new()))
+    ///                 .unwrap())
+    ///         })
+    ///         .needs_body(false)
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn needs_body(self, needs_body: bool) -> Self {
+        self.and_then_last_route("needs_body", move |route| {
+            route.needs_body = needs_body;
+            Ok(())
+        })
+    }
+
+    /// Sets the deadline of the routes added by the preceding call, overriding the router-wide
+    /// [`handler_timeout`](#method.handler_timeout).
+    ///
+    /// If the handler takes longer, a `503 Service Unavailable` response is sent instead. The build fails if the
+    /// preceding call didn't register routes, e.g. a [`scope`](#method.scope) one. It applies to each path of
+    /// [`get_many`](#method.get_many).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Request, Response};
+    /// use routerify_ng::Router;
+    /// use std::time::Duration;
+    ///
+    /// async fn import_handler(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, hyper::Error> {
+    ///     Ok(Response::new(Full::new(Bytes::from("import"))))
+    /// }
+    ///
+    /// fn run() -> Router<hyper::Error> {
+    ///     let router = Router::builder()
+    ///         .handler_timeout(Duration::from_secs(5))
+    ///         .post("/import", import_handler)
+    ///         .with_timeout(Duration::from_secs(60))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.and_then_last_route("with_timeout", move |route| {
+            route.timeout = Some(timeout);
+            Ok(())
+        })
+    }
+
+    /// Attaches the metadata to the routes added by the preceding call, which their handlers can access via
+    /// [`RequestExt::data`](./ext/trait.RequestExt.html#tymethod.data). It takes precedence over the data of the
+    /// router.
+    ///
+    /// The build fails if the preceding call didn't register routes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::{ext::RequestExt, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// #[derive(Clone)]
+    /// struct RequiredScope(&'static str);
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/admin", |req| async move {
+    ///             let scope = req.data::<RequiredScope>().unwrap();
+    ///             Ok(Response::new(Full::new(Bytes::from(scope.0))))
+    ///         })
+    ///         .with_meta(RequiredScope("admin"))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn with_meta<T: Send + Sync + Clone + 'static>(self, meta: T) -> Self {
+        let mut data_map = DataMap::new();
+        data_map.insert(meta);
+        let data_map = SharedDataMap::new(Arc::new(data_map));
+
+        self.and_then_last_route("with_meta", move |route| {
+            route.private_data_maps.insert(0, data_map.clone());
+            Ok(())
+        })
+    }
+
+    /// Sets the handler of the errors raised by the routes added by the preceding call, taking precedence over the
+    /// [`err_handler`](#method.err_handler) of the router and the scoped ones.
+    ///
+    /// The build fails if the preceding call didn't register routes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response, StatusCode};
+    /// use routerify_ng::Router;
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .get("/webhook", |_| async move { Err(routerify_ng::Error::new("Invalid signature")) })
+    ///         .with_err_handler(|err| async move {
+    ///             Response::builder()
+    ///                 .status(StatusCode::UNAUTHORIZED)
+    ///                 .body(Full::new(Bytes::from(err.to_string())))
+    ///                 .unwrap()
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn with_err_handler<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(crate::RouteError) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: ErrHandlerWithoutInfo<B> = Box::new(move |err: crate::RouteError| Box::new(handler(err)));
+        let handler = Arc::new(ErrHandler::WithoutInfo(handler));
+
+        self.and_then_last_route("with_err_handler", move |route| {
+            route.scope_err_handler = Some(handler.clone());
+            Ok(())
+        })
+    }
+
+    /// Sets the handler of the errors raised by the routes added by the preceding call like
+    /// [`with_err_handler`](#method.with_err_handler), which also accesses the [request info](./struct.RequestInfo.html).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response, StatusCode};
+    /// use routerify_ng::Router;
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .post("/webhook", |_| async move { Err(routerify_ng::Error::new("Invalid signature")) })
+    ///         .with_err_handler_with_info(|err, req_info| async move {
+    ///             Response::builder()
+    ///                 .status(StatusCode::UNAUTHORIZED)
+    ///                 .body(Full::new(Bytes::from(format!("{} {}: {}", req_info.method(), req_info.uri(), err))))
+    ///                 .unwrap()
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn with_err_handler_with_info<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(crate::RouteError, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: ErrHandlerWithInfo<B> =
+            Box::new(move |err: crate::RouteError, req_info: RequestInfo| Box::new(handler(err, req_info)));
+        let handler = Arc::new(ErrHandler::WithInfo(handler));
+
+        self.and_then_last_route("with_err_handler_with_info", move |route| {
+            route.scope_err_handler = Some(handler.clone());
+            Ok(())
+        })
+    }
+
+    /// Sets the handler of the requests which aren't matched by any other route of this router, replacing the default
+    /// 404 handler.
+    ///
+    /// When this router is mounted via [`scope`](#method.scope), the handler only receives the unmatched requests under
+    /// the scope path, so that e.g. an API router can respond with a JSON 404 while the root one responds with an HTML
+    /// page. The routes of the parent routers still take precedence, and the handler of the deepest scope wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response, StatusCode};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let api = Router::builder()
+    ///         .get("/users", |_| async move { Ok(Response::new(Full::new(Bytes::from("[]")))) })
+    ///         .not_found(|_| async move {
+    ///             Ok(Response::builder()
+    ///                 .status(StatusCode::NOT_FOUND)
+    ///                 .header("content-type", "application/json")
+    ///                 .body(Full::new(Bytes::from(r#"{"error":"Not Found"}"#)))
+    ///                 .unwrap())
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let router = Router::builder()
+    ///         .scope("/api", api)
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn not_found<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.and_then(move |mut inner| {
+            let mut route = Route::new("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler)?;
+            route.not_found = true;
+            inner.routes.push(route);
+            inner.last_routes = inner.routes.len() - 1..inner.routes.len();
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// It mounts a router onto another router. It can be very useful when you want to write modular routing logic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::Router;
+    /// use hyper::body::Incoming;
+    /// use std::convert::Infallible;
+    ///
+    /// mod api {
+    ///     use http_body_util::Full;
+    ///     use hyper::{body::Bytes, Response};
+    ///     use routerify_ng::Router;
+    ///     use hyper::body::Incoming;
+    ///     use std::convert::Infallible;
+    ///
+    ///     pub fn router() -> Router<Infallible> {
+    ///         Router::builder()
+    ///             .get("/users", |_| async move {
+    ///                 Ok(Response::new(Full::new(Bytes::from("User list"))))
+    ///             })
+    ///             .get("/books", |_| async move {
+    ///                 Ok(Response::new(Full::new(Bytes::from("Book list"))))
+    ///             })
+    ///             .build()
+    ///             .unwrap()
+    ///     }
+    /// }
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router: Router<Infallible> = Router::builder()
+    ///         .scope("/api", api::router())
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    ///
+    /// Now, the app can handle requests on: `/api/users` and `/api/books` paths.
+    pub fn scope<P>(self, path: P, mut router: Router<E, B>) -> Self
+    where
+        P: Into<String>,
+    {
+        let started = Instant::now();
+        let mut path = path.into();
+
+        if path.ends_with('/') {
+            path = path[..path.len() - 1].to_string();
+        }
+
+        let mut builder = self;
+
+        // The scoped router's error handler is attached to its routes and middlewares which don't have a nearer one.
+        let scope_err_handler = router.err_handler.take().map(Arc::new);
+
+        for pre_middleware in router.pre_middlewares.iter_mut() {
+            let new_pre_middleware = PreMiddleware::new_with_boxed_handler(
+                format!("{}{}", path.as_str(), pre_middleware.path.as_str()),
+                pre_middleware
+                    .handler
+                    .take()
+                    .expect("No handler found in one of the pre-middlewares"),
                 pre_middleware.scope_depth + 1,
-            );
+            )
+            .map(|mut new_pre_middleware| {
+                new_pre_middleware.priority = pre_middleware.priority;
+                new_pre_middleware.name = pre_middleware.name.take();
+                new_pre_middleware.label = pre_middleware.label.take();
+                new_pre_middleware.overrides_method = pre_middleware.overrides_method;
+                new_pre_middleware.private_data_maps = std::mem::take(&mut pre_middleware.private_data_maps);
+                new_pre_middleware.scope_err_handler = pre_middleware
+                    .scope_err_handler
+                    .take()
+                    .or_else(|| scope_err_handler.clone());
+                new_pre_middleware
+            });
             builder = builder.and_then(move |mut inner| {
                 inner.pre_middlewares.push(new_pre_middleware?);
                 crate::Result::Ok(inner)
@@ -587,12 +1382,33 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
         }
 
         for route in router.routes.iter_mut() {
+            // A prefix stripped by a nested scope stays stripped, as the handlers of that scope
+            // expect paths relative to their own mount point.
+            let strip_prefix = match route.strip_prefix.take() {
+                Some(inner_prefix) => Some(format!("{}{}", path.as_str(), inner_prefix)),
+                None if router.strip_prefix_from_scope => Some(path.clone()),
+                None => None,
+            };
             let new_route = Route::new_with_boxed_handler(
                 format!("{}{}", path.as_str(), route.path.as_str()),
                 route.methods.clone(),
                 route.handler.take().expect("No handler found in one of the routes"),
                 route.scope_depth + 1,
-            );
+            )
+            .and_then(|mut new_route| {
+                new_route.strip_prefix = strip_prefix;
+                new_route.scope_path = format!("{}{}", path.as_str(), route.scope_path.as_str());
+                new_route.guard = route.guard.take();
+                new_route.private_data_maps = std::mem::take(&mut route.private_data_maps);
+                new_route.timeout = route.timeout;
+                new_route.needs_body = route.needs_body;
+                new_route.not_found = route.not_found;
+                new_route.custom = route.custom;
+                new_route.trailing_slash = route.trailing_slash;
+                new_route.scope_err_handler = route.scope_err_handler.take().or_else(|| scope_err_handler.clone());
+                new_route.apply_param_constraints(&route.param_constraints)?;
+                Ok(new_route)
+            });
             builder = builder.and_then(move |mut inner| {
                 inner.routes.push(new_route?);
                 crate::Result::Ok(inner)
@@ -607,13 +1423,50 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
                     .take()
                     .expect("No handler found in one of the post-middlewares"),
                 post_middleware.scope_depth + 1,
-            );
+            )
+            .map(|mut new_post_middleware| {
+                new_post_middleware.priority = post_middleware.priority;
+                new_post_middleware.name = post_middleware.name.take();
+                new_post_middleware.label = post_middleware.label.take();
+                new_post_middleware.private_data_maps = std::mem::take(&mut post_middleware.private_data_maps);
+                new_post_middleware.scope_err_handler = post_middleware
+                    .scope_err_handler
+                    .take()
+                    .or_else(|| scope_err_handler.clone());
+                new_post_middleware
+            });
             builder = builder.and_then(move |mut inner| {
                 inner.post_middlewares.push(new_post_middleware?);
                 crate::Result::Ok(inner)
             });
         }
 
+        for around_middleware in router.around_middlewares.iter_mut() {
+            let new_around_middleware = AroundMiddleware::new_with_boxed_handler(
+                format!("{}{}", path.as_str(), around_middleware.path.as_str()),
+                around_middleware
+                    .handler
+                    .take()
+                    .expect("No handler found in one of the around-middlewares"),
+                around_middleware.scope_depth + 1,
+            )
+            .map(|mut new_around_middleware| {
+                new_around_middleware.priority = around_middleware.priority;
+                new_around_middleware.name = around_middleware.name.take();
+                new_around_middleware.label = around_middleware.label.take();
+                new_around_middleware.private_data_maps = std::mem::take(&mut around_middleware.private_data_maps);
+                new_around_middleware.scope_err_handler = around_middleware
+                    .scope_err_handler
+                    .take()
+                    .or_else(|| scope_err_handler.clone());
+                new_around_middleware
+            });
+            builder = builder.and_then(move |mut inner| {
+                inner.around_middlewares.push(new_around_middleware?);
+                crate::Result::Ok(inner)
+            });
+        }
+
         for scoped_data_map in router.scoped_data_maps.iter_mut() {
             let new_path = format!("{}{}", path.as_str(), scoped_data_map.path.as_str());
             let data_map = Arc::try_unwrap(
@@ -638,13 +1491,23 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
             });
         }
 
-        builder
+        // Remounting is dominated by recompiling the regexes of the scoped router.
+        let elapsed = started.elapsed();
+        builder.and_then(move |mut inner| {
+            inner.regex_compilation += elapsed;
+            crate::Result::Ok(inner)
+        })
     }
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<E> {
-    /// Adds a single middleware. A pre middleware can be created by [`Middleware::pre`](./enum.Middleware.html#method.pre) method and a post
-    /// middleware can be created by [`Middleware::post`](./enum.Middleware.html#method.post) method.
+impl<E, B> RouterBuilder<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    /// Adds a single middleware. A pre middleware can be created by [`Middleware::pre`](./enum.Middleware.html#method.pre) method, a post
+    /// middleware can be created by [`Middleware::post`](./enum.Middleware.html#method.post) method and an around middleware can be
+    /// created by [`Middleware::around`](./enum.Middleware.html#method.around) method.
     ///
     /// # Examples
     ///
@@ -671,7 +1534,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
     ///     router
     /// }
     /// ```
-    pub fn middleware(self, m: Middleware<E>) -> Self {
+    pub fn middleware(self, m: Middleware<E, B>) -> Self {
         self.and_then(move |mut inner| {
             match m {
                 Middleware::Pre(middleware) => {
@@ -680,77 +1543,1216 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<
                 Middleware::Post(middleware) => {
                     inner.post_middlewares.push(middleware);
                 }
+                Middleware::Around(middleware) => {
+                    inner.around_middlewares.push(middleware);
+                }
             }
             crate::Result::Ok(inner)
         })
     }
 
-    /// Specify app data to be shared across route handlers, middlewares and the error handler.
+    /// Adds a single middleware with a label, so that it can be removed later via
+    /// [`remove_middleware`](#method.remove_middleware), e.g. to disable it in the tests.
     ///
-    /// Please refer to the [Data and State Sharing](./index.html#data-and-state-sharing) for more info.
-    pub fn data<K: Send + Sync + Clone + 'static>(self, data: K) -> Self {
-        self.and_then(move |mut inner| {
-            let data_maps = &mut inner.data_maps;
-
-            let data_map_arr = data_maps.get_mut(&"/*".to_owned());
-            if let Some(data_map_arr) = data_map_arr {
-                let first_data_map = data_map_arr.get_mut(0).unwrap();
-                first_data_map.insert(data);
-            } else {
-                let mut data_map = DataMap::new();
-                data_map.insert(data);
-                data_maps.insert("/*".to_owned(), vec![data_map]);
-            }
-
-            crate::Result::Ok(inner)
-        })
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router, RouterBuilder};
+    /// use std::convert::Infallible;
+    ///
+    /// fn app() -> RouterBuilder<Infallible> {
+    ///     Router::builder()
+    ///         .middleware_labeled("auth", Middleware::pre(|req| async move {
+    ///             /* Authenticate the request */
+    ///             Ok(req)
+    ///         }))
+    /// }
+    ///
+    /// fn test_router() -> Router<Infallible> {
+    ///     app().remove_middleware("auth").build().unwrap()
+    /// }
+    /// ```
+    pub fn middleware_labeled<L: Into<String>>(self, label: L, mut m: Middleware<E, B>) -> Self {
+        let label = Some(label.into());
+        match m {
+            Middleware::Pre(ref mut middleware) => middleware.label = label,
+            Middleware::Post(ref mut middleware) => middleware.label = label,
+            Middleware::Around(ref mut middleware) => middleware.label = label,
+        }
+        self.middleware(m)
     }
 
-    /// Adds a handler to handle any error raised by the routes or any middlewares. Please refer to [Error Handling](./index.html#error-handling) section
-    /// for more info.
-    pub fn err_handler<H, R>(self, handler: H) -> Self
-    where
-        H: Fn(crate::RouteError) -> R + Send + Sync + 'static,
-        R: Future<Output = Response<Full<Bytes>>> + Send + 'static,
-    {
-        let handler: ErrHandlerWithoutInfo = Box::new(move |err: crate::RouteError| Box::new(handler(err)));
-
+    /// Removes the middlewares previously added with the specified label via
+    /// [`middleware_labeled`](#method.middleware_labeled), including the ones added by the mounted routers.
+    ///
+    /// It fails the build if no middleware has the label.
+    pub fn remove_middleware<L: AsRef<str>>(self, label: L) -> Self {
         self.and_then(move |mut inner| {
-            inner.err_handler = Some(ErrHandler::WithoutInfo(handler));
+            let label = label.as_ref();
+            let count = inner.pre_middlewares.len() + inner.post_middlewares.len() + inner.around_middlewares.len();
+
+            inner.pre_middlewares.retain(|m| m.label.as_deref() != Some(label));
+            inner.post_middlewares.retain(|m| m.label.as_deref() != Some(label));
+            inner.around_middlewares.retain(|m| m.label.as_deref() != Some(label));
+
+            if inner.pre_middlewares.len() + inner.post_middlewares.len() + inner.around_middlewares.len() == count {
+                return Err(crate::Error::new(format!("No middleware found with the label `{}`", label)).into());
+            }
             crate::Result::Ok(inner)
         })
     }
 
-    /// Adds a handler to handle any error raised by the routes or any middlewares.
+    /// Controls whether the route handlers of this router see the request path without the prefix this router is
+    /// mounted at via the [`scope`](./struct.RouterBuilder.html#method.scope) method.
     ///
-    /// Here, the handler also access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc to generate response based on the request information.
+    /// By default, the handlers see the full request path e.g. `/api/users` for a `/users` route mounted at `/api`.
+    /// When it's enabled, `req.uri().path()` is rewritten to `/users` for the handlers of this router, the query string
+    /// is kept as it is. It has no effect on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn api_router() -> Router<Infallible> {
+    ///     Router::builder()
+    ///         .strip_prefix_from_scope(true)
+    ///         .get("/users", |req| async move {
+    ///             // It prints "/users" for a request to "/api/users".
+    ///             println!("{}", req.uri().path());
+    ///             Ok(Response::new(Full::new(Bytes::from("User list"))))
+    ///         })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder().scope("/api", api_router()).build().unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn strip_prefix_from_scope(self, strip: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.strip_prefix_from_scope = strip;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Makes the default 404 response list the routes closest to the requested path, which helps to diagnose typos
+    /// while developing. It's disabled by default and has no effect if a custom `.any(handler)` route is added.
+    ///
+    /// It should be enabled on the root router only, and preferably only for debug builds as it exposes the route table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .debug_404(cfg!(debug_assertions))
+    ///         .get("/users", |_| async move { Ok(Response::new(Full::new(Bytes::from("User list")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn debug_404(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.debug_404 = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Trusts the `X-Forwarded-Proto` header, so that a request forwarded by a TLS-terminating proxy with the value
+    /// `https` is reported as secure by [`RequestExt::is_secure`](./ext/trait.RequestExt.html#tymethod.is_secure).
+    ///
+    /// It must be enabled only if the server is reachable exclusively through the proxy, as the clients can set the
+    /// header themselves. It should be set on the root router.
+    pub fn trust_forwarded_proto(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.trust_forwarded_proto = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Trusts the proxies of the config to report the client address via the `X-Forwarded-For` or the `Forwarded`
+    /// header chosen by the config, which is then returned by [`RequestExt::client_addr`](./ext/trait.RequestExt.html#tymethod.client_addr)
+    /// instead of the proxy address. It fails if a proxy range of the config is invalid.
+    ///
+    /// A client can set these headers itself, so only the proxies which overwrite or append to the chosen one may be
+    /// trusted, see
+    /// [`TrustConfig`](./struct.TrustConfig.html). It should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::{Router, TrustConfig};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .trust_proxy(TrustConfig::new().proxy("10.0.0.0/8"))
+    ///         .get("/", |req| async move {
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Hello {}", req.client_addr())))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn trust_proxy(self, config: TrustConfig) -> Self {
+        self.and_then(move |mut inner| {
+            if let Some(ref cidr) = config.invalid {
+                return Err(crate::Error::new(format!("Invalid trusted proxy range: {}", cidr)).into());
+            }
+            inner.trust_proxy = Some(Arc::new(config));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Allows the routes accepting any method, e.g. the ones added by [`any`](#method.any) and the default 404 route, to
+    /// answer `TRACE` requests.
+    ///
+    /// By default, a `TRACE` request is answered with `405 Method Not Allowed` unless a route is registered explicitly
+    /// for the `TRACE` method e.g. via [`trace`](#method.trace), because echoing requests back enables cross-site tracing.
+    /// It should be set on the root router.
+    pub fn allow_trace(self) -> Self {
+        self.and_then(move |mut inner| {
+            inner.allow_trace = true;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets whether the durations of the middlewares and the route handler are reported to the client via the
+    /// `Server-Timing` header, e.g. `Server-Timing: mw_auth;dur=0.4, handler;dur=12.3`, which is shown by the browser
+    /// devtools.
+    ///
+    /// The middlewares are reported by their names set via [`Middleware::named`](./enum.Middleware.html#method.named),
+    /// or as `mw_pre_<index>` and `mw_post_<index>` otherwise. It should be set on the root router.
+    pub fn server_timing(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.server_timing = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets whether the `Content-Length` header is set from the buffered response body when a handler or a middleware
+    /// didn't set it, which is the default. The responses having `Transfer-Encoding` and the ones which must not have
+    /// a body, e.g. `204 No Content`, are left as they are.
+    ///
+    /// It's done after the [`map_response`](#method.map_response) hooks run, so the header reflects the bodies they
+    /// changed. It should be set on the root router.
+    pub fn auto_content_length(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.auto_content_length = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Defers reading the request body until a handler or middleware asks for it via
+    /// [`RequestExt::body_bytes`](./ext/trait.RequestExt.html#tymethod.body_bytes). The body is then read once and cached.
+    ///
+    /// When enabled, the `Full<Bytes>` body passed to the handlers is empty, so the requests which are answered without
+    /// looking at the body, e.g. the rejected ones, don't wait for it to be uploaded. By default, the body is buffered
+    /// before the request is routed. It should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .defer_body_reading(true)
+    ///         .post("/upload", |req| async move {
+    ///             let body = req.body_bytes().await?;
+    ///             Ok(Response::new(Full::new(Bytes::from(format!("Received {} bytes", body.len())))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn defer_body_reading(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.defer_body_reading = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Rejects the requests having more than `count` headers with `431 Request Header Fields Too Large`.
+    ///
+    /// The check is done before the request is routed, so no middleware or handler runs for a rejected request.
+    /// It should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .max_headers(64)
+    ///         .max_header_bytes(16 * 1024)
+    ///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn max_headers(self, count: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.max_headers = Some(count);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Rejects the requests whose headers take more than `bytes` in total, counting the names and the values,
+    /// with `431 Request Header Fields Too Large`.
+    ///
+    /// Like [`max_headers`](#method.max_headers), the check is done before the request is routed. It should be set
+    /// on the root router.
+    pub fn max_header_bytes(self, bytes: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.max_header_bytes = Some(bytes);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Rejects the requests whose body is larger than `bytes` with `413 Payload Too Large`, so that a giant payload
+    /// isn't buffered in memory.
+    ///
+    /// A request declaring a larger `Content-Length` is rejected before it's routed. Otherwise the body is read up to
+    /// the limit: a buffered body is rejected the same way, and reading a deferred one, e.g. via
+    /// [`RequestExt::body_bytes`](./ext/trait.RequestExt.html#tymethod.body_bytes), fails. It should be set on the
+    /// root router.
+    ///
+    /// The request body size is unlimited by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .body_limit(1024 * 1024)
+    ///         .post("/upload", |_| async move { Ok(Response::new(Full::new(Bytes::from("Uploaded")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn body_limit(self, bytes: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.body_limit = Some(bytes);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Limits how many requests a single HTTP/1 connection may serve. The response to the last allowed request gets the
+    /// `Connection: close` header and the connection is closed once it's sent, so that a client can't hold a keep-alive
+    /// connection forever. The HTTP/2 connections aren't limited.
+    ///
+    /// It should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .max_requests_per_connection(100)
+    ///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn max_requests_per_connection(self, count: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.max_requests_per_connection = Some(count);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Decompresses the request bodies sent with the `gzip` or `deflate` `Content-Encoding` before the request is
+    /// routed, so that the middlewares and the handlers see the decoded body. The `Content-Encoding` header is removed
+    /// and the `Content-Length` header is set to the decoded size.
+    ///
+    /// A body expanding beyond `max_decompressed_bytes` is rejected with `413 Payload Too Large` to guard against the
+    /// decompression bombs, and a corrupted one with `400 Bad Request`. The bodies with other codings are passed through
+    /// as they are. It should be set on the root router.
+    ///
+    /// The bodies which aren't buffered, e.g. with [`defer_body_reading`](#method.defer_body_reading), are decompressed
+    /// when they're first read via [`RequestExt::body_bytes`](./ext/trait.RequestExt.html#tymethod.body_bytes) and the
+    /// like. The `Content-Encoding` and the `Content-Length` headers are removed up front, and a failing decoding is
+    /// reported by the read instead of a rejection response.
+    ///
+    /// It's available with the `decompression` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .decompress_requests(10 * 1024 * 1024)
+    ///         .post("/upload", |_| async move { Ok(Response::new(Full::new(Bytes::from("Uploaded")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    #[cfg(feature = "decompression")]
+    pub fn decompress_requests(self, max_decompressed_bytes: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.decompress_requests = Some(max_decompressed_bytes);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets a router which the requests not matched by any route of this router are dispatched to, before they reach
+    /// the catch-all routes of this router, e.g. the default 404 route.
+    ///
+    /// Unlike [`scope`](#method.scope), the routers aren't merged: the fallback router handles the request on its own,
+    /// with its own middlewares and error handler. It can have its own fallback router too. It should be set on the
+    /// root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let plugin = Router::builder()
+    ///         .get("/plugin", |_| async move { Ok(Response::new(Full::new(Bytes::from("Plugin page")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let router = Router::builder()
+    ///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+    ///         .fallback_router(plugin)
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn fallback_router(self, router: Router<E, B>) -> Self {
+        self.and_then(move |mut inner| {
+            inner.fallback = Some(Box::new(router));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Forwards the requests not matched by any route of this router to an upstream HTTP server and responds with the
+    /// upstream's response, e.g. to serve some routes locally in a gateway.
+    ///
+    /// The method, the headers and the body are forwarded as they are, except for the hop-by-hop headers and the `Host`
+    /// header, which is set to the upstream's authority. The request path and query are appended to the path of the
+    /// `upstream_uri`, which must be an absolute `http` URI. If the upstream can't be reached, the client gets
+    /// `502 Bad Gateway`.
+    ///
+    /// It sets the [`fallback_router`](#method.fallback_router) of this router, so it should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/health", |_| async move { Ok(Response::new(Full::new(Bytes::from("OK")))) })
+    ///         .proxy_fallback("http://127.0.0.1:8080")
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn proxy_fallback<U: AsRef<str>>(self, upstream_uri: U) -> Self {
+        let proxy = crate::proxy::Proxy::new(upstream_uri.as_ref()).map(Arc::new);
+
+        self.and_then(move |mut inner| {
+            let proxy = proxy?;
+            let router = Router::builder()
+                .any(move |req| {
+                    let proxy = proxy.clone();
+                    async move { Ok(proxy.forward(req).await) }
+                })
+                .build()?;
+
+            inner.fallback = Some(Box::new(router));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Serves the files of the `dir` directory under the `mount` path, e.g. `/assets/css/main.css` from
+    /// `./public/css/main.css` for the `/assets` mount, to the `GET` and `HEAD` requests.
+    ///
+    /// The files are streamed with the `Content-Type` guessed from their extension, and with the `Last-Modified` and
+    /// `ETag` headers, so that the conditional requests get `304 Not Modified` if the client's copy is still fresh. A
+    /// directory is served by its `index.html` file. The paths having a `..` segment are rejected with
+    /// `403 Forbidden` and the missing files get `404 Not Found`.
+    ///
+    /// As the files are streamed, the body type of the router must be creatable from a
+    /// [`BoxBody`](./body/struct.BoxBody.html), e.g. a `Router<E, BoxBody>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::body::BoxBody;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible, BoxBody> {
+    ///     let router = Router::builder()
+    ///         .get("/", |_| async move { Ok(Response::new(BoxBody::from(Bytes::from("Home page")))) })
+    ///         .static_files("/assets", "./public")
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn static_files<M: Into<String>, D: Into<PathBuf>>(self, mount: M, dir: D) -> Self
+    where
+        B: From<BoxBody>,
+    {
+        let path = format!("{}/*path", mount.into().trim_end_matches('/'));
+        let static_files = Arc::new(StaticFiles::new(dir.into()));
+
+        self.add(path, vec![Method::GET, Method::HEAD], move |req| {
+            let static_files = static_files.clone();
+            async move { Ok(static_files.serve(&req).await.map(B::from)) }
+        })
+    }
+
+    /// Applies the `f` closure to the builder only when the `condition` holds, e.g. to register the debug-only routes
+    /// without breaking the builder chain.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run(debug: bool) -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+    ///         .when(debug, |builder| {
+    ///             builder.get("/debug/dump", |_| async move { Ok(Response::new(Full::new(Bytes::from("Dump")))) })
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn when<F>(self, condition: bool, f: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        if condition { f(self) } else { self }
+    }
+
+    /// Specify app data to be shared across route handlers, middlewares and the error handler.
+    ///
+    /// Please refer to the [Data and State Sharing](./index.html#data-and-state-sharing) for more info.
+    pub fn data<K: Send + Sync + Clone + 'static>(self, data: K) -> Self {
+        self.and_then(move |mut inner| {
+            let data_maps = &mut inner.data_maps;
+
+            let data_map_arr = data_maps.get_mut(&"/*".to_owned());
+            if let Some(data_map_arr) = data_map_arr {
+                let first_data_map = data_map_arr.get_mut(0).unwrap();
+                first_data_map.insert(data);
+            } else {
+                let mut data_map = DataMap::new();
+                data_map.insert(data);
+                data_maps.insert("/*".to_owned(), vec![data_map]);
+            }
+
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Mounts a router like [`scope`](#method.scope) and injects the `data` into the mounted router's scope, as if it
+    /// was added via [`data`](#method.data) on the mounted router itself, replacing its own value of the same type.
+    ///
+    /// It's handy when the data comes from the parent context, e.g. a per-module configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// #[derive(Clone)]
+    /// struct ApiConfig {
+    ///     version: u32,
+    /// }
+    ///
+    /// fn api_router() -> Router<Infallible> {
+    ///     Router::builder()
+    ///         .get("/version", |req| async move {
+    ///             let config = req.data::<ApiConfig>().unwrap();
+    ///             Ok(Response::new(Full::new(Bytes::from(config.version.to_string()))))
+    ///         })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .scope_with_data("/v1", ApiConfig { version: 1 }, api_router())
+    ///         .scope_with_data("/v2", ApiConfig { version: 2 }, api_router())
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn scope_with_data<P, K>(self, path: P, data: K, router: Router<E, B>) -> Self
+    where
+        P: Into<String>,
+        K: Send + Sync + Clone + 'static,
+    {
+        let path = path.into();
+        let data_path = format!("{}/*", path.strip_suffix('/').unwrap_or(path.as_str()));
+
+        self.scope(path, router).and_then(move |mut inner| {
+            let data_map_arr = inner.data_maps.entry(data_path).or_default();
+            if let Some(first_data_map) = data_map_arr.first_mut() {
+                first_data_map.insert(data);
+            } else {
+                let mut data_map = DataMap::new();
+                data_map.insert(data);
+                data_map_arr.push(data_map);
+            }
+
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Specify app data which is private to this router, i.e. it's shared only across the route handlers and the
+    /// middlewares added to this router, including the ones of its nested scopes. The post middlewares access it via
+    /// [`RequestInfo::data`](./struct.RequestInfo.html#method.data).
+    ///
+    /// Unlike [`data`](#method.data), it's not visible to the parent router and its other scopes when this router is
+    /// mounted via [`scope`](#method.scope), even for the requests matching the scope path. The private data takes
+    /// precedence over the shared data of the same type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::ext::RequestExt;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// #[derive(Clone)]
+    /// struct AdminToken(String);
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let admin = Router::builder()
+    ///         .data_private(AdminToken("secret".to_owned()))
+    ///         .get("/", |req| async move {
+    ///             let token = req.data::<AdminToken>().unwrap();
+    ///             Ok(Response::new(Full::new(Bytes::from(token.0.clone()))))
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     Router::builder().scope("/admin", admin).build().unwrap()
+    /// }
+    /// ```
+    pub fn data_private<K: Send + Sync + Clone + 'static>(self, data: K) -> Self {
+        self.and_then(move |mut inner| {
+            inner.private_data_map.get_or_insert_with(DataMap::new).insert(data);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares. Please refer to [Error Handling](./index.html#error-handling) section
+    /// for more info.
+    ///
+    /// The error handler of a router mounted via [`scope`](#method.scope) takes precedence over the parent's one for
+    /// the errors of the scoped router's routes and middlewares, see [Scoped Error Handlers](./index.html#scoped-error-handlers).
+    pub fn err_handler<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(crate::RouteError) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: ErrHandlerWithoutInfo<B> = Box::new(move |err: crate::RouteError| Box::new(handler(err)));
+
+        self.and_then(move |mut inner| {
+            inner.err_handler = Some(ErrHandler::WithoutInfo(handler));
+            inner.err_handler_factory = None;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares.
+    ///
+    /// Here, the handler also access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc to generate response based on the request information.
     ///
     /// Please refer to [Error Handling](./index.html#error-handling) section
     /// for more info.
     pub fn err_handler_with_info<H, R>(self, handler: H) -> Self
     where
-        H: Fn(crate::RouteError, RequestInfo) -> R + Send + Sync + 'static,
-        R: Future<Output = Response<Full<Bytes>>> + Send + 'static,
+        H: Fn(crate::RouteError, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: ErrHandlerWithInfo<B> =
+            Box::new(move |err: crate::RouteError, req_info: RequestInfo| Box::new(handler(err, req_info)));
+
+        self.and_then(move |mut inner| {
+            inner.err_handler = Some(ErrHandler::WithInfo(handler));
+            inner.err_handler_factory = None;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares, which is constructed by the `factory`
+    /// when the router is built, with access to the data of type `T` added via [`data`](#method.data).
+    ///
+    /// It allows the error handler to capture its configuration, e.g. a templating engine, from the router's data. The
+    /// build fails if there's no data of type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response, StatusCode};
+    /// use routerify_ng::{RouteError, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// #[derive(Clone)]
+    /// struct ErrorPage {
+    ///     template: String,
+    /// }
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .data(ErrorPage { template: "<h1>{}</h1>".to_owned() })
+    ///         .err_handler_with_data(|page: &ErrorPage| {
+    ///             let template = page.template.clone();
+    ///             move |err: RouteError| {
+    ///                 let body = template.replace("{}", &err.to_string());
+    ///                 async move {
+    ///                     Response::builder()
+    ///                         .status(StatusCode::INTERNAL_SERVER_ERROR)
+    ///                         .body(Full::new(Bytes::from(body)))
+    ///                         .unwrap()
+    ///                 }
+    ///             }
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn err_handler_with_data<T, F, H, R>(self, factory: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(&T) -> H + Send + 'static,
+        H: Fn(crate::RouteError) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
     {
-        let handler: ErrHandlerWithInfo =
-            Box::new(move |err: crate::RouteError, req_info: RequestInfo| Box::new(handler(err, req_info)));
+        let err_handler_factory: ErrHandlerFactory<B> = Box::new(move |data_map: Option<&DataMap>| {
+            let data = data_map.and_then(|data_map| data_map.get::<T>()).ok_or_else(|| {
+                crate::Error::new(format!(
+                    "No data of type `{}` found for the error handler",
+                    std::any::type_name::<T>()
+                ))
+            })?;
+
+            let handler = factory(data);
+            let handler: ErrHandlerWithoutInfo<B> = Box::new(move |err: crate::RouteError| Box::new(handler(err)));
+            crate::Result::Ok(ErrHandler::WithoutInfo(handler))
+        });
 
         self.and_then(move |mut inner| {
-            inner.err_handler = Some(ErrHandler::WithInfo(handler));
+            inner.err_handler_factory = Some(err_handler_factory);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a function which synchronously transforms every outgoing response after the post middlewares, including the
+    /// 404 responses and the ones generated by the error handler.
+    ///
+    /// It's a lightweight alternative to a post middleware for simple response tweaks which don't need path scoping or
+    /// async operations. The mappers are applied in the order they're added. Like the error handler, it should be added
+    /// on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::header::HeaderValue;
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .map_response(|mut res| {
+    ///             res.headers_mut().insert("x-powered-by", HeaderValue::from_static("routerify"));
+    ///             res
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn map_response<F>(self, mapper: F) -> Self
+    where
+        F: Fn(Response<B>) -> Response<B> + Send + Sync + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.response_mappers.push(Box::new(mapper));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets a function which generates the path matched against the routes from the request path, e.g. to lowercase it
+    /// or to strip the session IDs like `;jsessionid=...`.
+    ///
+    /// It supersedes the built-in percent-decoding of the request path, so the function receives the raw path. The
+    /// route parameters are extracted from the returned path. Like the error handler, it should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::borrow::Cow;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .canonicalize_path(|path| Cow::Owned(path.to_lowercase()))
+    ///         .get("/about", |_| async move { Ok(Response::new(Full::new(Bytes::from("About page")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn canonicalize_path<F>(self, canonicalizer: F) -> Self
+    where
+        F: for<'a> Fn(&'a str) -> Cow<'a, str> + Send + Sync + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.path_canonicalizer = Some(Box::new(canonicalizer));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Constrains the route parameter `name` to match the `regex` in all the routes of this router, including the
+    /// ones of the scoped routers. A constraint set in a scoped router takes precedence for its own routes.
+    ///
+    /// The requests whose parameter doesn't match are handled as if no route matched. The `regex` must not have
+    /// capturing groups, use `(?:...)` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .param_constraint("org", "[a-z0-9-]+")
+    ///         .param_constraint("repo", "[a-z0-9-]+")
+    ///         .get("/orgs/:org", |_| async move { Ok(Response::new(Full::new(Bytes::from("Org")))) })
+    ///         .get("/orgs/:org/repos/:repo", |_| async move { Ok(Response::new(Full::new(Bytes::from("Repo")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn param_constraint<N: Into<String>, R: Into<String>>(self, name: N, regex: R) -> Self {
+        let name = name.into();
+        let regex = regex.into();
+        self.and_then(move |mut inner| {
+            validate_param_constraint(name.as_str(), regex.as_str())?;
+            inner.param_constraints.insert(name, regex);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Limits the compiled size of each route, middleware and data scope path regex to `bytes`.
+    ///
+    /// When it's set, the route table is compiled by [`build`](#method.build), which fails with an error naming the
+    /// offending path instead of spending unbounded time and memory on it. It should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .regex_size_limit(64 * 1024)
+    ///         .regex_set_size_limit(8 * 1024 * 1024)
+    ///         .get("/users/:id", |_| async move { Ok(Response::new(Full::new(Bytes::from("User")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn regex_size_limit(self, bytes: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.regex_size_limit = Some(bytes);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Limits the compiled size of the `RegexSet` matching all the paths of the router to `bytes`.
+    ///
+    /// Like [`regex_size_limit`](#method.regex_size_limit), the route table is compiled by [`build`](#method.build)
+    /// when it's set, and it should be set on the root router.
+    pub fn regex_set_size_limit(self, bytes: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.regex_set_size_limit = Some(bytes);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the deadline of the route handlers, after which a `503 Service Unavailable` response is sent instead. A route
+    /// can override it, e.g. via [`get_with_timeout`](#method.get_with_timeout).
+    ///
+    /// The deadline covers the route handler only, not the middlewares. Like the error handler, it should be set on the
+    /// root router.
+    pub fn handler_timeout(self, timeout: Duration) -> Self {
+        self.and_then(move |mut inner| {
+            inner.handler_timeout = Some(timeout);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the [`Maintenance`](./struct.Maintenance.html) mode handle of the router, which can be toggled at runtime to
+    /// answer the requests with `503 Service Unavailable`. It should be set on the root router.
+    pub fn maintenance(self, maintenance: Maintenance) -> Self {
+        self.and_then(move |mut inner| {
+            inner.maintenance = maintenance;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets a [`MetricsRecorder`](./trait.MetricsRecorder.html) which is notified when a request starts and ends, along
+    /// with the response status, the duration and the path template of the route which handled the request. The requests
+    /// answered before routing, e.g. the ones exceeding the [`body_limit`](#method.body_limit), are recorded too,
+    /// without a route.
+    ///
+    /// Like the error handler, it should be set on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::StatusCode;
+    /// use routerify_ng::{MetricsRecorder, Router};
+    /// use std::convert::Infallible;
+    /// use std::time::Duration;
+    ///
+    /// struct LogRecorder;
+    ///
+    /// impl MetricsRecorder for LogRecorder {
+    ///     fn on_request_end(&self, status: StatusCode, duration: Duration, route: Option<&str>) {
+    ///         println!("{} {} in {:?}", route.unwrap_or("unmatched"), status, duration);
+    ///     }
+    /// }
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder().metrics(LogRecorder).build().unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn metrics<R: MetricsRecorder>(self, recorder: R) -> Self {
+        self.and_then(move |mut inner| {
+            inner.metrics = Some(Arc::new(recorder));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Registers an error type implementing [`ErrorStatus`](./trait.ErrorStatus.html), so that the default error handler
+    /// responds with the status code returned by [`ErrorStatus::status`](./trait.ErrorStatus.html#tymethod.status) for
+    /// the errors of this type instead of `500 Internal Server Error`.
+    ///
+    /// It has no effect if a custom error handler is added. Like the error handler, it should be registered on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response, StatusCode};
+    /// use routerify_ng::{ErrorStatus, Router};
+    /// use std::fmt;
+    ///
+    /// #[derive(Debug)]
+    /// struct NotFound;
+    ///
+    /// impl fmt::Display for NotFound {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "Not found")
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for NotFound {}
+    ///
+    /// impl ErrorStatus for NotFound {
+    ///     fn status(&self) -> StatusCode {
+    ///         StatusCode::NOT_FOUND
+    ///     }
+    /// }
+    ///
+    /// fn run() -> Router<NotFound> {
+    ///     let router = Router::builder()
+    ///         .get("/users/:userId", |_| async move { Err(NotFound) })
+    ///         .err_status::<NotFound>()
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn err_status<T>(self) -> Self
+    where
+        T: ErrorStatus + std::error::Error + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.err_status_mappers.push(err_status_of::<T>);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Makes the default error handler render the errors as the RFC 7807 `application/problem+json` documents, e.g.
+    /// `{"type":"about:blank","title":"Not Found","status":404,"detail":"No such user"}`, instead of the empty bodies.
+    ///
+    /// The status is resolved via the [`err_status`](#method.err_status) registrations like without this option, and the
+    /// detail is the error's `Display` output, so the error messages shouldn't contain anything the clients mustn't see.
+    /// It has no effect if a custom [`err_handler`](#method.err_handler) is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::Router;
+    ///
+    /// fn run() -> Router<routerify_ng::Error> {
+    ///     let router = Router::builder()
+    ///         .problem_json_errors()
+    ///         .get("/", |_| async move { Err(routerify_ng::Error::new("Something went wrong")) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn problem_json_errors(self) -> Self {
+        self.and_then(move |mut inner| {
+            inner.problem_json_errors = true;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Skips the global `OPTIONS` route, the default 404 route and the default error handler which are otherwise added
+    /// when the router starts serving, e.g. to embed it in another system whose catch-all route handles everything.
+    ///
+    /// An unmatched request gets a bare `404 Not Found` response with an empty body then, unless the router has its own
+    /// `/*` routes, and a failed handler is reported as the service error, unless the router has its own
+    /// [`err_handler`](#method.err_handler). It should be called only on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response, StatusCode};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .minimal()
+    ///         .get("/api/status", |_| async move { Ok(Response::new(Full::new(Bytes::from("ok")))) })
+    ///         .any(|_| async move {
+    ///             Ok(Response::builder()
+    ///                 .status(StatusCode::MISDIRECTED_REQUEST)
+    ///                 .body(Full::new(Bytes::new()))
+    ///                 .unwrap())
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn minimal(self) -> Self {
+        self.and_then(move |mut inner| {
+            inner.minimal = true;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets how a trailing slash in the request path is treated when matching the routes, which is
+    /// [`TrailingSlashPolicy::Merge`](./enum.TrailingSlashPolicy.html#variant.Merge) by default, i.e. `/about` and
+    /// `/about/` are the same path.
+    ///
+    /// With the [`Strict`](./enum.TrailingSlashPolicy.html#variant.Strict) policy, a route only matches the request
+    /// paths with the same trailing slash as its own path. The redirecting policies answer the requests with the other
+    /// form of the path via `308 Permanent Redirect` before they're routed, so the middlewares don't see them.
+    ///
+    /// The glob routes, e.g. `/files/*`, match both forms of the path under any policy, but the redirects apply to them
+    /// too, e.g. `/files/report.pdf` is redirected to `/files/report.pdf/` with the
+    /// [`RedirectToSlash`](./enum.TrailingSlashPolicy.html#variant.RedirectToSlash) policy. It should be set only on the
+    /// root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::{Router, TrailingSlashPolicy};
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         // `GET /about/` is redirected to `/about`.
+    ///         .trailing_slash(TrailingSlashPolicy::RedirectToNoSlash)
+    ///         .get("/about", |_| async move { Ok(Response::new(Full::new(Bytes::from("About")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn trailing_slash(self, policy: TrailingSlashPolicy) -> Self {
+        self.and_then(move |mut inner| {
+            inner.trailing_slash = policy;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a hook which is called with the assembled [Router](./struct.Router.html) at the end of
+    /// [`build`](#method.build), e.g. to add the routes computed from the rest of the configuration via
+    /// [`Router::add_route`](./struct.Router.html#method.add_route). The hooks are called in the order they were added,
+    /// and an error returned by a hook fails the build.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Method, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/users", |_| async move { Ok(Response::new(Full::new(Bytes::from("Users")))) })
+    ///         .finalize(|router| {
+    ///             let middleware_count = router.pre_middleware().len() + router.post_middleware().len();
+    ///             router.add_route("/_debug", vec![Method::GET], move |_| async move {
+    ///                 Ok(Response::new(Full::new(Bytes::from(format!("{} middlewares", middleware_count)))))
+    ///             })
+    ///         })
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn finalize<F>(self, hook: F) -> Self
+    where
+        F: FnOnce(&mut Router<E, B>) -> crate::Result<()> + Send + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.finalizers.push(Box::new(hook));
             crate::Result::Ok(inner)
         })
     }
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Default for RouterBuilder<E> {
-    fn default() -> RouterBuilder<E> {
+impl<E, B> Default for RouterBuilder<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    fn default() -> RouterBuilder<E, B> {
         RouterBuilder {
             inner: Ok(BuilderInner {
                 pre_middlewares: Vec::new(),
                 routes: Vec::new(),
                 post_middlewares: Vec::new(),
+                around_middlewares: Vec::new(),
                 data_maps: HashMap::new(),
+                private_data_map: None,
                 err_handler: None,
+                err_handler_factory: None,
+                err_status_mappers: Vec::new(),
+                strip_prefix_from_scope: false,
+                debug_404: false,
+                problem_json_errors: false,
+                minimal: false,
+                trailing_slash: TrailingSlashPolicy::default(),
+                allow_trace: false,
+                auto_content_length: true,
+                server_timing: false,
+                defer_body_reading: false,
+                max_headers: None,
+                max_header_bytes: None,
+                body_limit: None,
+                max_requests_per_connection: None,
+                trust_forwarded_proto: false,
+                trust_proxy: None,
+                #[cfg(feature = "decompression")]
+                decompress_requests: None,
+                path_canonicalizer: None,
+                fallback: None,
+                handler_timeout: None,
+                regex_size_limit: None,
+                regex_set_size_limit: None,
+                param_constraints: HashMap::new(),
+                maintenance: Maintenance::default(),
+                metrics: None,
+                response_mappers: Vec::new(),
+                regex_compilation: Duration::ZERO,
+                finalizers: Vec::new(),
+                last_routes: 0..0,
             }),
         }
     }
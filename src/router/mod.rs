@@ -1,40 +1,71 @@
+#[cfg(feature = "json")]
+use crate::BodyError;
 use crate::Error;
+use crate::ParamError;
+#[cfg(feature = "serde")]
+use crate::QueryError;
 use crate::RouteError;
 use crate::constants;
 use crate::data_map::ScopedDataMap;
-use crate::middleware::{PostMiddleware, PreMiddleware};
+use crate::error::{ErrStatusMapper, err_status_of};
+use crate::helpers;
+use crate::maintenance::Maintenance;
+use crate::metrics::{MatchedRoute, MetricsRecorder};
+use crate::middleware::around::internal_server_error;
+use crate::middleware::{AroundMiddleware, Next, PostMiddleware, PreMiddleware};
+use crate::regex_generator::ends_with_glob;
 use crate::route::Route;
-use crate::types::RequestInfo;
+use crate::types::{
+    MiddlewareInfo, RequestInfo, RequestMeta, ResponseSource, RouteInfo, TrailingSlashPolicy, TrustConfig,
+};
 use http_body_util::Full;
-use hyper::body::Bytes;
-use hyper::{Method, Request, Response, StatusCode, header};
-use regex::RegexSet;
-use std::any::Any;
-use std::fmt::{self, Debug, Formatter};
+use hyper::body::{Body, Bytes};
+use hyper::header::HeaderValue;
+use hyper::{HeaderMap, Method, Request, Response, StatusCode, Uri, header};
+use regex::{RegexBuilder, RegexSet, RegexSetBuilder};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub use self::builder::RouterBuilder;
 
 mod builder;
 
-pub(crate) type ErrHandlerWithoutInfo = Box<dyn Fn(RouteError) -> ErrHandlerWithoutInfoReturn + Send + Sync + 'static>;
-pub(crate) type ErrHandlerWithoutInfoReturn = Box<dyn Future<Output = Response<Full<Bytes>>> + Send + 'static>;
+pub(crate) type ErrHandlerWithoutInfo<B> =
+    Box<dyn Fn(RouteError) -> ErrHandlerWithoutInfoReturn<B> + Send + Sync + 'static>;
+pub(crate) type ErrHandlerWithoutInfoReturn<B> = Box<dyn Future<Output = Response<B>> + Send + 'static>;
 
-pub(crate) type ErrHandlerWithInfo =
-    Box<dyn Fn(RouteError, RequestInfo) -> ErrHandlerWithInfoReturn + Send + Sync + 'static>;
-pub(crate) type ErrHandlerWithInfoReturn = Box<dyn Future<Output = Response<Full<Bytes>>> + Send + 'static>;
+pub(crate) type ErrHandlerWithInfo<B> =
+    Box<dyn Fn(RouteError, RequestInfo) -> ErrHandlerWithInfoReturn<B> + Send + Sync + 'static>;
+pub(crate) type ErrHandlerWithInfoReturn<B> = Box<dyn Future<Output = Response<B>> + Send + 'static>;
+
+pub(crate) type PathCanonicalizer = Box<dyn for<'a> Fn(&'a str) -> Cow<'a, str> + Send + Sync + 'static>;
+
+// The indices of the matched pre middlewares, routes, post middlewares, scoped data maps and around middlewares.
+type RegexSetMatches = (Vec<usize>, Vec<usize>, Vec<usize>, Vec<usize>, Vec<usize>);
+
+// The future of the route handler wrapped by the around middlewares.
+type ChainReturn<'a, B> = Pin<Box<dyn Future<Output = crate::Result<Response<B>>> + Send + 'a>>;
+
+pub(crate) type ResponseMapper<B> = Box<dyn Fn(Response<B>) -> Response<B> + Send + Sync + 'static>;
 
 /// Represents a modular, lightweight and mountable router type.
 ///
 /// A router consists of some routes, some pre-middlewares and some post-middlewares.
 ///
-/// This `Router<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Router<E, B>` type accepts two type parameters: `E` and `B`.
 ///
-/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
-///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
-///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html) trait with `Bytes` data, and be creatable from `Bytes` for the responses
+///   generated by the router itself, e.g. the default 404 one. It defaults to
+///   [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html), so a `Router<E>` is a `Router<E, Full<Bytes>>`.
+///   The streaming responses, e.g. the [`Sse`](./body/struct.Sse.html) ones, can be mixed with the buffered ones via
+///   [`body::BoxBody`](./body/struct.BoxBody.html).
 ///
 /// A `Router` can be created using the `Router::builder()` method.
 ///
@@ -56,15 +87,110 @@ pub(crate) type ErrHandlerWithInfoReturn = Box<dyn Future<Output = Response<Full
 ///     router
 /// }
 /// ```
-pub struct Router<E> {
-    pub(crate) pre_middlewares: Vec<PreMiddleware<E>>,
-    pub(crate) routes: Vec<Route<E>>,
-    pub(crate) post_middlewares: Vec<PostMiddleware<E>>,
+pub struct Router<E, B = Full<Bytes>> {
+    pub(crate) pre_middlewares: Vec<PreMiddleware<E, B>>,
+    pub(crate) routes: Vec<Route<E, B>>,
+    pub(crate) post_middlewares: Vec<PostMiddleware<E, B>>,
     pub(crate) scoped_data_maps: Vec<ScopedDataMap>,
+    pub(crate) around_middlewares: Vec<AroundMiddleware<E, B>>,
 
     // This handler should be added only on root Router.
     // Any error handler attached to scoped router will be ignored.
-    pub(crate) err_handler: Option<ErrHandler>,
+    pub(crate) err_handler: Option<ErrHandler<B>>,
+
+    // The status code mappers for the error types implementing `ErrorStatus`,
+    // used by the default error handler.
+    pub(crate) err_status_mappers: Vec<ErrStatusMapper>,
+
+    // Whether the default error handler renders the errors as `application/problem+json`.
+    pub(crate) problem_json_errors: bool,
+
+    // Whether the global OPTIONS route, the default 404 route and the default error handler are left out.
+    // It should be set only on root Router.
+    pub(crate) minimal: bool,
+
+    // How a trailing slash in the request path is treated when matching the routes.
+    // It should be set only on root Router.
+    pub(crate) trailing_slash: TrailingSlashPolicy,
+
+    // Whether the routes of this router see the request path without the prefix
+    // it's mounted at when used as a scoped router.
+    pub(crate) strip_prefix_from_scope: bool,
+
+    // Whether the default 404 response lists the closest matching routes.
+    pub(crate) debug_404: bool,
+
+    // Whether `TRACE` requests can be answered by the routes accepting any method.
+    pub(crate) allow_trace: bool,
+
+    // Whether the missing `Content-Length` header is set from the buffered response body.
+    // It should be set only on root Router.
+    pub(crate) auto_content_length: bool,
+
+    // Whether the durations of the middlewares and the handler are reported via the `Server-Timing` header.
+    // It should be set only on root Router.
+    pub(crate) server_timing: bool,
+
+    // Whether the request body is read only when a handler asks for it via `RequestExt::body_bytes`.
+    // It should be set only on root Router.
+    pub(crate) defer_body_reading: bool,
+
+    // The limits on the request headers, checked before the request is routed.
+    // They should be set only on root Router.
+    pub(crate) max_headers: Option<usize>,
+    pub(crate) max_header_bytes: Option<usize>,
+
+    // The limit of the request body size, checked when the body is read.
+    // It should be set only on root Router.
+    pub(crate) body_limit: Option<usize>,
+
+    // The number of requests after which a connection is closed.
+    // It should be set only on root Router.
+    pub(crate) max_requests_per_connection: Option<usize>,
+
+    // Whether the `X-Forwarded-Proto` header set by a proxy is trusted to tell the secure requests.
+    // It should be set only on root Router.
+    pub(crate) trust_forwarded_proto: bool,
+
+    // The proxies trusted to report the client address.
+    // It should be set only on root Router.
+    pub(crate) trust_proxy: Option<Arc<TrustConfig>>,
+
+    // The limit of the decompressed request body size, enabling the request decompression.
+    // It should be set only on root Router.
+    #[cfg(feature = "decompression")]
+    pub(crate) decompress_requests: Option<usize>,
+
+    // It supersedes the percent-decoding of the request path before matching.
+    // It should be set only on root Router.
+    pub(crate) path_canonicalizer: Option<PathCanonicalizer>,
+
+    // The router which the requests not matched by any route of this router are dispatched to.
+    pub(crate) fallback: Option<Box<Router<E, B>>>,
+
+    // The deadline of the route handlers which don't have their own one.
+    // It should be set only on root Router.
+    pub(crate) handler_timeout: Option<Duration>,
+
+    // The compiled size limits of each path regex and of the RegexSet.
+    // It should be set only on root Router.
+    pub(crate) regex_size_limit: Option<usize>,
+    pub(crate) regex_set_size_limit: Option<usize>,
+
+    // The constraints of the route params, kept for the routes added via `Router::add_route`.
+    pub(crate) param_constraints: HashMap<String, String>,
+
+    // The maintenance mode toggle short-circuiting the requests.
+    // It should be set only on root Router.
+    pub(crate) maintenance: Maintenance,
+
+    // It receives the request lifecycle events.
+    // It should be set only on root Router.
+    pub(crate) metrics: Option<Arc<dyn MetricsRecorder>>,
+
+    // These are applied to every outgoing response after the post middlewares.
+    // They should be added only on root Router.
+    pub(crate) response_mappers: Vec<ResponseMapper<B>>,
 
     // We'll initialize it from the RouterService via Router::init_regex_set() method.
     regex_set: Option<RegexSet>,
@@ -73,13 +199,13 @@ pub struct Router<E> {
     pub(crate) should_gen_req_info: Option<bool>,
 }
 
-pub(crate) enum ErrHandler {
-    WithoutInfo(ErrHandlerWithoutInfo),
-    WithInfo(ErrHandlerWithInfo),
+pub(crate) enum ErrHandler<B> {
+    WithoutInfo(ErrHandlerWithoutInfo<B>),
+    WithInfo(ErrHandlerWithInfo<B>),
 }
 
-impl ErrHandler {
-    pub(crate) async fn execute(&self, err: RouteError, req_info: Option<RequestInfo>) -> Response<Full<Bytes>> {
+impl<B> ErrHandler<B> {
+    pub(crate) async fn execute(&self, err: RouteError, req_info: Option<RequestInfo>) -> Response<B> {
         match self {
             ErrHandler::WithoutInfo(err_handler) => Pin::from(err_handler(err)).await,
             ErrHandler::WithInfo(err_handler) => {
@@ -89,25 +215,89 @@ impl ErrHandler {
     }
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
+impl<E, B> Router<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     pub(crate) fn new(
-        pre_middlewares: Vec<PreMiddleware<E>>,
-        routes: Vec<Route<E>>,
-        post_middlewares: Vec<PostMiddleware<E>>,
+        pre_middlewares: Vec<PreMiddleware<E, B>>,
+        routes: Vec<Route<E, B>>,
+        post_middlewares: Vec<PostMiddleware<E, B>>,
         scoped_data_maps: Vec<ScopedDataMap>,
-        err_handler: Option<ErrHandler>,
+        err_handler: Option<ErrHandler<B>>,
     ) -> Self {
         Router {
             pre_middlewares,
             routes,
             post_middlewares,
             scoped_data_maps,
+            around_middlewares: Vec::new(),
             err_handler,
+            err_status_mappers: Vec::new(),
+            problem_json_errors: false,
+            minimal: false,
+            trailing_slash: TrailingSlashPolicy::Merge,
+            strip_prefix_from_scope: false,
+            debug_404: false,
+            allow_trace: false,
+            auto_content_length: true,
+            server_timing: false,
+            defer_body_reading: false,
+            max_headers: None,
+            max_header_bytes: None,
+            body_limit: None,
+            max_requests_per_connection: None,
+            trust_forwarded_proto: false,
+            trust_proxy: None,
+            #[cfg(feature = "decompression")]
+            decompress_requests: None,
+            path_canonicalizer: None,
+            fallback: None,
+            handler_timeout: None,
+            regex_size_limit: None,
+            regex_set_size_limit: None,
+            param_constraints: HashMap::new(),
+            maintenance: Maintenance::default(),
+            metrics: None,
+            response_mappers: Vec::new(),
             regex_set: None,
             should_gen_req_info: None,
         }
     }
 
+    // Initializes the router for serving the requests, along with its fallback router.
+    pub(crate) fn init(&mut self) -> crate::Result<()> {
+        // self.init_keep_alive_middleware();
+
+        if !self.minimal {
+            self.init_global_options_route();
+            self.init_default_404_route();
+        }
+        self.order_fallback_routes();
+
+        if !self.minimal {
+            self.init_err_handler();
+        }
+
+        // The route table compiled by `RouterBuilder::build_timed` is reused unless the routes changed since.
+        if self.regex_set.is_none() {
+            self.init_regex_set()?;
+        }
+        self.init_req_info_gen();
+
+        if let Some(fallback) = self.fallback.as_mut() {
+            fallback.init()?;
+
+            // The fallback router receives the request info generated for this router.
+            if fallback.should_gen_req_info == Some(true) {
+                self.should_gen_req_info = Some(true);
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn init_regex_set(&mut self) -> crate::Result<()> {
         let regex_iter = self
             .pre_middlewares
@@ -115,10 +305,30 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
             .map(|m| m.regex.as_str())
             .chain(self.routes.iter().map(|r| r.regex.as_str()))
             .chain(self.post_middlewares.iter().map(|m| m.regex.as_str()))
-            .chain(self.scoped_data_maps.iter().map(|d| d.regex.as_str()));
+            .chain(self.scoped_data_maps.iter().map(|d| d.regex.as_str()))
+            .chain(self.around_middlewares.iter().map(|m| m.regex.as_str()));
 
-        self.regex_set =
-            Some(RegexSet::new(regex_iter).map_err(|e| Error::new(format!("Couldn't create router RegexSet: {}", e)))?);
+        if let Some(limit) = self.regex_size_limit {
+            for re in regex_iter.clone() {
+                RegexBuilder::new(re).size_limit(limit).build().map_err(|e| {
+                    Error::new(format!(
+                        "The path regex `{}` exceeds the regex size limit of {} bytes: {}",
+                        re, limit, e
+                    ))
+                })?;
+            }
+        }
+
+        let mut regex_set_builder = RegexSetBuilder::new(regex_iter);
+        if let Some(limit) = self.regex_set_size_limit {
+            regex_set_builder.size_limit(limit);
+        }
+
+        self.regex_set = Some(
+            regex_set_builder
+                .build()
+                .map_err(|e| Error::new(format!("Couldn't create router RegexSet: {}", e)))?,
+        );
 
         Ok(())
     }
@@ -129,6 +339,20 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
             return;
         }
 
+        let scope_err_handlers = self
+            .pre_middlewares
+            .iter()
+            .map(|m| &m.scope_err_handler)
+            .chain(self.routes.iter().map(|route| &route.scope_err_handler))
+            .chain(self.post_middlewares.iter().map(|m| &m.scope_err_handler))
+            .chain(self.around_middlewares.iter().map(|m| &m.scope_err_handler));
+        for scope_err_handler in scope_err_handlers {
+            if let Some(ErrHandler::WithInfo(_)) = scope_err_handler.as_deref() {
+                self.should_gen_req_info = Some(true);
+                return;
+            }
+        }
+
         for post_middleware in self.post_middlewares.iter() {
             if post_middleware.should_require_req_meta() {
                 self.should_gen_req_info = Some(true);
@@ -161,51 +385,87 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
             return;
         }
 
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let options_route: Route<E> = Route::new("/*", options_method, |_req| async move {
-                Ok(Response::builder()
-                    .status(StatusCode::NO_CONTENT)
-                    .body(Full::new(Bytes::new()))
-                    .expect("Couldn't create the default OPTIONS response"))
-            })
-            .unwrap();
+        let options_route: Route<E, B> = Route::new("/*", options_method, |_req| async move {
+            Ok(Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .body(B::from(Bytes::new()))
+                .expect("Couldn't create the default OPTIONS response"))
+        })
+        .unwrap();
 
-            router.routes.push(options_route);
-        } else {
-            eprintln!(
-                "Warning: No global `options method` route added. It is recommended to send response to any `options` request.\n\
-                Please add one by calling `.options(\"/*\", handler)` method of the root router builder.\n"
-            );
+        self.routes.push(options_route);
+        self.regex_set = None;
+    }
+
+    // Moves the custom routes after the other routes and the not found handlers after them, the ones of the deeper
+    // scopes first, so that they only receive the requests which no regular route matches.
+    fn order_fallback_routes(&mut self) {
+        fn fallback_order<E, B>(route: &Route<E, B>) -> (bool, Option<std::cmp::Reverse<u32>>) {
+            (
+                route.custom || route.not_found,
+                route.not_found.then_some(std::cmp::Reverse(route.scope_depth)),
+            )
         }
+
+        if self.routes.is_sorted_by_key(fallback_order) {
+            return;
+        }
+
+        // The sort is stable, so the routes of the same kind keep their order.
+        self.routes.sort_by_key(fallback_order);
+        self.regex_set = None;
     }
 
     pub(crate) fn init_default_404_route(&mut self) {
         let found = self
             .routes
             .iter()
-            .any(|route| route.path == "/*" && route.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..]);
+            .any(|route| route.path == "/*" && route.is_any_method() && !route.custom);
 
         if found {
             return;
         }
 
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let default_404_route: Route<E> =
-                Route::new("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), |_req| async move {
-                    Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(Full::new(Bytes::new()))
-                        .expect("Couldn't create the default 404 response"))
-                })
-                .unwrap();
-            router.routes.push(default_404_route);
+        // The route table is captured before the catch-all route is added, so that
+        // the diagnostics only mention the routes registered by the user.
+        let registered_routes = if self.debug_404 {
+            self.routes
+                .iter()
+                .filter(|route| route.path != "/*" && !route.not_found && !route.custom)
+                .map(|route| (route.path.clone(), route.methods.clone()))
+                .collect::<Vec<_>>()
         } else {
-            eprintln!(
-                "Warning: No default 404 route added. It is recommended to send 404 response to any non-existent route.\n\
-                Please add one by calling `.any(handler)` method of the root router builder.\n"
-            );
-        }
+            Vec::new()
+        };
+        let registered_routes = Arc::new(registered_routes);
+        let debug_404 = self.debug_404;
+
+        let mut default_404_route: Route<E, B> =
+            Route::new("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), move |req| {
+                let res = match req.extensions().get::<AllowedMethods>() {
+                    Some(AllowedMethods(methods)) => method_not_allowed_response(methods),
+                    None => {
+                        let body = if debug_404 {
+                            Bytes::from(debug_404_body(&registered_routes, req.method(), req.uri().path()))
+                        } else {
+                            Bytes::new()
+                        };
+
+                        Response::builder()
+                            .status(StatusCode::NOT_FOUND)
+                            .header(header::CONTENT_TYPE, "text/plain")
+                            .extension(ResponseSource::Fallback404)
+                            .body(B::from(body))
+                            .expect("Couldn't create the default 404 response")
+                    }
+                };
+
+                async move { Ok(res) }
+            })
+            .unwrap();
+        default_404_route.not_found = true;
+        self.routes.push(default_404_route);
+        self.regex_set = None;
     }
 
     pub(crate) fn init_err_handler(&mut self) {
@@ -215,60 +475,434 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
             return;
         }
 
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let handler: ErrHandler = ErrHandler::WithoutInfo(Box::new(move |_: RouteError| {
-                Box::new(async move {
-                    Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(Full::new(Bytes::new()))
-                        .expect("Couldn't create a response while handling the server error")
-                })
-            }));
-            router.err_handler = Some(handler);
-        } else {
-            eprintln!(
-                "Warning: No error handler added. It is recommended to add one to see what went wrong if any route or middleware fails.\n\
-                Please add one by calling `.err_handler(handler)` method of the root router builder.\n"
-            );
-        }
-    }
+        // The built-in error types are checked after the registered ones.
+        let mut err_status_mappers = self.err_status_mappers.clone();
+        err_status_mappers.push(err_status_of::<ParamError>);
+        #[cfg(feature = "serde")]
+        err_status_mappers.push(err_status_of::<QueryError>);
+        #[cfg(feature = "json")]
+        err_status_mappers.push(err_status_of::<BodyError>);
+
+        let problem_json_errors = self.problem_json_errors;
+        let handler: ErrHandler<B> = ErrHandler::WithoutInfo(Box::new(move |err: RouteError| {
+            let status = err_status_mappers
+                .iter()
+                .find_map(|mapper| mapper(&err))
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+            let (content_type, body) = if problem_json_errors {
+                ("application/problem+json", Bytes::from(problem_json_body(status, &err)))
+            } else {
+                ("text/plain", Bytes::new())
+            };
 
-    fn downcast_to_hyper_body_type(&mut self) -> Option<&mut Router<E>> {
-        let any_obj: &mut dyn Any = self;
-        any_obj.downcast_mut::<Router<E>>()
+            Box::new(async move {
+                Response::builder()
+                    .status(status)
+                    .header(header::CONTENT_TYPE, content_type)
+                    .body(B::from(body))
+                    .expect("Couldn't create a response while handling the server error")
+            })
+        }));
+        self.err_handler = Some(handler);
     }
 
     /// Return a [RouterBuilder](./struct.RouterBuilder.html) instance to build a `Router`.
-    pub fn builder() -> RouterBuilder<E> {
+    pub fn builder() -> RouterBuilder<E, B> {
         builder::RouterBuilder::new()
     }
 
+    /// Adds a new route with the specified method(s) and the handler at the specified path, after the routes added via
+    /// the [RouterBuilder](./struct.RouterBuilder.html). It's meant for the
+    /// [`finalize`](./struct.RouterBuilder.html#method.finalize) hooks. Its params are constrained by the ones set via
+    /// [`RouterBuilder::param_constraint`](./struct.RouterBuilder.html#method.param_constraint).
+    pub fn add_route<P, H, R>(&mut self, path: P, methods: Vec<Method>, handler: H) -> crate::Result<()>
+    where
+        P: Into<String>,
+        H: Fn(Request<Full<Bytes>>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let mut path = path.into();
+        let trailing_slash = path.len() > 1 && path.ends_with('/');
+
+        if !path.ends_with('/') && !ends_with_glob(path.as_str()) {
+            path.push('/');
+        }
+
+        let mut route = Route::new(path, methods, handler)?;
+        route.trailing_slash = trailing_slash;
+        route.apply_param_constraints(&self.param_constraints)?;
+        self.routes.push(route);
+        // The route table must be recompiled to match the new route.
+        self.regex_set = None;
+
+        Ok(())
+    }
+
+    /// Returns the metadata of the pre middlewares in the order they are executed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{Middleware, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// let router: Router<Infallible> = Router::builder()
+    ///     .middleware_labeled("logger", Middleware::pre(|req| async move { Ok(req) }))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let pre_middleware = router.pre_middleware();
+    /// assert_eq!(1, pre_middleware.len());
+    /// assert_eq!(Some("logger"), pre_middleware[0].label());
+    /// ```
+    pub fn pre_middleware(&self) -> Vec<MiddlewareInfo> {
+        self.pre_middlewares
+            .iter()
+            .map(|m| MiddlewareInfo {
+                path: m.path.clone(),
+                priority: m.priority,
+                name: m.name.clone(),
+                label: m.label.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the metadata of the post middlewares in the order they are executed.
+    pub fn post_middleware(&self) -> Vec<MiddlewareInfo> {
+        self.post_middlewares
+            .iter()
+            .map(|m| MiddlewareInfo {
+                path: m.path.clone(),
+                priority: m.priority,
+                name: m.name.clone(),
+                label: m.label.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the metadata of the around middlewares in the order they are nested, the outermost first.
+    pub fn around_middleware(&self) -> Vec<MiddlewareInfo> {
+        self.around_middlewares
+            .iter()
+            .map(|m| MiddlewareInfo {
+                path: m.path.clone(),
+                priority: m.priority,
+                name: m.name.clone(),
+                label: m.label.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the metadata of the routes in the order they are matched, e.g. to print the routing table at startup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Method, Response};
+    /// use routerify_ng::Router;
+    /// use std::convert::Infallible;
+    ///
+    /// let api: Router<Infallible> = Router::builder()
+    ///     .get("/users/:userId", |_| async move { Ok(Response::new(Full::new(Bytes::from("User")))) })
+    ///     .build()
+    ///     .unwrap();
+    /// let router: Router<Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home")))) })
+    ///     .scope("/api", api)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let routes = router.routes();
+    /// assert_eq!(2, routes.len());
+    /// assert_eq!("/api/users/:userId", routes[1].path());
+    /// assert_eq!(&[Method::GET], routes[1].methods());
+    /// assert_eq!(2, routes[1].scope_depth());
+    /// ```
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        self.routes
+            .iter()
+            .map(|route| RouteInfo {
+                // The trailing slash appended to the path when the route was added is dropped.
+                path: route.declared_path().to_owned(),
+                methods: route.methods.clone(),
+                scope_depth: route.scope_depth,
+            })
+            .collect()
+    }
+
     pub(crate) async fn process(
+        &self,
+        target_path: &str,
+        mut req: Request<Full<Bytes>>,
+        req_info: Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        let Some(ref metrics) = self.metrics else {
+            return self.respond(target_path, req, req_info).await;
+        };
+
+        let started = Instant::now();
+        metrics.on_request_start(req.method(), req.uri().path());
+
+        // The request service shares it with the `RequestInfo` already.
+        let matched_route = match req.extensions().get::<MatchedRoute>() {
+            Some(matched_route) => matched_route.clone(),
+            None => {
+                let matched_route = MatchedRoute::default();
+                req.extensions_mut().insert(matched_route.clone());
+                matched_route
+            }
+        };
+
+        // The maintenance responses are recorded too, without a matched route.
+        let res = self.respond(target_path, req, req_info).await;
+
+        let status = res
+            .as_ref()
+            .map(|res| res.status())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        metrics.on_request_end(status, started.elapsed(), matched_route.get());
+
+        res
+    }
+
+    // Records a response the request service sends before the request reaches `process`, e.g. a rejected oversized
+    // request, so that the metrics cover every request.
+    pub(crate) fn record_early_response(
+        &self,
+        method: &Method,
+        path: &str,
+        started: Instant,
+        res: crate::Result<Response<B>>,
+    ) -> crate::Result<Response<B>> {
+        if let Some(ref metrics) = self.metrics {
+            metrics.on_request_start(method, path);
+            let status = res
+                .as_ref()
+                .map(|res| res.status())
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            metrics.on_request_end(status, started.elapsed(), None);
+        }
+        res
+    }
+
+    // Dispatches the request, unless the maintenance mode short-circuits it.
+    async fn respond(
+        &self,
+        target_path: &str,
+        req: Request<Full<Bytes>>,
+        req_info: Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        if let Some(res) = self.maintenance.check(req.uri().path()) {
+            return Ok(self.map_response(res));
+        }
+
+        self.dispatch(target_path, req, req_info)
+            .await
+            .map(|res| self.map_response(res))
+    }
+
+    // Generates the path which is matched against the routes from the request path.
+    pub(crate) fn target_path(&self, req_path: &str) -> crate::Result<String> {
+        let mut target_path = match self.path_canonicalizer {
+            Some(ref canonicalize) => canonicalize(req_path).into_owned(),
+            None => helpers::percent_decode_request_path(req_path)
+                .map_err(|e| Error::new(format!("Couldn't percent decode request path: {}", e)))?,
+        };
+
+        if target_path.is_empty() || target_path.as_bytes()[target_path.len() - 1] != b'/' {
+            target_path.push('/');
+        }
+
+        Ok(target_path)
+    }
+
+    pub(crate) fn map_response(&self, res: Response<B>) -> Response<B> {
+        let mut res = self
+            .response_mappers
+            .iter()
+            .fold(res, |res, response_mapper| response_mapper(res));
+
+        if self.auto_content_length {
+            set_content_length(&mut res);
+        }
+
+        res
+    }
+
+    // Checks whether a trusted proxy reported the original request as secure. Only the first
+    // proxy's value counts, as the following ones may be appended by the untrusted hops.
+    pub(crate) fn is_forwarded_secure(&self, headers: &HeaderMap) -> bool {
+        self.trust_forwarded_proto
+            && headers
+                .get("x-forwarded-proto")
+                .and_then(|val| val.to_str().ok())
+                .and_then(|val| val.split(',').next())
+                .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+    }
+
+    // Returns the `308 Permanent Redirect` response to the canonical form of the request path if the trailing slash
+    // policy asks for redirecting it.
+    pub(crate) fn redirect_trailing_slash(&self, uri: &Uri) -> Option<Response<B>> {
+        let path = uri.path();
+        let location = match self.trailing_slash {
+            TrailingSlashPolicy::RedirectToNoSlash if has_trailing_slash(path) => path.trim_end_matches('/').to_owned(),
+            TrailingSlashPolicy::RedirectToSlash if !path.ends_with('/') => format!("{}/", path),
+            _ => return None,
+        };
+        // A path consisting of slashes only is redirected to the root.
+        let location = match (location.is_empty(), uri.query()) {
+            (true, Some(query)) => format!("/?{}", query),
+            (true, None) => "/".to_owned(),
+            (false, Some(query)) => format!("{}?{}", location, query),
+            (false, None) => location,
+        };
+
+        Some(
+            Response::builder()
+                .status(StatusCode::PERMANENT_REDIRECT)
+                .header(header::LOCATION, location)
+                .body(B::from(Bytes::new()))
+                .expect("Couldn't create the trailing slash redirect response"),
+        )
+    }
+
+    // Returns the `431 Request Header Fields Too Large` response if the request headers exceed the limits.
+    pub(crate) fn check_header_limits(&self, headers: &HeaderMap) -> Option<Response<B>> {
+        let too_many = self.max_headers.is_some_and(|max| headers.len() > max);
+        let too_large = self.max_header_bytes.is_some_and(|max| {
+            let size = headers
+                .iter()
+                .map(|(name, value)| name.as_str().len() + value.len())
+                .sum::<usize>();
+            size > max
+        });
+
+        if !too_many && !too_large {
+            return None;
+        }
+
+        Some(
+            Response::builder()
+                .status(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE)
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(B::from(Bytes::new()))
+                .expect("Couldn't create the header limit rejection response"),
+        )
+    }
+
+    // Returns the `413 Payload Too Large` response if the declared `Content-Length` of the request exceeds the body
+    // limit, so that the body isn't read at all.
+    pub(crate) fn check_content_length(&self, headers: &HeaderMap) -> Option<Response<B>> {
+        let limit = self.body_limit?;
+        let content_length = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.parse::<u64>().ok())?;
+
+        (content_length > limit as u64).then(payload_too_large_response)
+    }
+
+    // Decodes the buffered request body in place if the request decompression is enabled. It returns the rejection
+    // response if the body can't be decoded.
+    #[cfg(feature = "decompression")]
+    pub(crate) fn decompress_body(&self, headers: &mut HeaderMap, body: &mut Bytes) -> Option<Response<B>> {
+        let max_size = self.decompress_requests?;
+        crate::decompression::decompress(headers, body, max_size).map(|res| self.map_response(res))
+    }
+
+    // Returns the decoding of a body which is read only when it's accessed, if the request decompression is enabled.
+    #[cfg(feature = "decompression")]
+    pub(crate) fn deferred_decompression(
+        &self,
+        headers: &mut HeaderMap,
+    ) -> Option<crate::decompression::Decompression> {
+        crate::decompression::Decompression::from_headers(headers, self.decompress_requests?)
+    }
+
+    // Collects the methods of the routes matching the path, i.e. the ones listed in the `Allow` header of a
+    // `405 Method Not Allowed` response.
+    fn allowed_methods(&self, matched_route_idxs: &[usize], req: &Request<Full<Bytes>>) -> Vec<Method> {
+        let mut allowed_methods = Vec::new();
+        for idx in matched_route_idxs {
+            let route = &self.routes[*idx];
+            if route.path == "/*" || route.not_found || route.custom || !route.is_match_guard(req) {
+                continue;
+            }
+            for method in route.methods.iter() {
+                if !allowed_methods.contains(method) {
+                    allowed_methods.push(method.clone());
+                }
+            }
+        }
+        allowed_methods
+    }
+
+    async fn dispatch(
         &self,
         target_path: &str,
         mut req: Request<Full<Bytes>>,
         mut req_info: Option<RequestInfo>,
-    ) -> crate::Result<Response<Full<Bytes>>> {
+    ) -> crate::Result<Response<B>> {
         let (
             matched_pre_middleware_idxs,
-            matched_route_idxs,
+            mut matched_route_idxs,
             matched_post_middleware_idxs,
             matched_scoped_data_map_idxs,
+            matched_around_middleware_idxs,
         ) = self.match_regex_set(target_path);
 
+        // The target path always ends with a slash, so the one of the request path is checked separately.
+        if self.trailing_slash == TrailingSlashPolicy::Strict {
+            let trailing_slash = has_trailing_slash(req.uri().path());
+            matched_route_idxs.retain(|idx| self.routes[*idx].is_match_trailing_slash(trailing_slash));
+        }
+
+        // The method is overridden up front, as the routes and the middlewares to run are picked by it.
+        if matched_pre_middleware_idxs
+            .iter()
+            .any(|idx| self.pre_middlewares[*idx].overrides_method)
+        {
+            crate::method_override::apply(&mut req);
+        }
+
         let mut route_scope_depth = None;
         for idx in &matched_route_idxs {
             let route = &self.routes[*idx];
             // Middleware should be executed even if there's no route, e.g.
             // logging. Before doing the depth check make sure that there's
-            // an actual route match, not a catch-all "/*".
-            if route.is_match_method(req.method()) && route.path != "/*" {
+            // an actual route match, not a catch-all "/*" or a not found handler.
+            if route.is_match_method(req.method())
+                && route.is_match_guard(&req)
+                && (route.path != "/*" || route.custom)
+                && !route.not_found
+            {
                 route_scope_depth = Some(route.scope_depth);
+                // It's set up front, so that the pre middlewares can read it too.
+                helpers::update_req_meta_in_extensions(
+                    req.extensions_mut(),
+                    RequestMeta::with_matched_path(route.matched_path().to_owned()),
+                );
                 break;
             }
         }
 
+        // An unmatched request is handed over to the fallback router as is, before it reaches
+        // the catch-all routes of this router.
+        if let (None, Some(fallback)) = (route_scope_depth, self.fallback.as_ref()) {
+            return Box::pin(fallback.process(target_path, req, req_info)).await;
+        }
+
+        // The methods of the routes matching the path are passed to the default 404 route, which
+        // responds with `405 Method Not Allowed` instead if there are any.
+        if route_scope_depth.is_none() {
+            let allowed_methods = self.allowed_methods(&matched_route_idxs, &req);
+            if !allowed_methods.is_empty() {
+                req.extensions_mut().insert(AllowedMethods(allowed_methods));
+            }
+        }
+
         let shared_data_maps = matched_scoped_data_map_idxs
             .into_iter()
             .map(|idx| self.scoped_data_maps[idx].clone_data_map())
@@ -283,8 +917,22 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
         let ext = req.extensions_mut();
         ext.insert(shared_data_maps);
 
+        // Do not execute middleware with the same prefix but from a deeper scope.
+        let matched_around_middleware_idxs = matched_around_middleware_idxs
+            .into_iter()
+            .filter(|idx| route_scope_depth.is_none_or(|depth| self.around_middlewares[*idx].scope_depth <= depth))
+            .collect::<Vec<_>>();
+
+        let mut timings = self.server_timing.then(Vec::new);
+
         let res_pre = self
-            .execute_pre_middleware(req, matched_pre_middleware_idxs, route_scope_depth, req_info.clone())
+            .execute_pre_middleware(
+                req,
+                matched_pre_middleware_idxs,
+                route_scope_depth,
+                req_info.clone(),
+                &mut timings,
+            )
             .await?;
 
         // If pre middlewares succeed then execute the route handler.
@@ -294,34 +942,79 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
         let mut resp = None;
         match res_pre {
             Ok(transformed_req) => {
+                // A `TRACE` request is answered only by the routes registered explicitly for it,
+                // unless it's allowed via `RouterBuilder::allow_trace`.
+                let block_trace = transformed_req.method() == Method::TRACE && !self.allow_trace;
+                let trace_allowed_methods = block_trace.then(|| {
+                    let mut allowed_methods = self.allowed_methods(&matched_route_idxs, &transformed_req);
+                    allowed_methods.retain(|method| method != Method::TRACE);
+                    allowed_methods
+                });
+
                 for idx in matched_route_idxs {
                     let route = &self.routes[idx];
 
-                    if route.is_match_method(transformed_req.method()) {
+                    if block_trace && route.is_any_method() {
+                        continue;
+                    }
+
+                    if route.is_match_method(transformed_req.method()) && route.is_match_guard(&transformed_req) {
+                        // The path exists with other methods, so that a scoped not found handler mustn't hide it.
+                        if route.not_found
+                            && let Some(AllowedMethods(methods)) = transformed_req.extensions().get::<AllowedMethods>()
+                        {
+                            resp = Some(method_not_allowed_response(methods));
+                            break;
+                        }
+
                         // Convert transformed_req to the expected type for route.process
-                        let req_for_route = transformed_req.map(|b| b);
-                        let route_resp_res = route.process(target_path, req_for_route).await;
-
-                        let route_resp = match route_resp_res {
-                            Ok(route_resp) => route_resp,
-                            Err(err) => {
-                                if let Some(ref err_handler) = self.err_handler {
-                                    err_handler.execute(err, req_info.clone()).await
-                                } else {
-                                    return Err(err);
-                                }
-                            }
-                        };
+                        let mut req_for_route = transformed_req.map(|b| b);
+                        helpers::update_req_meta_in_extensions(
+                            req_for_route.extensions_mut(),
+                            RequestMeta::with_matched_route_index(idx),
+                        );
+                        // A pre middleware may have changed the request, so that another route handles it.
+                        helpers::update_req_meta_in_extensions(
+                            req_for_route.extensions_mut(),
+                            RequestMeta::with_matched_path(route.matched_path().to_owned()),
+                        );
+                        let mut handler_elapsed = None;
+                        let route_resp = self
+                            .call_around(
+                                &matched_around_middleware_idxs,
+                                route,
+                                target_path,
+                                req_for_route,
+                                &req_info,
+                                &mut handler_elapsed,
+                            )
+                            .await?;
+                        if let (Some(timings), Some(elapsed)) = (timings.as_mut(), handler_elapsed) {
+                            timings.push(("handler".to_owned(), elapsed));
+                        }
+
                         resp = Some(route_resp);
                         break;
                     }
                 }
+
+                if resp.is_none()
+                    && let Some(allowed_methods) = trace_allowed_methods
+                {
+                    resp = Some(method_not_allowed_response(&allowed_methods));
+                }
             }
             Err(err_response) => {
                 resp = Some(err_response);
             }
         };
 
+        // A minimal router has no 404 route, so that the unmatched request gets a bare response instead of the
+        // connection being aborted.
+        if resp.is_none() && self.minimal {
+            resp = Some(bare_not_found_response());
+        }
+
         if resp.is_none() {
             let e = "No handlers added to handle non-existent routes. Tips: Please add an '.any' route at the bottom to handle any routes.";
             return Err(crate::Error::new(e).into());
@@ -332,13 +1025,23 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
             let post_middleware = &self.post_middlewares[idx];
             // Do not execute middleware with the same prefix but from a deeper scope.
             if route_scope_depth.is_none() || post_middleware.scope_depth <= route_scope_depth.unwrap() {
-                match post_middleware.process(transformed_res, req_info.clone()).await {
+                let started = Instant::now();
+                let post_res = post_middleware.process(transformed_res, req_info.clone()).await;
+                if let Some(ref mut timings) = timings {
+                    let name = middleware_timing_name(post_middleware.name.as_deref(), "post", idx);
+                    timings.push((name, started.elapsed()));
+                }
+
+                match post_res {
                     Ok(res_resp) => {
                         transformed_res = res_resp;
                     }
                     Err(err) => {
-                        if let Some(ref err_handler) = self.err_handler {
-                            return Ok(err_handler.execute(err, req_info.clone()).await);
+                        if let Some(err_handler) = self.err_handler_for(post_middleware.scope_err_handler.as_ref()) {
+                            return Ok(with_source(
+                                err_handler.execute(err, req_info.clone()).await,
+                                ResponseSource::ErrorHandler,
+                            ));
                         } else {
                             return Err(err);
                         }
@@ -347,6 +1050,12 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
             }
         }
 
+        if let Some(timings) = timings
+            && !timings.is_empty()
+        {
+            append_server_timing(&mut transformed_res, &timings);
+        }
+
         Ok(transformed_res)
     }
 
@@ -356,19 +1065,30 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
         matched_pre_middleware_idxs: Vec<usize>,
         route_scope_depth: Option<u32>,
         req_info: Option<RequestInfo>,
-    ) -> crate::Result<Result<Request<Full<Bytes>>, Response<Full<Bytes>>>> {
+        timings: &mut Option<Vec<(String, Duration)>>,
+    ) -> crate::Result<Result<Request<Full<Bytes>>, Response<B>>> {
         let mut transformed_req = req;
         for idx in matched_pre_middleware_idxs {
             let pre_middleware = &self.pre_middlewares[idx];
             // Do not execute middleware with the same prefix but from a deeper scope.
             if route_scope_depth.is_none() || pre_middleware.scope_depth <= route_scope_depth.unwrap() {
-                match pre_middleware.process(transformed_req).await {
+                let started = Instant::now();
+                let pre_res = pre_middleware.process(transformed_req).await;
+                if let Some(timings) = timings {
+                    let name = middleware_timing_name(pre_middleware.name.as_deref(), "pre", idx);
+                    timings.push((name, started.elapsed()));
+                }
+
+                match pre_res {
                     Ok(res_req) => {
                         transformed_req = res_req;
                     }
                     Err(err) => {
-                        if let Some(ref err_handler) = self.err_handler {
-                            return Ok(Err(err_handler.execute(err, req_info).await));
+                        if let Some(err_handler) = self.err_handler_for(pre_middleware.scope_err_handler.as_ref()) {
+                            return Ok(Err(with_source(
+                                err_handler.execute(err, req_info).await,
+                                ResponseSource::ErrorHandler,
+                            )));
                         } else {
                             return Err(err);
                         }
@@ -379,7 +1099,144 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
         Ok(Ok(transformed_req))
     }
 
-    fn match_regex_set(&self, target_path: &str) -> (Vec<usize>, Vec<usize>, Vec<usize>, Vec<usize>) {
+    // Calls the route handler wrapped by the around middlewares at `around_idxs`, the first one being the outermost.
+    // The request and the response are passed between an around middleware and the rest of the chain via its `Next`,
+    // so that its handler can return a `'static` future like the other handlers.
+    fn call_around<'a>(
+        &'a self,
+        around_idxs: &'a [usize],
+        route: &'a Route<E, B>,
+        target_path: &'a str,
+        req: Request<Full<Bytes>>,
+        req_info: &'a Option<RequestInfo>,
+        handler_elapsed: &'a mut Option<Duration>,
+    ) -> ChainReturn<'a, B> {
+        Box::pin(async move {
+            let Some((&idx, inner_around_idxs)) = around_idxs.split_first() else {
+                return self
+                    .call_route(route, target_path, req, req_info, handler_elapsed)
+                    .await;
+            };
+
+            let around_middleware = &self.around_middlewares[idx];
+            let (next, mut req_rx, res_tx) = Next::channel();
+            let mut around_res = std::pin::pin!(around_middleware.process(req, next));
+
+            // The around middleware may respond without running the rest of the chain.
+            let req = tokio::select! {
+                res = &mut around_res => return self.around_response(around_middleware, res, req_info).await,
+                Ok(req) = &mut req_rx => req,
+            };
+
+            // The around middleware is still polled while the rest of the chain runs, e.g. for its own deadline.
+            let inner_res = self.call_around(inner_around_idxs, route, target_path, req, req_info, handler_elapsed);
+            let inner_err = tokio::select! {
+                res = &mut around_res => return self.around_response(around_middleware, res, req_info).await,
+                inner_res = inner_res => match inner_res {
+                    Ok(res) => {
+                        let _ = res_tx.send(res);
+                        None
+                    }
+                    // An error without an error handler to turn it into a response is returned once the around
+                    // middleware is done with the stand-in response.
+                    Err(err) => {
+                        let _ = res_tx.send(internal_server_error());
+                        Some(err)
+                    }
+                },
+            };
+
+            let res = around_res.await;
+            match inner_err {
+                Some(err) => Err(err),
+                None => self.around_response(around_middleware, res, req_info).await,
+            }
+        })
+    }
+
+    // Calls the route handler, turning its error into a response via the error handler when there's one.
+    async fn call_route(
+        &self,
+        route: &Route<E, B>,
+        target_path: &str,
+        req: Request<Full<Bytes>>,
+        req_info: &Option<RequestInfo>,
+        handler_elapsed: &mut Option<Duration>,
+    ) -> crate::Result<Response<B>> {
+        let started = Instant::now();
+        let route_resp_res = match route.timeout.or(self.handler_timeout) {
+            Some(timeout) => tokio::time::timeout(timeout, route.process(target_path, req))
+                .await
+                .unwrap_or_else(|_| Ok(handler_timeout_response())),
+            None => route.process(target_path, req).await,
+        };
+        *handler_elapsed = Some(started.elapsed());
+
+        match route_resp_res {
+            Ok(mut route_resp) => {
+                // The default 404 and the timeout responses are marked when created.
+                if route_resp.extensions().get::<ResponseSource>().is_none() {
+                    route_resp.extensions_mut().insert(ResponseSource::Route);
+                }
+                Ok(route_resp)
+            }
+            Err(err) => match self.err_handler_for(route.scope_err_handler.as_ref()) {
+                Some(err_handler) => Ok(with_source(
+                    err_handler.execute(err, req_info.clone()).await,
+                    ResponseSource::ErrorHandler,
+                )),
+                None => Err(err),
+            },
+        }
+    }
+
+    // Turns the error of an around middleware into a response via the error handler when there's one.
+    async fn around_response(
+        &self,
+        around_middleware: &AroundMiddleware<E, B>,
+        res: crate::Result<Response<B>>,
+        req_info: &Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        match res {
+            Ok(res) => Ok(res),
+            Err(err) => match self.err_handler_for(around_middleware.scope_err_handler.as_ref()) {
+                Some(err_handler) => Ok(with_source(
+                    err_handler.execute(err, req_info.clone()).await,
+                    ResponseSource::ErrorHandler,
+                )),
+                None => Err(err),
+            },
+        }
+    }
+
+    // Resolves the error handler of a failed route or middleware: the one of the innermost scope it was mounted from
+    // which has one, otherwise the one of this router.
+    fn err_handler_for<'a>(&'a self, scope_err_handler: Option<&'a Arc<ErrHandler<B>>>) -> Option<&'a ErrHandler<B>> {
+        scope_err_handler.map(Arc::as_ref).or(self.err_handler.as_ref())
+    }
+
+    // Returns whether the request body must be buffered before the request is routed, i.e. unless all the routes
+    // matching the request declared they don't need it.
+    pub(crate) fn needs_body(&self, target_path: &str, method: &Method) -> bool {
+        let (_, matched_route_idxs, _, _, _) = self.match_regex_set(target_path);
+
+        let mut matched_routes = matched_route_idxs
+            .into_iter()
+            .map(|idx| &self.routes[idx])
+            .filter(|route| route.is_match_method(method) && (route.path != "/*" || route.custom) && !route.not_found)
+            .peekable();
+
+        if matched_routes.peek().is_none() {
+            return match self.fallback {
+                Some(ref fallback) => fallback.needs_body(target_path, method),
+                None => true,
+            };
+        }
+
+        matched_routes.any(|route| route.needs_body)
+    }
+
+    fn match_regex_set(&self, target_path: &str) -> RegexSetMatches {
         let matches = self
             .regex_set
             .as_ref()
@@ -391,11 +1248,13 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
         let routes_len = self.routes.len();
         let post_middlewares_len = self.post_middlewares.len();
         let scoped_data_maps_len = self.scoped_data_maps.len();
+        let around_middlewares_len = self.around_middlewares.len();
 
         let mut matched_pre_middleware_idxs = Vec::new();
         let mut matched_route_idxs = Vec::new();
         let mut matched_post_middleware_idxs = Vec::new();
         let mut matched_scoped_data_map_idxs = Vec::new();
+        let mut matched_around_middleware_idxs = Vec::new();
 
         for idx in matches {
             if idx < pre_middlewares_len {
@@ -410,6 +1269,16 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
                 && idx < (pre_middlewares_len + routes_len + post_middlewares_len + scoped_data_maps_len)
             {
                 matched_scoped_data_map_idxs.push(idx - pre_middlewares_len - routes_len - post_middlewares_len);
+            } else if idx >= (pre_middlewares_len + routes_len + post_middlewares_len + scoped_data_maps_len)
+                && idx
+                    < (pre_middlewares_len
+                        + routes_len
+                        + post_middlewares_len
+                        + scoped_data_maps_len
+                        + around_middlewares_len)
+            {
+                matched_around_middleware_idxs
+                    .push(idx - pre_middlewares_len - routes_len - post_middlewares_len - scoped_data_maps_len);
             }
         }
 
@@ -418,21 +1287,228 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<E> {
             matched_route_idxs,
             matched_post_middleware_idxs,
             matched_scoped_data_map_idxs,
+            matched_around_middleware_idxs,
         )
     }
 }
 
-impl<E> Debug for Router<E> {
+// Renders the RFC 7807 problem details of the error handled by the default error handler.
+fn problem_json_body(status: StatusCode, err: &RouteError) -> String {
+    format!(
+        r#"{{"type":"about:blank","title":"{}","status":{},"detail":"{}"}}"#,
+        helpers::escape_json(status.canonical_reason().unwrap_or_default()),
+        status.as_u16(),
+        helpers::escape_json(&err.to_string())
+    )
+}
+
+// The number of the closest matching routes listed in the debug 404 response.
+const DEBUG_404_SUGGESTIONS: usize = 3;
+
+fn debug_404_body(registered_routes: &[(String, Vec<Method>)], method: &Method, path: &str) -> String {
+    let mut body = format!("No route matches {} {}\n", method, path);
+
+    let mut candidates = registered_routes
+        .iter()
+        .map(|(route_path, methods)| {
+            let route_path = match route_path.strip_suffix('/') {
+                Some(trimmed) if !trimmed.is_empty() => trimmed,
+                _ => route_path.as_str(),
+            };
+            (helpers::levenshtein_distance(route_path, path), route_path, methods)
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(distance, _, _)| *distance);
+
+    if !candidates.is_empty() {
+        body.push_str("\nClosest matching routes:\n");
+        for (_, route_path, methods) in candidates.into_iter().take(DEBUG_404_SUGGESTIONS) {
+            let methods = methods.iter().map(Method::as_str).collect::<Vec<_>>().join(", ");
+            body.push_str(&format!("  {} {}\n", methods, route_path));
+        }
+    }
+
+    body
+}
+
+fn has_trailing_slash(path: &str) -> bool {
+    path.len() > 1 && path.ends_with('/')
+}
+
+// The methods of the routes matching the path of a request which none of them accepts.
+#[derive(Clone)]
+struct AllowedMethods(Vec<Method>);
+
+fn method_not_allowed_response<B: From<Bytes>>(allowed_methods: &[Method]) -> Response<B> {
+    let allow = allowed_methods
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Response::builder()
+        .status(StatusCode::METHOD_NOT_ALLOWED)
+        .header(header::ALLOW, allow)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .extension(ResponseSource::MethodNotAllowed)
+        .body(B::from(Bytes::new()))
+        .expect("Couldn't create the method not allowed response")
+}
+
+fn bare_not_found_response<B: From<Bytes>>() -> Response<B> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .extension(ResponseSource::Fallback404)
+        .body(B::from(Bytes::new()))
+        .expect("Couldn't create the bare not found response")
+}
+
+pub(crate) fn payload_too_large_response<B: From<Bytes>>() -> Response<B> {
+    Response::builder()
+        .status(StatusCode::PAYLOAD_TOO_LARGE)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(B::from(Bytes::new()))
+        .expect("Couldn't create the body limit rejection response")
+}
+
+fn handler_timeout_response<B: From<Bytes>>() -> Response<B> {
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .extension(ResponseSource::Timeout)
+        .body(B::from(Bytes::new()))
+        .expect("Couldn't create the handler timeout response")
+}
+
+// Sets the `Content-Length` header from the buffered body, unless the response has it already, is chunked or must not
+// have a body.
+fn set_content_length<B: Body>(res: &mut Response<B>) {
+    let status = res.status();
+    if res.headers().contains_key(header::CONTENT_LENGTH)
+        || res.headers().contains_key(header::TRANSFER_ENCODING)
+        || status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+    {
+        return;
+    }
+
+    if let Some(len) = res.body().size_hint().exact() {
+        res.headers_mut().insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+    }
+}
+
+fn middleware_timing_name(name: Option<&str>, kind: &str, idx: usize) -> String {
+    match name {
+        Some(name) => name.to_owned(),
+        None => format!("mw_{}_{}", kind, idx),
+    }
+}
+
+fn append_server_timing<B>(res: &mut Response<B>, timings: &[(String, Duration)]) {
+    let value = timings
+        .iter()
+        .map(|(name, dur)| format!("{};dur={:.1}", name, dur.as_secs_f64() * 1000.0))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        res.headers_mut()
+            .append(header::HeaderName::from_static("server-timing"), value);
+    }
+}
+
+fn with_source<B>(mut res: Response<B>, source: ResponseSource) -> Response<B> {
+    res.extensions_mut().insert(source);
+    res
+}
+
+impl<E, B> Debug for Router<E, B> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{ Pre-Middlewares: {:?}, Routes: {:?}, Post-Middlewares: {:?}, ScopedDataMaps: {:?}, ErrHandler: {:?}, ShouldGenReqInfo: {:?} }}",
+            "{{ Pre-Middlewares: {:?}, Routes: {:?}, Post-Middlewares: {:?}, Around-Middlewares: {:?}, ScopedDataMaps: {:?}, ErrHandler: {:?}, ShouldGenReqInfo: {:?} }}",
             self.pre_middlewares,
             self.routes,
             self.post_middlewares,
+            self.around_middlewares,
             self.scoped_data_maps,
             self.err_handler.is_some(),
             self.should_gen_req_info
         )
     }
 }
+
+/// Prints the route table in a readable multi-line format, e.g. for debugging.
+///
+/// The routes are listed as `METHOD  /path` in the order they were added, grouped under the path of the scope they
+/// were mounted at. The routes accepting any method are listed with `*`. The fallback router, if any, is listed last.
+impl<E, B> Display for Router<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The routes are grouped by the scope they were mounted at, in the order the scopes first appear.
+        let mut scopes: Vec<(&str, Vec<(String, &str)>)> = Vec::new();
+        for route in self.routes.iter() {
+            let methods = if route.is_any_method() {
+                "*".to_owned()
+            } else {
+                route.methods.iter().map(Method::as_str).collect::<Vec<_>>().join(", ")
+            };
+            let entry = (methods, route.declared_path());
+            match scopes
+                .iter_mut()
+                .find(|(scope_path, _)| *scope_path == route.scope_path)
+            {
+                Some((_, routes)) => routes.push(entry),
+                None => scopes.push((route.scope_path.as_str(), vec![entry])),
+            }
+        }
+        let width = scopes
+            .iter()
+            .flat_map(|(_, routes)| routes.iter())
+            .map(|(methods, _)| methods.len())
+            .max()
+            .unwrap_or(0);
+
+        writeln!(f, "Pre-Middlewares:")?;
+        for m in self.pre_middlewares.iter() {
+            writeln!(f, "  {}", m.path)?;
+        }
+
+        if !self.around_middlewares.is_empty() {
+            writeln!(f, "Around-Middlewares:")?;
+            for m in self.around_middlewares.iter() {
+                writeln!(f, "  {}", m.path)?;
+            }
+        }
+
+        writeln!(f, "Routes:")?;
+        for (scope_path, routes) in scopes.iter() {
+            let indent = if scope_path.is_empty() {
+                "  "
+            } else {
+                writeln!(f, "  Scope {}:", scope_path)?;
+                "    "
+            };
+            for (methods, path) in routes.iter() {
+                writeln!(f, "{}{:width$}  {}", indent, methods, path, width = width)?;
+            }
+        }
+
+        writeln!(f, "Post-Middlewares:")?;
+        for m in self.post_middlewares.iter() {
+            writeln!(f, "  {}", m.path)?;
+        }
+
+        if let Some(ref fallback) = self.fallback {
+            writeln!(f, "Fallback Router:")?;
+            for line in fallback.to_string().lines() {
+                writeln!(f, "  {}", line)?;
+            }
+        }
+
+        Ok(())
+    }
+}
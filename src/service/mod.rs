@@ -1,5 +1,11 @@
 pub use request_service::{RequestService, RequestServiceBuilder};
-pub use router_service::RouterService;
+pub use router_service::{IntoMakeService, RouterService};
+pub use serve::{serve, serve_with_graceful_shutdown};
+pub use shutdown::ShutdownHandle;
 
 mod request_service;
 mod router_service;
+mod serve;
+mod shutdown;
+#[cfg(feature = "tower")]
+mod tower;
@@ -1,45 +1,135 @@
-use crate::Error;
 use crate::helpers;
-use crate::router::Router;
-use crate::types::{RequestContext, RequestInfo, RequestMeta};
-use bytes::BytesMut;
+use crate::maintenance::Maintenance;
+use crate::metrics::MatchedRoute;
+use crate::router::{Router, payload_too_large_response};
+use crate::service::ShutdownHandle;
+#[cfg(feature = "client-cert")]
+use crate::types::ClientCert;
+use crate::types::{
+    BufferedBody, CancellationGuard, CookieCache, DeferredTasks, QueryCache, ReadError, RemoteAddr, RequestBody,
+    RequestContext, RequestInfo, RequestMeta, collect_body,
+};
 use http_body_util::BodyExt;
 use http_body_util::Full;
-use hyper::body::Bytes;
 use hyper::body::Incoming;
-use hyper::{Request, Response, service::Service};
+use hyper::body::{Body, Bytes};
+use hyper::header::{self, HeaderValue};
+use hyper::{Request, Response, Version, service::Service};
 use std::future::Future;
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// A [`Service`](https://docs.rs/hyper/1/hyper/service/trait.Service.html) processing the requests of a single connection
+/// with the router.
+///
+/// With the `tower` feature enabled, it also implements [`tower::Service`](https://docs.rs/tower-service/0.3/tower_service/trait.Service.html),
+/// so that it can be wrapped in the Tower layers.
+pub struct RequestService<E, B = Full<Bytes>> {
+    pub(crate) router: Arc<Router<E, B>>,
+    pub(crate) remote_addr: RemoteAddr,
+    pub(crate) local_addr: Option<SocketAddr>,
+    pub(crate) defer_body_reading: bool,
+    pub(crate) secure: bool,
+    // The number of requests received on the connection so far.
+    pub(crate) requests_served: AtomicUsize,
+    // Counts the in-flight requests, see `RouterService::graceful`.
+    pub(crate) shutdown: Option<ShutdownHandle>,
+    #[cfg(feature = "client-cert")]
+    pub(crate) client_cert: Option<Arc<ClientCert>>,
+}
 
-pub struct RequestService<E> {
-    pub(crate) router: Arc<Router<E>>,
-    pub(crate) remote_addr: SocketAddr,
+impl<E, B> RequestService<E, B> {
+    /// Attaches the certificate the client authenticated the connection with, so that the handlers can access it via
+    /// [`RequestExt::client_cert`](./ext/trait.RequestExt.html#tymethod.client_cert).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use hyper_util::rt::{TokioExecutor, TokioIo};
+    /// use hyper_util::server::conn::auto::Builder;
+    /// use routerify_ng::{ClientCert, RequestServiceBuilder, Router};
+    /// use std::convert::Infallible;
+    /// use tokio::net::TcpStream;
+    ///
+    /// # async fn accept_tls(stream: TcpStream) -> std::io::Result<(TcpStream, Vec<Vec<u8>>)> { unimplemented!() }
+    /// async fn run(stream: TcpStream, builder: RequestServiceBuilder<Infallible>) {
+    ///     let remote_addr = stream.peer_addr().unwrap();
+    ///     // Run the TLS handshake, e.g. with `tokio-rustls`, and get the verified peer certificate chain.
+    ///     let (tls_stream, peer_certificates) = accept_tls(stream).await.unwrap();
+    ///
+    ///     let mut service = builder.build(remote_addr);
+    ///     if let Ok(client_cert) = ClientCert::from_der_chain(peer_certificates) {
+    ///         service = service.with_client_cert(client_cert);
+    ///     }
+    ///
+    ///     Builder::new(TokioExecutor::new())
+    ///         .serve_connection(TokioIo::new(tls_stream), service)
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "client-cert")]
+    pub fn with_client_cert(mut self, client_cert: ClientCert) -> Self {
+        self.client_cert = Some(Arc::new(client_cert));
+        self
+    }
 }
 
-impl<E> Service<Request<Full<Bytes>>> for RequestService<E>
+impl<E, B> Service<Request<Full<Bytes>>> for RequestService<E, B>
 where
     E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
 {
-    type Response = Response<Full<Bytes>>;
+    type Response = Response<B>;
     type Error = crate::RouteError;
     #[allow(clippy::type_complexity)]
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
 
     fn call(&self, mut req: Request<Full<Bytes>>) -> Self::Future {
         let router = self.router.clone();
-        let remote_addr = self.remote_addr;
+        let remote_addr = self.remote_addr.clone();
+        let local_addr = self.local_addr;
+        let secure = self.secure;
+        #[cfg(feature = "client-cert")]
+        let client_cert = self.client_cert.clone();
 
-        let fut = async move {
-            helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+        let close_connection = close_connection_after(&self.router, &self.requests_served, req.version());
+        let in_flight = self.shutdown.as_ref().map(ShutdownHandle::track);
 
-            let mut target_path = helpers::percent_decode_request_path(req.uri().path())
-                .map_err(|e| Error::new(format!("Couldn't percent decode request path: {}", e)))?;
+        let fut = async move {
+            let _in_flight = in_flight;
+            let started = Instant::now();
+
+            let early_res = router
+                .check_header_limits(req.headers())
+                .or_else(|| router.check_content_length(req.headers()))
+                .or_else(|| router.redirect_trailing_slash(req.uri()));
+            if let Some(res) = early_res {
+                let res = Ok(router.map_response(res));
+                return router.record_early_response(req.method(), req.uri().path(), started, res);
+            }
 
-            if target_path.is_empty() || target_path.as_bytes()[target_path.len() - 1] != b'/' {
-                target_path.push('/');
+            helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+            if let Some(local_addr) = local_addr {
+                helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_local_addr(local_addr));
+            }
+            if secure || router.is_forwarded_secure(req.headers()) {
+                helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_secure());
             }
+            #[cfg(feature = "client-cert")]
+            if let Some(client_cert) = client_cert {
+                req.extensions_mut().insert(client_cert);
+            }
+
+            let target_path = match router.target_path(req.uri().path()) {
+                Ok(target_path) => target_path,
+                Err(err) => {
+                    return router.record_early_response(req.method(), req.uri().path(), started, Err(err));
+                }
+            };
 
             let mut req_info = None;
             let should_gen_req_info = router
@@ -47,6 +137,7 @@ where
                 .expect("The `should_gen_req_info` flag in Router is not initialized");
 
             let context = RequestContext::new();
+            req.extensions_mut().insert(MatchedRoute::default());
 
             if should_gen_req_info {
                 req_info = Some(RequestInfo::new_from_req(&req, context.clone()));
@@ -54,35 +145,104 @@ where
 
             req.extensions_mut().insert(context);
 
-            router.process(target_path.as_str(), req, req_info.clone()).await
+            // The guard is dropped without completion if hyper drops this future, e.g. on client disconnect.
+            let (cancellation_guard, cancellation) = CancellationGuard::new();
+            req.extensions_mut().insert(cancellation);
+
+            let deferred_tasks = DeferredTasks::default();
+            req.extensions_mut().insert(deferred_tasks.clone());
+            req.extensions_mut().insert(QueryCache::default());
+            req.extensions_mut().insert(CookieCache::default());
+            if let Some(ref trust_proxy) = router.trust_proxy {
+                req.extensions_mut().insert(trust_proxy.clone());
+            }
+
+            // The `Full` body is already in memory, so it's buffered right away to make it available
+            // via `RequestExt::body_bytes` too.
+            let (mut parts, body) = req.into_parts();
+            let collected = body.collect().await?.to_bytes();
+            if router.body_limit.is_some_and(|limit| collected.len() > limit) {
+                cancellation_guard.complete();
+                let res = Ok(router.map_response(payload_too_large_response()));
+                return router.record_early_response(&parts.method, parts.uri.path(), started, res);
+            }
+            #[cfg(feature = "decompression")]
+            let collected = {
+                let mut collected = collected;
+                if let Some(res) = router.decompress_body(&mut parts.headers, &mut collected) {
+                    cancellation_guard.complete();
+                    return router.record_early_response(&parts.method, parts.uri.path(), started, Ok(res));
+                }
+                collected
+            };
+            parts.extensions.insert(RequestBody::read(collected.clone()));
+            parts.extensions.insert(BufferedBody(collected.clone()));
+
+            let req = Request::from_parts(parts, Full::new(collected));
+
+            let res = router.process(target_path.as_str(), req, req_info.clone()).await;
+            cancellation_guard.complete();
+            deferred_tasks.spawn_all();
+            res
         };
 
-        Box::pin(fut)
+        Box::pin(with_connection_close(fut, close_connection))
     }
 }
 
-impl<E> Service<Request<Incoming>> for RequestService<E>
+impl<E, B> Service<Request<Incoming>> for RequestService<E, B>
 where
     E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
 {
-    type Response = Response<Full<Bytes>>;
+    type Response = Response<B>;
     type Error = crate::RouteError;
     #[allow(clippy::type_complexity)]
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
 
     fn call(&self, mut req: Request<Incoming>) -> Self::Future {
         let router = self.router.clone();
-        let remote_addr = self.remote_addr;
+        let remote_addr = self.remote_addr.clone();
+        let local_addr = self.local_addr;
+        let secure = self.secure;
+        let defer_body_reading = self.defer_body_reading;
+        #[cfg(feature = "client-cert")]
+        let client_cert = self.client_cert.clone();
 
-        let fut = async move {
-            helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+        let close_connection = close_connection_after(&self.router, &self.requests_served, req.version());
+        let in_flight = self.shutdown.as_ref().map(ShutdownHandle::track);
 
-            let mut target_path = helpers::percent_decode_request_path(req.uri().path())
-                .map_err(|e| Error::new(format!("Couldn't percent decode request path: {}", e)))?;
+        let fut = async move {
+            let _in_flight = in_flight;
+            let started = Instant::now();
+
+            let early_res = router
+                .check_header_limits(req.headers())
+                .or_else(|| router.check_content_length(req.headers()))
+                .or_else(|| router.redirect_trailing_slash(req.uri()));
+            if let Some(res) = early_res {
+                let res = Ok(router.map_response(res));
+                return router.record_early_response(req.method(), req.uri().path(), started, res);
+            }
 
-            if target_path.is_empty() || target_path.as_bytes()[target_path.len() - 1] != b'/' {
-                target_path.push('/');
+            helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+            if let Some(local_addr) = local_addr {
+                helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_local_addr(local_addr));
+            }
+            if secure || router.is_forwarded_secure(req.headers()) {
+                helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_secure());
             }
+            #[cfg(feature = "client-cert")]
+            if let Some(client_cert) = client_cert {
+                req.extensions_mut().insert(client_cert);
+            }
+
+            let target_path = match router.target_path(req.uri().path()) {
+                Ok(target_path) => target_path,
+                Err(err) => {
+                    return router.record_early_response(req.method(), req.uri().path(), started, Err(err));
+                }
+            };
 
             let mut req_info = None;
             let should_gen_req_info = router
@@ -90,6 +250,7 @@ where
                 .expect("The `should_gen_req_info` flag in Router is not initialized");
 
             let context = RequestContext::new();
+            req.extensions_mut().insert(MatchedRoute::default());
 
             if should_gen_req_info {
                 req_info = Some(RequestInfo::new_from_req(&req, context.clone()));
@@ -97,55 +258,182 @@ where
 
             req.extensions_mut().insert(context);
 
-            let (parts, mut body) = req.into_parts();
-
-            let mut buf = BytesMut::new();
+            // The guard is dropped without completion if hyper drops this future, e.g. on client disconnect.
+            let (cancellation_guard, cancellation) = CancellationGuard::new();
+            req.extensions_mut().insert(cancellation);
 
-            while let Some(frame) = body.frame().await {
-                let frame = frame?;
-                if let Some(data) = frame.data_ref() {
-                    buf.extend_from_slice(data);
-                }
+            let deferred_tasks = DeferredTasks::default();
+            req.extensions_mut().insert(deferred_tasks.clone());
+            req.extensions_mut().insert(QueryCache::default());
+            req.extensions_mut().insert(CookieCache::default());
+            if let Some(ref trust_proxy) = router.trust_proxy {
+                req.extensions_mut().insert(trust_proxy.clone());
             }
 
-            let collected = buf.freeze();
-
-            let req_rebuilt = Request::from_parts(parts, Full::new(collected));
-
-            router
+            let (mut parts, body) = req.into_parts();
+
+            let defer_body_reading = router.defer_body_reading
+                || defer_body_reading
+                || !router.needs_body(target_path.as_str(), &parts.method);
+
+            let req_rebuilt = if defer_body_reading {
+                #[cfg(feature = "decompression")]
+                let request_body = match router.deferred_decompression(&mut parts.headers) {
+                    Some(decompression) => RequestBody::unread_decompressed(body, router.body_limit, decompression),
+                    None => RequestBody::unread(body, router.body_limit),
+                };
+                #[cfg(not(feature = "decompression"))]
+                let request_body = RequestBody::unread(body, router.body_limit);
+                parts.extensions.insert(request_body);
+                Request::from_parts(parts, Full::new(Bytes::new()))
+            } else {
+                let collected = match collect_body(body, router.body_limit).await {
+                    Ok(collected) => collected,
+                    Err(ReadError::TooLarge(_)) => {
+                        cancellation_guard.complete();
+                        let res = Ok(router.map_response(payload_too_large_response()));
+                        return router.record_early_response(&parts.method, parts.uri.path(), started, res);
+                    }
+                    Err(err) => {
+                        let res = Err(crate::Error::new(err.to_string()).into());
+                        return router.record_early_response(&parts.method, parts.uri.path(), started, res);
+                    }
+                };
+                #[cfg(feature = "decompression")]
+                let collected = {
+                    let mut collected = collected;
+                    if let Some(res) = router.decompress_body(&mut parts.headers, &mut collected) {
+                        cancellation_guard.complete();
+                        return router.record_early_response(&parts.method, parts.uri.path(), started, Ok(res));
+                    }
+                    collected
+                };
+                parts.extensions.insert(RequestBody::read(collected.clone()));
+                parts.extensions.insert(BufferedBody(collected.clone()));
+                Request::from_parts(parts, Full::new(collected))
+            };
+
+            let res = router
                 .process(target_path.as_str(), req_rebuilt, req_info.clone())
-                .await
+                .await;
+            cancellation_guard.complete();
+            deferred_tasks.spawn_all();
+            res
         };
 
-        Box::pin(fut)
+        Box::pin(with_connection_close(fut, close_connection))
     }
 }
 
-#[derive(Debug)]
-pub struct RequestServiceBuilder<E> {
-    router: Arc<Router<E>>,
+// Counts the request and returns whether the connection must be closed after responding to it. Only the HTTP/1
+// connections are closed via the `Connection` header.
+fn close_connection_after<E, B>(router: &Router<E, B>, requests_served: &AtomicUsize, version: Version) -> bool {
+    let served = requests_served.fetch_add(1, Ordering::Relaxed) + 1;
+    version <= Version::HTTP_11 && router.max_requests_per_connection.is_some_and(|max| served >= max)
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RequestServiceBuilder<E> {
-    pub fn new(mut router: Router<E>) -> crate::Result<Self> {
-        // router.init_keep_alive_middleware();
-
-        router.init_global_options_route();
-        router.init_default_404_route();
+async fn with_connection_close<F, B>(fut: F, close_connection: bool) -> Result<Response<B>, crate::RouteError>
+where
+    F: Future<Output = Result<Response<B>, crate::RouteError>>,
+{
+    let mut res = fut.await?;
+    if close_connection {
+        res.headers_mut()
+            .insert(header::CONNECTION, HeaderValue::from_static("close"));
+    }
+    Ok(res)
+}
 
-        router.init_err_handler();
+#[derive(Debug)]
+pub struct RequestServiceBuilder<E, B = Full<Bytes>> {
+    router: Arc<Router<E, B>>,
+    defer_body_reading: bool,
+    secure: bool,
+    shutdown: Option<ShutdownHandle>,
+}
 
-        router.init_regex_set()?;
-        router.init_req_info_gen();
+impl<E, B> RequestServiceBuilder<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    pub fn new(mut router: Router<E, B>) -> crate::Result<Self> {
+        router.init()?;
         Ok(Self {
             router: Arc::from(router),
+            defer_body_reading: false,
+            secure: false,
+            shutdown: None,
         })
     }
 
-    pub fn build(&self, remote_addr: SocketAddr) -> RequestService<E> {
+    /// Sets whether reading the request bodies is deferred until a handler asks for them, which is `false` by default.
+    ///
+    /// It enables the router's [`defer_body_reading`](./struct.RouterBuilder.html#method.defer_body_reading) option
+    /// for the requests served by the built services, e.g. to share a router between services which differ in it. The
+    /// handlers and the pre middlewares receive an empty body, and the body is still buffered in full, with the
+    /// router's body limit, when it's first read via
+    /// [`RequestExt::body_bytes`](./ext/trait.RequestExt.html#tymethod.body_bytes).
+    pub fn defer_body_reading(mut self, defer: bool) -> Self {
+        self.defer_body_reading = defer;
+        self
+    }
+
+    /// Sets whether the connections the request services are built for are secure, e.g. accepted by a TLS acceptor,
+    /// which is reported by [`RequestExt::is_secure`](./ext/trait.RequestExt.html#tymethod.is_secure). It's `false` by
+    /// default.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Returns the [`Maintenance`](./struct.Maintenance.html) mode handle of the router, to toggle it at runtime.
+    pub fn maintenance(&self) -> Maintenance {
+        self.router.maintenance.clone()
+    }
+
+    // Makes the request services count their in-flight requests with the handle.
+    pub(crate) fn track_in_flight(mut self, shutdown: ShutdownHandle) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    pub fn build(&self, remote_addr: SocketAddr) -> RequestService<E, B> {
+        self.build_with_remote_addr(RemoteAddr::Tcp(remote_addr))
+    }
+
+    // Creates a request service for a connection from a client connected over TCP or a Unix domain socket.
+    pub(crate) fn build_with_remote_addr(&self, remote_addr: RemoteAddr) -> RequestService<E, B> {
         RequestService {
             router: self.router.clone(),
             remote_addr,
+            local_addr: None,
+            defer_body_reading: self.defer_body_reading,
+            secure: self.secure,
+            requests_served: AtomicUsize::new(0),
+            shutdown: self.shutdown.clone(),
+            #[cfg(feature = "client-cert")]
+            client_cert: None,
+        }
+    }
+
+    /// Creates a request service for a connection like [`build`](#method.build), which also knows the local address
+    /// the connection was accepted on, see [`RequestExt::local_addr`](./ext/trait.RequestExt.html#tymethod.local_addr).
+    pub fn build_with_local_addr(&self, remote_addr: SocketAddr, local_addr: SocketAddr) -> RequestService<E, B> {
+        RequestService {
+            local_addr: Some(local_addr),
+            ..self.build(remote_addr)
+        }
+    }
+}
+
+impl<E, B> Clone for RequestServiceBuilder<E, B> {
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+            defer_body_reading: self.defer_body_reading,
+            secure: self.secure,
+            shutdown: self.shutdown.clone(),
         }
     }
 }
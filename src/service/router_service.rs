@@ -1,18 +1,26 @@
+use crate::maintenance::Maintenance;
 use crate::router::Router;
+use crate::service::ShutdownHandle;
 use crate::service::request_service::{RequestService, RequestServiceBuilder};
+use crate::types::{RemoteAddr, UNSPECIFIED_SOCKET_ADDR};
+use http_body_util::Full;
+use hyper::body::{Body, Bytes};
 use hyper::service::Service;
 use std::convert::Infallible;
 use std::future::{Ready, ready};
+use std::net::SocketAddr;
 use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
 
 /// A [`Service`](https://docs.rs/hyper/0.14.4/hyper/service/trait.Service.html) to process incoming requests.
 ///
-/// This `RouterService<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `RouterService<E, B>` type accepts two type parameters: `E` and `B`.
 ///
-/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
-///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
-///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [Body](https://docs.rs/hyper/1/hyper/body/trait.Body.html) trait with `Bytes` data, and be creatable from `Bytes`. It defaults to
+///   [`Full<Bytes>`](https://docs.rs/http-body-util/0.1/http_body_util/struct.Full.html).
 ///
 /// # Examples
 ///
@@ -73,31 +81,154 @@ use tokio::net::TcpStream;
 /// }
 /// ```
 #[derive(Debug)]
-pub struct RouterService<E> {
-    builder: RequestServiceBuilder<E>,
+pub struct RouterService<E, B = Full<Bytes>> {
+    builder: RequestServiceBuilder<E, B>,
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterService<E> {
+impl<E, B> RouterService<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
     /// Creates a new service with the provided router and it's ready to be used with the hyper [`serve`](https://docs.rs/hyper/0.14.4/hyper/server/struct.Builder.html#method.serve)
     /// method.
-    pub fn new(router: Router<E>) -> crate::Result<RouterService<E>> {
+    pub fn new(router: Router<E, B>) -> crate::Result<RouterService<E, B>> {
         let builder = RequestServiceBuilder::new(router)?;
         Ok(RouterService { builder })
     }
+
+    /// Returns the [`Maintenance`](./struct.Maintenance.html) mode handle of the router, to toggle it at runtime.
+    pub fn maintenance(&self) -> Maintenance {
+        self.builder.maintenance()
+    }
+
+    /// Makes the service track the requests being processed and returns it along with a
+    /// [`ShutdownHandle`](./struct.ShutdownHandle.html), whose [`shutdown`](./struct.ShutdownHandle.html#method.shutdown)
+    /// resolves once they all complete. So the in-flight requests can be drained after the server stops accepting
+    /// new connections.
+    ///
+    /// The tracking carries over to the make-service created by [`into_make_service`](#method.into_make_service).
+    pub fn graceful(self) -> (RouterService<E, B>, ShutdownHandle) {
+        let shutdown = ShutdownHandle::default();
+        let builder = self.builder.track_in_flight(shutdown.clone());
+        (RouterService { builder }, shutdown)
+    }
+
+    /// Converts the service into a make-service, which creates a [`RequestService`](./struct.RequestService.html) for
+    /// each connection from the remote address instead of the `TcpStream`. So it can be used with any listener and
+    /// directly with the `hyper_util` connection builders.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use http_body_util::Full;
+    /// use hyper::body::Bytes;
+    /// use hyper::service::Service;
+    /// use hyper::Response;
+    /// use hyper_util::rt::{TokioExecutor, TokioIo};
+    /// use hyper_util::server::conn::auto::Builder;
+    /// use routerify_ng::{Router, RouterService};
+    /// use std::convert::Infallible;
+    /// use tokio::net::TcpListener;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ///     let router: Router<Infallible> = Router::builder()
+    ///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+    ///         .build()
+    ///         .unwrap();
+    ///     let make_service = RouterService::new(router)?.into_make_service();
+    ///
+    ///     let listener = TcpListener::bind("127.0.0.1:3001").await?;
+    ///     loop {
+    ///         let (stream, addr) = listener.accept().await?;
+    ///         let request_service = make_service.call(addr).await?;
+    ///
+    ///         tokio::spawn(async move {
+    ///             let builder = Builder::new(TokioExecutor::new());
+    ///             if let Err(err) = builder.serve_connection(TokioIo::new(stream), request_service).await {
+    ///                 eprintln!("Error serving connection: {:?}", err);
+    ///             }
+    ///         });
+    ///     }
+    /// }
+    /// ```
+    pub fn into_make_service(self) -> IntoMakeService<E, B> {
+        IntoMakeService { builder: self.builder }
+    }
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Service<&TcpStream> for RouterService<E> {
-    type Response = RequestService<E>;
+impl<E, B> Service<&TcpStream> for RouterService<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    type Response = RequestService<E, B>;
     type Error = Infallible;
     type Future = Ready<Result<Self::Response, Self::Error>>;
 
     fn call(&self, conn: &TcpStream) -> Self::Future {
-        let addr = match conn.peer_addr() {
-            Ok(addr) => addr,
-            Err(_) => std::net::SocketAddr::from(([0, 0, 0, 0], 0)),
+        let addr = conn.peer_addr().unwrap_or(UNSPECIFIED_SOCKET_ADDR);
+        let req_service = match conn.local_addr() {
+            Ok(local_addr) => self.builder.build_with_local_addr(addr, local_addr),
+            Err(_) => self.builder.build(addr),
         };
-        let req_service = self.builder.build(addr);
 
         ready(Ok(req_service))
     }
 }
+
+/// Creates a [`RequestService`](./struct.RequestService.html) for a connection accepted on a Unix domain socket, e.g. by
+/// a local-only admin server or a sidecar.
+///
+/// The client address is available via [`RequestExt::peer_addr`](./ext/trait.RequestExt.html#tymethod.peer_addr), while
+/// [`RequestExt::remote_addr`](./ext/trait.RequestExt.html#tymethod.remote_addr) returns the unspecified `0.0.0.0:0`
+/// address.
+#[cfg(unix)]
+impl<E, B> Service<&UnixStream> for RouterService<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    type Response = RequestService<E, B>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn call(&self, conn: &UnixStream) -> Self::Future {
+        let path = conn
+            .peer_addr()
+            .ok()
+            .and_then(|addr| addr.as_pathname().map(|path| path.to_path_buf()));
+
+        ready(Ok(self.builder.build_with_remote_addr(RemoteAddr::Unix(path))))
+    }
+}
+
+/// A make-service which creates a [`RequestService`](./struct.RequestService.html) for each connection from its
+/// remote address, see [`RouterService::into_make_service`](./struct.RouterService.html#method.into_make_service).
+#[derive(Debug)]
+pub struct IntoMakeService<E, B = Full<Bytes>> {
+    builder: RequestServiceBuilder<E, B>,
+}
+
+impl<E, B> Clone for IntoMakeService<E, B> {
+    fn clone(&self) -> Self {
+        IntoMakeService {
+            builder: self.builder.clone(),
+        }
+    }
+}
+
+impl<E, B> Service<SocketAddr> for IntoMakeService<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    type Response = RequestService<E, B>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn call(&self, remote_addr: SocketAddr) -> Self::Future {
+        ready(Ok(self.builder.build(remote_addr)))
+    }
+}
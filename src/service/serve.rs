@@ -0,0 +1,132 @@
+use crate::RouteError;
+use crate::service::RouterService;
+use hyper::body::{Body, Bytes};
+use hyper::service::Service;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::server::graceful::GracefulShutdown;
+use std::future::{Future, pending};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Accepts connections on the `listener` and serves them with the provided [`RouterService`](./struct.RouterService.html)
+/// until an error occurs while accepting a new connection.
+///
+/// Any error raised while serving an individual connection, e.g. a client sending a malformed request, doesn't stop the server.
+/// Instead, it's passed to the `on_conn_error` callback so that it can be routed into the app's logging or metrics.
+///
+/// # Examples
+///
+/// ```no_run
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Request, Response};
+/// use routerify_ng::{Router, RouterService};
+/// use std::convert::Infallible;
+/// use tokio::net::TcpListener;
+///
+/// async fn home(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, Infallible> {
+///     Ok(Response::new(Full::new(Bytes::from("Home page"))))
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> routerify_ng::Result<()> {
+///     let router: Router<Infallible> = Router::builder().get("/", home).build()?;
+///     let service = RouterService::new(router)?;
+///
+///     let listener = TcpListener::bind("127.0.0.1:3001").await?;
+///
+///     routerify_ng::serve(listener, service, |err| {
+///         eprintln!("Error serving connection: {}", err);
+///     })
+///     .await
+/// }
+/// ```
+pub async fn serve<E, B, C>(listener: TcpListener, service: RouterService<E, B>, on_conn_error: C) -> crate::Result<()>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    C: Fn(&RouteError) + Send + Sync + 'static,
+{
+    serve_with_graceful_shutdown(listener, service, on_conn_error, pending()).await
+}
+
+/// Same as [`serve`](./fn.serve.html), but stops accepting new connections once the `signal` future resolves.
+///
+/// After the signal fires, the connections which are already open are asked to shut down gracefully and this function
+/// waits for them to finish before returning.
+///
+/// # Examples
+///
+/// ```no_run
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Request, Response};
+/// use routerify_ng::{Router, RouterService};
+/// use std::convert::Infallible;
+/// use tokio::net::TcpListener;
+///
+/// async fn home(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, Infallible> {
+///     Ok(Response::new(Full::new(Bytes::from("Home page"))))
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> routerify_ng::Result<()> {
+///     let router: Router<Infallible> = Router::builder().get("/", home).build()?;
+///     let service = RouterService::new(router)?;
+///
+///     let listener = TcpListener::bind("127.0.0.1:3001").await?;
+///
+///     routerify_ng::serve_with_graceful_shutdown(
+///         listener,
+///         service,
+///         |err| eprintln!("Error serving connection: {}", err),
+///         async {
+///             tokio::signal::ctrl_c().await.ok();
+///         },
+///     )
+///     .await
+/// }
+/// ```
+pub async fn serve_with_graceful_shutdown<E, B, C, S>(
+    listener: TcpListener,
+    service: RouterService<E, B>,
+    on_conn_error: C,
+    signal: S,
+) -> crate::Result<()>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    C: Fn(&RouteError) + Send + Sync + 'static,
+    S: Future<Output = ()>,
+{
+    let on_conn_error = Arc::new(on_conn_error);
+    let builder = Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+
+    tokio::pin!(signal);
+
+    loop {
+        tokio::select! {
+            conn = listener.accept() => {
+                let (stream, _) = conn?;
+
+                let request_service = service.call(&stream).await?;
+                let conn = builder.serve_connection(TokioIo::new(stream), request_service).into_owned();
+                let conn = graceful.watch(conn);
+
+                let on_conn_error = on_conn_error.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = conn.await {
+                        on_conn_error(&err);
+                    }
+                });
+            }
+            _ = &mut signal => break,
+        }
+    }
+
+    graceful.shutdown().await;
+
+    Ok(())
+}
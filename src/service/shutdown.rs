@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Notify;
+
+/// A handle to wait for the in-flight requests of a [`RouterService`](./struct.RouterService.html) to complete, e.g.
+/// to drain the server during a rolling deploy. It's created by
+/// [`RouterService::graceful`](./struct.RouterService.html#method.graceful) and the clones share the same counter.
+///
+/// A request is counted from the moment the [`RequestService`](./struct.RequestService.html) is called with it until
+/// its response is produced or its future is dropped, e.g. on client disconnect.
+///
+/// # Examples
+///
+/// ```no_run
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::{Router, RouterService};
+/// use std::convert::Infallible;
+/// use tokio::net::TcpListener;
+///
+/// #[tokio::main]
+/// async fn main() -> routerify_ng::Result<()> {
+///     let router: Router<Infallible> = Router::builder()
+///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+///         .build()?;
+///     let (service, shutdown) = RouterService::new(router)?.graceful();
+///
+///     let listener = TcpListener::bind("127.0.0.1:3001").await?;
+///     routerify_ng::serve_with_graceful_shutdown(
+///         listener,
+///         service,
+///         |err| eprintln!("Error serving connection: {}", err),
+///         async {
+///             tokio::signal::ctrl_c().await.ok();
+///         },
+///     )
+///     .await?;
+///
+///     // Wait for the requests which are still being processed.
+///     shutdown.shutdown().await;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownHandle {
+    in_flight: Arc<InFlight>,
+}
+
+impl ShutdownHandle {
+    /// Returns the number of requests which are being processed.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.count.load(Ordering::Acquire)
+    }
+
+    /// Resolves once there are no in-flight requests. It resolves right away if there are none.
+    ///
+    /// It doesn't stop the new requests from being accepted, so it should be awaited after the listener is closed.
+    pub async fn shutdown(&self) {
+        loop {
+            let notified = self.in_flight.idle.notified();
+            tokio::pin!(notified);
+            // Register the waiter before checking the counter, so that the notification can't be missed.
+            notified.as_mut().enable();
+
+            if self.in_flight() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    pub(crate) fn track(&self) -> InFlightGuard {
+        self.in_flight.count.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct InFlight {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+// Counts a request as in-flight until it's dropped.
+#[derive(Debug)]
+pub(crate) struct InFlightGuard {
+    in_flight: Arc<InFlight>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.in_flight.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.in_flight.idle.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn waits_for_in_flight_requests() {
+        let handle = ShutdownHandle::default();
+        handle.shutdown().await;
+
+        let first = handle.track();
+        let second = handle.track();
+        assert_eq!(handle.in_flight(), 2);
+
+        let waiter = tokio::spawn({
+            let handle = handle.clone();
+            async move { handle.shutdown().await }
+        });
+
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        drop(second);
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("shutdown didn't resolve")
+            .unwrap();
+        assert_eq!(handle.in_flight(), 0);
+    }
+}
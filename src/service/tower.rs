@@ -0,0 +1,48 @@
+use crate::service::request_service::RequestService;
+use http_body_util::Full;
+use hyper::body::{Body, Bytes, Incoming};
+use hyper::{Request, Response};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// The `tower::Service` implementations delegate to the `hyper::service::Service` ones, so that the
+// router can be wrapped in the Tower layers, e.g. the ones from `tower-http`.
+
+impl<E, B> tower_service::Service<Request<Incoming>> for RequestService<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    type Response = Response<B>;
+    type Error = crate::RouteError;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Incoming>) -> Self::Future {
+        hyper::service::Service::call(self, req)
+    }
+}
+
+impl<E, B> tower_service::Service<Request<Full<Bytes>>> for RequestService<E, B>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+{
+    type Response = Response<B>;
+    type Error = crate::RouteError;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Full<Bytes>>) -> Self::Future {
+        hyper::service::Service::call(self, req)
+    }
+}
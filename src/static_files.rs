@@ -0,0 +1,204 @@
+use crate::body::{self, BoxBody};
+use crate::ext::RequestExt;
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::header;
+use hyper::{Method, Request, Response, StatusCode};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The content types of the common file extensions, the other files are served as `application/octet-stream`.
+const CONTENT_TYPES: [(&str, &str); 31] = [
+    ("css", "text/css; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("gif", "image/gif"),
+    ("gz", "application/gzip"),
+    ("htm", "text/html; charset=utf-8"),
+    ("html", "text/html; charset=utf-8"),
+    ("ico", "image/x-icon"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("ogg", "audio/ogg"),
+    ("otf", "font/otf"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("ttf", "font/ttf"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("wasm", "application/wasm"),
+    ("wav", "audio/wav"),
+    ("webm", "video/webm"),
+    ("webp", "image/webp"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("xml", "application/xml"),
+    ("zip", "application/zip"),
+];
+
+// Serves the files of a directory, see `RouterBuilder::static_files`.
+pub(crate) struct StaticFiles {
+    dir: PathBuf,
+}
+
+impl StaticFiles {
+    pub(crate) fn new(dir: PathBuf) -> StaticFiles {
+        StaticFiles { dir }
+    }
+
+    // Responds with the file at the path captured by the `path` glob, `304 Not Modified` if the client's copy is
+    // still fresh, `403 Forbidden` if the path tries to escape the directory or `404 Not Found` if there's no such file.
+    pub(crate) async fn serve(&self, req: &Request<Full<Bytes>>) -> Response<BoxBody> {
+        let Some(file_path) = resolve(&self.dir, req.param("path").map(String::as_str).unwrap_or_default()) else {
+            return rejection(StatusCode::FORBIDDEN);
+        };
+
+        let (file_path, metadata) = match tokio::fs::metadata(&file_path).await {
+            Ok(metadata) if metadata.is_dir() => {
+                let index_path = file_path.join("index.html");
+                match tokio::fs::metadata(&index_path).await {
+                    Ok(metadata) if metadata.is_file() => (index_path, metadata),
+                    _ => return rejection(StatusCode::NOT_FOUND),
+                }
+            }
+            Ok(metadata) if metadata.is_file() => (file_path, metadata),
+            _ => return rejection(StatusCode::NOT_FOUND),
+        };
+
+        let last_modified = metadata.modified().ok();
+        let etag = etag(metadata.len(), last_modified);
+
+        let mut builder = Response::builder().header(header::ETAG, etag.as_str());
+        if let Some(last_modified) = last_modified {
+            builder = builder.header(header::LAST_MODIFIED, httpdate::fmt_http_date(last_modified));
+        }
+
+        let not_modified = match req.headers().get(header::IF_NONE_MATCH) {
+            Some(val) => val.to_str().is_ok_and(|val| etag_matches(val, &etag)),
+            None => last_modified.is_some_and(|last_modified| req.not_modified_since(last_modified)),
+        };
+        if not_modified {
+            return builder
+                .status(StatusCode::NOT_MODIFIED)
+                .body(BoxBody::default())
+                .expect("Couldn't create the static file response");
+        }
+
+        let builder = builder
+            .header(header::CONTENT_TYPE, content_type_of(&file_path))
+            .header(header::CONTENT_LENGTH, metadata.len());
+        if req.method() == Method::HEAD {
+            return builder
+                .body(BoxBody::default())
+                .expect("Couldn't create the static file response");
+        }
+
+        match tokio::fs::File::open(&file_path).await {
+            Ok(file) => builder
+                .body(BoxBody::new(body::from_reader(file)))
+                .expect("Couldn't create the static file response"),
+            Err(_) => rejection(StatusCode::NOT_FOUND),
+        }
+    }
+}
+
+// Joins the decoded request path to the directory, or returns `None` if a segment could escape it. The `.` and the
+// empty segments are skipped.
+fn resolve(dir: &Path, req_path: &str) -> Option<PathBuf> {
+    let mut file_path = dir.to_path_buf();
+    for segment in req_path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment if segment.contains(['\\', ':', '\0']) => return None,
+            segment => file_path.push(segment),
+        }
+    }
+    Some(file_path)
+}
+
+fn content_type_of(file_path: &Path) -> &'static str {
+    let ext = file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    CONTENT_TYPES
+        .iter()
+        .find(|(known_ext, _)| *known_ext == ext)
+        .map(|(_, content_type)| *content_type)
+        .unwrap_or("application/octet-stream")
+}
+
+// Creates the entity tag from the file size and the last modification time, so that it changes with the content
+// without reading the file.
+fn etag(len: u64, last_modified: Option<SystemTime>) -> String {
+    let modified = last_modified
+        .and_then(|last_modified| last_modified.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    format!("\"{:x}-{:x}\"", modified, len)
+}
+
+// Evaluates the `If-None-Match` header value against the entity tag using the weak comparison.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|val| val.trim())
+        .any(|val| val == "*" || val.strip_prefix("W/").unwrap_or(val) == etag)
+}
+
+fn rejection(status: StatusCode) -> Response<BoxBody> {
+    Response::builder()
+        .status(status)
+        .body(BoxBody::default())
+        .expect("Couldn't create the static file rejection response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let dir = Path::new("/srv/public");
+        assert_eq!(
+            resolve(dir, "css/main.css"),
+            Some(PathBuf::from("/srv/public/css/main.css"))
+        );
+        assert_eq!(
+            resolve(dir, "./css//main.css"),
+            Some(PathBuf::from("/srv/public/css/main.css"))
+        );
+        assert_eq!(resolve(dir, ""), Some(PathBuf::from("/srv/public")));
+        assert_eq!(resolve(dir, "../secret"), None);
+        assert_eq!(resolve(dir, "css/../../secret"), None);
+        assert_eq!(resolve(dir, "..\\secret"), None);
+        assert_eq!(resolve(dir, "C:secret"), None);
+    }
+
+    #[test]
+    fn test_content_type_of() {
+        assert_eq!(content_type_of(Path::new("index.html")), "text/html; charset=utf-8");
+        assert_eq!(content_type_of(Path::new("logo.PNG")), "image/png");
+        assert_eq!(content_type_of(Path::new("archive.tar")), "application/octet-stream");
+        assert_eq!(content_type_of(Path::new("LICENSE")), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = etag(42, Some(UNIX_EPOCH + std::time::Duration::from_secs(255)));
+        assert_eq!(etag, "\"ff-2a\"");
+        assert!(etag_matches("\"ff-2a\"", &etag));
+        assert!(etag_matches("\"abc\", W/\"ff-2a\"", &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"ff-2b\"", &etag));
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+/// Represents the timings and the counts collected while building a [Router](./struct.Router.html) via the
+/// [`RouterBuilder`](./struct.RouterBuilder.html) method [`build_timed`](./struct.RouterBuilder.html#method.build_timed).
+#[derive(Debug, Clone)]
+pub struct BuildReport {
+    pub(crate) regex_compilation: Duration,
+    pub(crate) validation: Duration,
+    pub(crate) regex_set_construction: Duration,
+    pub(crate) route_count: usize,
+    pub(crate) pre_middleware_count: usize,
+    pub(crate) post_middleware_count: usize,
+    pub(crate) around_middleware_count: usize,
+}
+
+impl BuildReport {
+    /// Returns the time spent compiling the regexes of the routes registered on the builder, including the ones
+    /// remounted from the scoped routers.
+    pub fn regex_compilation(&self) -> Duration {
+        self.regex_compilation
+    }
+
+    /// Returns the time spent validating the configuration and assembling the router.
+    pub fn validation(&self) -> Duration {
+        self.validation
+    }
+
+    /// Returns the time spent preparing the router for serving, mostly constructing the `RegexSet` used to match the
+    /// incoming requests, including the default `OPTIONS` and 404 routes.
+    pub fn regex_set_construction(&self) -> Duration {
+        self.regex_set_construction
+    }
+
+    /// Returns the sum of all the measured durations.
+    pub fn total(&self) -> Duration {
+        self.regex_compilation + self.validation + self.regex_set_construction
+    }
+
+    /// Returns the number of routes registered on the builder, not counting the default `OPTIONS` and 404 routes.
+    pub fn route_count(&self) -> usize {
+        self.route_count
+    }
+
+    /// Returns the number of pre middlewares in the router.
+    pub fn pre_middleware_count(&self) -> usize {
+        self.pre_middleware_count
+    }
+
+    /// Returns the number of post middlewares in the router.
+    pub fn post_middleware_count(&self) -> usize {
+        self.post_middleware_count
+    }
+
+    /// Returns the number of around middlewares in the router.
+    pub fn around_middleware_count(&self) -> usize {
+        self.around_middleware_count
+    }
+}
@@ -0,0 +1,122 @@
+use x509_parser::certificate::X509Certificate;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::FromDer;
+
+/// Represents the certificate a client authenticated with over a mutual TLS connection, i.e. the leaf of the peer
+/// certificate chain along with the rest of the chain.
+///
+/// It's created from the chain reported by the TLS acceptor, e.g. `ServerConnection::peer_certificates` of rustls,
+/// and attached to the connection's request service via
+/// [`RequestService::with_client_cert`](./struct.RequestService.html#method.with_client_cert), so that the handlers can
+/// access it as `req.client_cert()`. The chain is expected to be verified by the TLS acceptor already.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response, StatusCode};
+/// use routerify_ng::ext::RequestExt;
+/// use routerify_ng::Router;
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/whoami", |req| async move {
+///             let Some(cert) = req.client_cert() else {
+///                 return Ok(Response::builder().status(StatusCode::UNAUTHORIZED).body(Full::default()).unwrap());
+///             };
+///
+///             Ok(Response::new(Full::new(Bytes::from(cert.subject().to_owned()))))
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCert {
+    chain: Vec<Vec<u8>>,
+    subject: String,
+    subject_alt_names: Vec<String>,
+}
+
+impl ClientCert {
+    /// Creates a client certificate from the DER encoded peer certificate chain, starting with the client's own
+    /// certificate.
+    ///
+    /// It fails if the chain is empty or the client's certificate can't be parsed.
+    pub fn from_der_chain<I, C>(chain: I) -> crate::Result<ClientCert>
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Vec<u8>>,
+    {
+        let chain = chain.into_iter().map(Into::into).collect::<Vec<Vec<u8>>>();
+        let leaf = chain
+            .first()
+            .ok_or_else(|| crate::Error::new("The client certificate chain is empty"))?;
+
+        let (_, cert) = X509Certificate::from_der(leaf)
+            .map_err(|e| crate::Error::new(format!("Couldn't parse the client certificate: {}", e)))?;
+
+        let subject = cert.subject().to_string();
+        let subject_alt_names = match cert.subject_alternative_name() {
+            Ok(Some(ext)) => ext
+                .value
+                .general_names
+                .iter()
+                .filter_map(general_name_to_string)
+                .collect(),
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                return Err(crate::Error::new(format!(
+                    "Couldn't parse the client certificate alternative names: {}",
+                    e
+                ))
+                .into());
+            }
+        };
+
+        Ok(ClientCert {
+            chain,
+            subject,
+            subject_alt_names,
+        })
+    }
+
+    /// Returns the subject distinguished name of the client's certificate, e.g. `CN=billing, O=Example`.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the DNS names, the URIs, the email addresses and the IP addresses listed in the subject alternative name
+    /// extension of the client's certificate, in their order.
+    pub fn subject_alt_names(&self) -> &[String] {
+        &self.subject_alt_names
+    }
+
+    /// Returns the DER encoded client's certificate.
+    pub fn der(&self) -> &[u8] {
+        &self.chain[0]
+    }
+
+    /// Returns the DER encoded peer certificate chain, starting with the client's certificate.
+    pub fn chain(&self) -> &[Vec<u8>] {
+        &self.chain
+    }
+}
+
+fn general_name_to_string(name: &GeneralName) -> Option<String> {
+    match name {
+        GeneralName::DNSName(name) | GeneralName::URI(name) | GeneralName::RFC822Name(name) => Some((*name).to_owned()),
+        GeneralName::IPAddress(bytes) => match bytes.len() {
+            4 => <[u8; 4]>::try_from(*bytes)
+                .ok()
+                .map(|ip| std::net::Ipv4Addr::from(ip).to_string()),
+            16 => <[u8; 16]>::try_from(*bytes)
+                .ok()
+                .map(|ip| std::net::Ipv6Addr::from(ip).to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
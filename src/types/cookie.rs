@@ -0,0 +1,181 @@
+use percent_encoding::percent_decode_str;
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+/// Represents a cookie sent by the client via the `Cookie` header, as returned by
+/// [`RequestExt::cookie`](./ext/trait.RequestExt.html#tymethod.cookie).
+///
+/// The value is percent-decoded, with the invalid UTF-8 sequences replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+}
+
+impl Cookie {
+    /// Creates a cookie with the specified name and the decoded value.
+    pub fn new<N: Into<String>, V: Into<String>>(name: N, value: V) -> Cookie {
+        Cookie {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Returns the name of the cookie.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the decoded value of the cookie.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// Represents all the cookies sent by the client via the `Cookie` headers, in their order, as returned by
+/// [`RequestExt::cookies`](./ext/trait.RequestExt.html#tymethod.cookies).
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::ext::RequestExt;
+/// use routerify_ng::Router;
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .get("/preferences", |req| async move {
+///             let cookies = req.cookies();
+///             let theme = cookies.get("theme").map(|c| c.value()).unwrap_or("light");
+///
+///             Ok(Response::new(Full::new(Bytes::from(format!(
+///                 "Theme: {}, {} cookies",
+///                 theme,
+///                 cookies.len()
+///             )))))
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// Parses the values of the `Cookie` headers, i.e. the `name=value` pairs separated by `;`. The malformed pairs,
+    /// e.g. the ones without `=`, are skipped.
+    pub fn parse<'a, I: IntoIterator<Item = &'a str>>(headers: I) -> CookieJar {
+        let cookies = headers
+            .into_iter()
+            .flat_map(|header| header.split(';'))
+            .filter_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                let name = name.trim();
+                if name.is_empty() {
+                    return None;
+                }
+
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                Some(Cookie::new(name, percent_decode_str(value).decode_utf8_lossy()))
+            })
+            .collect();
+
+        CookieJar { cookies }
+    }
+
+    /// Returns the first cookie with the specified name.
+    pub fn get(&self, name: &str) -> Option<&Cookie> {
+        self.cookies.iter().find(|cookie| cookie.name == name)
+    }
+
+    /// Returns all the cookies with the specified name, e.g. the ones set for the different paths, in their order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Cookie> + 'a {
+        self.cookies.iter().filter(move |cookie| cookie.name == name)
+    }
+
+    /// Returns an iterator over the cookies, in their order.
+    pub fn iter(&self) -> slice::Iter<'_, Cookie> {
+        self.cookies.iter()
+    }
+
+    /// Returns the number of the cookies.
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Returns `true` if the client sent no cookies.
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a CookieJar {
+    type Item = &'a Cookie;
+    type IntoIter = slice::Iter<'a, Cookie>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// The `Cookie` headers joined by `; ` along with the cookies parsed from them.
+type ParsedCookies = (String, Arc<CookieJar>);
+
+// The cookies parsed by `RequestExt::cookie` and `RequestExt::cookies`, which the request service inserts empty
+// so that the `Cookie` headers are parsed once on the first call. They're parsed again if the headers were
+// rewritten since, e.g. by a pre middleware.
+#[derive(Clone, Default)]
+pub(crate) struct CookieCache {
+    parsed: Arc<Mutex<Option<ParsedCookies>>>,
+}
+
+impl CookieCache {
+    pub(crate) fn get_or_parse(&self, headers: &[&str]) -> Arc<CookieJar> {
+        let raw = headers.join("; ");
+        let mut parsed = self.parsed.lock().unwrap();
+        match *parsed {
+            Some((ref parsed_raw, ref jar)) if *parsed_raw == raw => jar.clone(),
+            _ => {
+                let jar = Arc::new(CookieJar::parse([raw.as_str()]));
+                *parsed = Some((raw, jar.clone()));
+                jar
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cookie_headers() {
+        let jar = CookieJar::parse(["session=abc%20123; theme=\"dark\"", "invalid; =empty; session=other"]);
+
+        let cookies = jar.iter().map(|c| (c.name(), c.value())).collect::<Vec<_>>();
+        assert_eq!(
+            vec![("session", "abc 123"), ("theme", "dark"), ("session", "other")],
+            cookies
+        );
+        assert_eq!(Some("abc 123"), jar.get("session").map(Cookie::value));
+        assert_eq!(2, jar.get_all("session").count());
+        assert!(jar.get("invalid").is_none());
+    }
+
+    #[test]
+    fn reparses_rewritten_headers() {
+        let cache = CookieCache::default();
+        let first = cache.get_or_parse(&["a=1"]);
+        assert!(Arc::ptr_eq(&first, &cache.get_or_parse(&["a=1"])));
+        assert_eq!(Some("2"), cache.get_or_parse(&["a=2"]).get("a").map(Cookie::value));
+    }
+}
@@ -0,0 +1,26 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+type DeferredTask = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+// The tasks registered via `RequestExt::spawn_after_response`, which the request service spawns
+// once the response is produced. They are dropped without running if the request service future
+// is dropped before, e.g. when the client disconnects mid-request.
+#[derive(Clone, Default)]
+pub(crate) struct DeferredTasks {
+    tasks: Arc<Mutex<Vec<DeferredTask>>>,
+}
+
+impl DeferredTasks {
+    pub(crate) fn push<F: Future<Output = ()> + Send + 'static>(&self, task: F) {
+        self.tasks.lock().unwrap().push(Box::pin(task));
+    }
+
+    // Spawns the registered tasks detached, in the registration order.
+    pub(crate) fn spawn_all(&self) {
+        for task in self.tasks.lock().unwrap().drain(..) {
+            tokio::spawn(task);
+        }
+    }
+}
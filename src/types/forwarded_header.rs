@@ -0,0 +1,14 @@
+/// Tells which header the trusted proxies maintain to report the client address, see
+/// [`TrustConfig::header`](./struct.TrustConfig.html#method.header).
+///
+/// Only this header is read, so that a client can't spoof its address via the other one, which the proxies pass
+/// through untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForwardedHeader {
+    /// The de facto standard `X-Forwarded-For` header, e.g. `X-Forwarded-For: 203.0.113.7, 10.0.0.1`, which nginx and
+    /// most load balancers append to.
+    #[default]
+    XForwardedFor,
+    /// The standard `Forwarded` header, e.g. `Forwarded: for=203.0.113.7, for=10.0.0.1`.
+    Forwarded,
+}
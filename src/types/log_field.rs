@@ -0,0 +1,58 @@
+use crate::types::RequestInfo;
+use hyper::Response;
+use hyper::header;
+
+/// A field of the records emitted by the [`structured_log`](./struct.Middleware.html#method.structured_log) middleware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogField {
+    /// The request method, e.g. `GET`, under the `method` key.
+    Method,
+    /// The request path, without the query, under the `path` key.
+    Path,
+    /// The path template of the route which handled the request, e.g. `/users/:id`, under the `matched_route` key.
+    /// It's omitted if no route matched.
+    MatchedRoute,
+    /// The response status code, e.g. `200`, under the `status` key.
+    Status,
+    /// The time the request took up to the logging, in milliseconds with a microsecond precision, under the
+    /// `duration_ms` key.
+    Duration,
+    /// The client address, under the `remote_addr` key.
+    RemoteAddr,
+    /// The `X-Request-Id` header value, under the `request_id` key. It's omitted if the header is missing.
+    RequestId,
+    /// The `User-Agent` header value, under the `user_agent` key. It's omitted if the header is missing.
+    UserAgent,
+}
+
+impl LogField {
+    /// Returns the key the field is emitted under.
+    pub fn key(&self) -> &'static str {
+        match self {
+            LogField::Method => "method",
+            LogField::Path => "path",
+            LogField::MatchedRoute => "matched_route",
+            LogField::Status => "status",
+            LogField::Duration => "duration_ms",
+            LogField::RemoteAddr => "remote_addr",
+            LogField::RequestId => "request_id",
+            LogField::UserAgent => "user_agent",
+        }
+    }
+
+    pub(crate) fn value<B>(&self, res: &Response<B>, req_info: &RequestInfo) -> Option<String> {
+        match self {
+            LogField::Method => Some(req_info.method().to_string()),
+            LogField::Path => Some(req_info.uri().path().to_owned()),
+            LogField::MatchedRoute => req_info.matched_path().map(str::to_owned),
+            LogField::Status => Some(res.status().as_u16().to_string()),
+            LogField::Duration => Some(format!(
+                "{:.3}",
+                req_info.received_at().elapsed().as_secs_f64() * 1000.0
+            )),
+            LogField::RemoteAddr => req_info.remote_addr().map(|addr| addr.to_string()),
+            LogField::RequestId => req_info.header("x-request-id").map(str::to_owned),
+            LogField::UserAgent => req_info.header(header::USER_AGENT).map(str::to_owned),
+        }
+    }
+}
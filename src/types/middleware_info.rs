@@ -0,0 +1,34 @@
+/// Represents the metadata of a middleware registered in a [Router](./struct.Router.html), as returned by the `Router`
+/// methods [`pre_middleware`](./struct.Router.html#method.pre_middleware),
+/// [`post_middleware`](./struct.Router.html#method.post_middleware) and
+/// [`around_middleware`](./struct.Router.html#method.around_middleware).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MiddlewareInfo {
+    pub(crate) path: String,
+    pub(crate) priority: i32,
+    pub(crate) name: Option<String>,
+    pub(crate) label: Option<String>,
+}
+
+impl MiddlewareInfo {
+    /// Returns the path the middleware is applied at, including the prefixes of the scopes it was mounted at.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the priority of the middleware.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Returns the name set via [`Middleware::named`](./enum.Middleware.html#method.named), if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the label set via the [`RouterBuilder`](./struct.RouterBuilder.html) method
+    /// [`middleware_labeled`](./struct.RouterBuilder.html#method.middleware_labeled), if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
@@ -1,9 +1,45 @@
-pub(crate) use request_context::RequestContext;
-pub use request_info::RequestInfo;
+pub use build_report::BuildReport;
+#[cfg(feature = "client-cert")]
+pub use client_cert::ClientCert;
+pub(crate) use cookie::CookieCache;
+pub use cookie::{Cookie, CookieJar};
+pub(crate) use deferred_tasks::DeferredTasks;
+pub use forwarded_header::ForwardedHeader;
+pub use log_field::LogField;
+pub use middleware_info::MiddlewareInfo;
+pub(crate) use query_cache::{QueryCache, parse_queries};
+pub use remote_addr::RemoteAddr;
+pub(crate) use remote_addr::UNSPECIFIED_SOCKET_ADDR;
+pub(crate) use request_body::{BufferedBody, ReadError, RequestBody, collect_body};
+pub(crate) use request_cancellation::{CancellationGuard, RequestCancellation};
+pub use request_context::RequestContext;
+pub use request_info::{RequestInfo, RequestInfoBuilder};
 pub(crate) use request_meta::RequestMeta;
+pub use response_source::ResponseSource;
+pub use route_info::RouteInfo;
 pub use route_params::RouteParams;
+pub use trace_context::TraceContext;
+pub use trailing_slash_policy::TrailingSlashPolicy;
+pub use trust_config::TrustConfig;
 
+mod build_report;
+#[cfg(feature = "client-cert")]
+mod client_cert;
+mod cookie;
+mod deferred_tasks;
+mod forwarded_header;
+mod log_field;
+mod middleware_info;
+mod query_cache;
+mod remote_addr;
+mod request_body;
+mod request_cancellation;
 mod request_context;
 mod request_info;
 mod request_meta;
+mod response_source;
+mod route_info;
 mod route_params;
+mod trace_context;
+mod trailing_slash_policy;
+mod trust_config;
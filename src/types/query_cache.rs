@@ -0,0 +1,56 @@
+use crate::helpers;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+type Queries = Arc<HashMap<String, Vec<String>>>;
+
+// The query parameters parsed by `RequestExt::query` and `RequestExt::queries`, which the request service
+// inserts empty so that the query string is parsed once on the first call. It's parsed again if the query
+// string was rewritten since, e.g. by a pre middleware.
+#[derive(Clone, Default)]
+pub(crate) struct QueryCache {
+    parsed: Arc<Mutex<Option<(String, Queries)>>>,
+}
+
+impl QueryCache {
+    pub(crate) fn get_or_parse(&self, query: &str) -> Queries {
+        let mut parsed = self.parsed.lock().unwrap();
+        match *parsed {
+            Some((ref raw, ref queries)) if raw == query => queries.clone(),
+            _ => {
+                let queries = Arc::new(parse_queries(query));
+                *parsed = Some((query.to_owned(), queries.clone()));
+                queries
+            }
+        }
+    }
+}
+
+// Groups the values of the repeated query parameters in their order.
+pub(crate) fn parse_queries(query: &str) -> HashMap<String, Vec<String>> {
+    let mut queries = HashMap::<String, Vec<String>>::new();
+    for (name, val) in helpers::query_pairs(query) {
+        queries.entry(name).or_default().push(val);
+    }
+    queries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_repeated_keys() {
+        let queries = parse_queries("a=1&b=x%20y&a=2");
+        assert_eq!(Some(&vec!["1".to_owned(), "2".to_owned()]), queries.get("a"));
+        assert_eq!(Some(&vec!["x y".to_owned()]), queries.get("b"));
+    }
+
+    #[test]
+    fn reparses_rewritten_query() {
+        let cache = QueryCache::default();
+        let first = cache.get_or_parse("a=1");
+        assert!(Arc::ptr_eq(&first, &cache.get_or_parse("a=1")));
+        assert_eq!(Some(&vec!["2".to_owned()]), cache.get_or_parse("a=2").get("a"));
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt::{self, Display, Formatter};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::path::PathBuf;
+
+// The address reported by `RequestExt::remote_addr` for the clients which aren't connected over TCP.
+pub(crate) static UNSPECIFIED_SOCKET_ADDR: SocketAddr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+
+/// The address of the client a request came from, see
+/// [`RequestExt::peer_addr`](./ext/trait.RequestExt.html#tymethod.peer_addr).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RemoteAddr {
+    /// The client is connected over TCP.
+    Tcp(SocketAddr),
+    /// The client is connected over a Unix domain socket, which is bound to the path unless the socket is unnamed,
+    /// e.g. the client didn't bind it.
+    Unix(Option<PathBuf>),
+}
+
+impl RemoteAddr {
+    /// Returns the TCP address of the client, or `None` if it's connected over a Unix domain socket.
+    pub fn tcp(&self) -> Option<SocketAddr> {
+        match self {
+            RemoteAddr::Tcp(addr) => Some(*addr),
+            RemoteAddr::Unix(_) => None,
+        }
+    }
+
+    // Returns the TCP address of the client, or the unspecified `0.0.0.0:0` one for a Unix domain socket.
+    pub(crate) fn socket_addr(&self) -> &SocketAddr {
+        match self {
+            RemoteAddr::Tcp(addr) => addr,
+            RemoteAddr::Unix(_) => &UNSPECIFIED_SOCKET_ADDR,
+        }
+    }
+}
+
+impl From<SocketAddr> for RemoteAddr {
+    fn from(addr: SocketAddr) -> Self {
+        RemoteAddr::Tcp(addr)
+    }
+}
+
+impl Display for RemoteAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteAddr::Tcp(addr) => write!(f, "{}", addr),
+            RemoteAddr::Unix(Some(path)) => write!(f, "unix:{}", path.display()),
+            RemoteAddr::Unix(None) => write!(f, "unix:(unnamed)"),
+        }
+    }
+}
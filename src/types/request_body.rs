@@ -0,0 +1,150 @@
+#[cfg(feature = "decompression")]
+use crate::decompression::Decompression;
+use bytes::BytesMut;
+use http_body_util::BodyExt;
+use hyper::body::{Bytes, Incoming};
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// The request body as seen by `RequestExt::body_bytes`. It's either buffered up front by the
+// request service or read from the connection on the first access and cached.
+#[derive(Clone)]
+pub(crate) struct RequestBody {
+    // The lock is held while the body is read, so that the concurrent accesses wait
+    // for the first one instead of racing it.
+    inner: Arc<Mutex<BodyState>>,
+}
+
+enum BodyState {
+    Unread {
+        body: Incoming,
+        limit: Option<usize>,
+        // The decoding applied once the body is read, see `RouterBuilder::decompress_requests`.
+        #[cfg(feature = "decompression")]
+        decompression: Option<Decompression>,
+    },
+    Read(Bytes),
+    Failed(ReadError),
+}
+
+// The failure of reading the request body, kept so that the later accesses fail the same way.
+#[derive(Debug, Clone)]
+pub(crate) enum ReadError {
+    // The body exceeds the limit set via `RouterBuilder::body_limit`, or its decoding exceeds the one set via
+    // `RouterBuilder::decompress_requests`.
+    TooLarge(usize),
+    Failed(String),
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::TooLarge(limit) => write!(f, "The request body exceeds the limit of {} bytes", limit),
+            ReadError::Failed(msg) => write!(f, "Couldn't read the request body: {}", msg),
+        }
+    }
+}
+
+impl RequestBody {
+    // The body is read up to the `limit`, if any.
+    pub(crate) fn unread(body: Incoming, limit: Option<usize>) -> Self {
+        Self::with_state(BodyState::Unread {
+            body,
+            limit,
+            #[cfg(feature = "decompression")]
+            decompression: None,
+        })
+    }
+
+    // The body is read up to the `limit`, if any, and then decoded.
+    #[cfg(feature = "decompression")]
+    pub(crate) fn unread_decompressed(body: Incoming, limit: Option<usize>, decompression: Decompression) -> Self {
+        Self::with_state(BodyState::Unread {
+            body,
+            limit,
+            decompression: Some(decompression),
+        })
+    }
+
+    pub(crate) fn read(bytes: Bytes) -> Self {
+        Self::with_state(BodyState::Read(bytes))
+    }
+
+    fn with_state(state: BodyState) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    pub(crate) async fn bytes(&self) -> Result<Bytes, ReadError> {
+        let mut state = self.inner.lock().await;
+
+        match std::mem::replace(&mut *state, BodyState::Read(Bytes::new())) {
+            BodyState::Read(bytes) => {
+                *state = BodyState::Read(bytes.clone());
+                Ok(bytes)
+            }
+            BodyState::Failed(err) => {
+                *state = BodyState::Failed(err.clone());
+                Err(err)
+            }
+            BodyState::Unread {
+                body,
+                limit,
+                #[cfg(feature = "decompression")]
+                decompression,
+            } => {
+                let res = collect_body(body, limit).await;
+                #[cfg(feature = "decompression")]
+                let res = res.and_then(|bytes| match decompression {
+                    Some(decompression) => decompress(&decompression, &bytes),
+                    None => Ok(bytes),
+                });
+
+                match res {
+                    Ok(bytes) => {
+                        *state = BodyState::Read(bytes.clone());
+                        Ok(bytes)
+                    }
+                    Err(err) => {
+                        *state = BodyState::Failed(err.clone());
+                        Err(err)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "decompression")]
+fn decompress(decompression: &Decompression, body: &Bytes) -> Result<Bytes, ReadError> {
+    decompression.decode(body).map_err(|err| match err.kind() {
+        std::io::ErrorKind::FileTooLarge => ReadError::TooLarge(decompression.max_size()),
+        _ => ReadError::Failed(format!("Invalid content coding: {}", err)),
+    })
+}
+
+// The buffered request body as seen by `RequestExt::peek_body`. It's kept apart from `RequestBody`,
+// so that it can be borrowed without locking.
+#[derive(Clone)]
+pub(crate) struct BufferedBody(pub(crate) Bytes);
+
+// Reads the body, failing as soon as it exceeds the `limit`, if any.
+pub(crate) async fn collect_body(mut body: Incoming, limit: Option<usize>) -> Result<Bytes, ReadError> {
+    let mut buf = BytesMut::new();
+
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|e| ReadError::Failed(e.to_string()))?;
+        if let Some(data) = frame.data_ref() {
+            if let Some(limit) = limit
+                && buf.len() + data.len() > limit
+            {
+                return Err(ReadError::TooLarge(limit));
+            }
+            buf.extend_from_slice(data);
+        }
+    }
+
+    Ok(buf.freeze())
+}
@@ -0,0 +1,45 @@
+use std::future::{Future, pending};
+use tokio::sync::watch;
+
+// It resolves the `RequestExt::cancelled` futures of a request once the request service future is
+// dropped before completion, which is what hyper does when the client disconnects mid-request.
+pub(crate) struct CancellationGuard {
+    tx: Option<watch::Sender<bool>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct RequestCancellation {
+    rx: watch::Receiver<bool>,
+}
+
+impl CancellationGuard {
+    pub(crate) fn new() -> (Self, RequestCancellation) {
+        let (tx, rx) = watch::channel(false);
+        (Self { tx: Some(tx) }, RequestCancellation { rx })
+    }
+
+    // Marks the request as completed, so that the pending cancellation futures never resolve.
+    pub(crate) fn complete(mut self) {
+        self.tx.take();
+    }
+}
+
+impl Drop for CancellationGuard {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(true);
+        }
+    }
+}
+
+impl RequestCancellation {
+    pub(crate) fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut rx = self.rx.clone();
+
+        async move {
+            if rx.wait_for(|cancelled| *cancelled).await.is_err() {
+                pending::<()>().await;
+            }
+        }
+    }
+}
@@ -1,8 +1,15 @@
 use crate::data_map::DataMap;
 use std::sync::{Arc, Mutex};
 
+/// The per-request context holding one value per type, shared by the middlewares, the route handler and the error
+/// handler of a request.
+///
+/// The typed accessors [`RequestExt::context`](./ext/trait.RequestExt.html#tymethod.context) and
+/// [`RequestExt::set_context`](./ext/trait.RequestExt.html#tymethod.set_context) are enough for most uses. The context
+/// itself can be accessed via [`RequestExt::request_context`](./ext/trait.RequestExt.html#tymethod.request_context)
+/// to remove the values or to clear it.
 #[derive(Clone)]
-pub(crate) struct RequestContext {
+pub struct RequestContext {
     // Strictly speaking, there should be no need to protect
     // the datamap because the context is per request,
     // thus no concurrent access.
@@ -20,11 +27,33 @@ impl RequestContext {
         }
     }
 
-    pub(crate) fn set<T: Send + Sync + Clone + 'static>(&self, val: T) {
+    /// Puts a value into the context, replacing the existing value of the same type.
+    pub fn set<T: Send + Sync + Clone + 'static>(&self, val: T) {
         self.inner.lock().unwrap().insert(val);
     }
 
-    pub(crate) fn get<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
+    /// Returns a copy of the value of the specified type.
+    pub fn get<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
         self.inner.lock().unwrap().get::<T>().cloned()
     }
+
+    /// Removes the value of the specified type from the context and returns it.
+    pub fn remove<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
+        self.inner.lock().unwrap().remove::<T>()
+    }
+
+    /// Removes all the values from the context.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().clear();
+    }
+
+    /// Returns the number of values in the context.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+
+    /// Returns `true` if the context holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
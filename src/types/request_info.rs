@@ -1,14 +1,22 @@
-use super::RequestContext;
-use crate::data_map::SharedDataMap;
+use super::{RequestContext, RequestMeta};
+use crate::data_map::{DataMap, SharedDataMap};
+use crate::helpers;
+use crate::metrics::MatchedRoute;
 use hyper::body::Body;
+use hyper::header::{AsHeaderName, HeaderName, HeaderValue};
 use hyper::{HeaderMap, Method, Request, Uri, Version};
 use std::fmt::{self, Debug, Formatter};
+use std::net::SocketAddr;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Represents some information for the incoming request.
 ///
 /// It's used to access request information e.g. headers, method, uri etc for the [Post Middleware](./index.html#post-middleware-with-request-info) and
 /// for the [error handling](./index.html#error-handling-with-request-info);
+///
+/// The headers, method and uri are stored behind an `Arc`, so cloning it doesn't copy them.
 #[derive(Clone)]
 pub struct RequestInfo {
     pub(crate) req_info_inner: Arc<RequestInfoInner>,
@@ -22,6 +30,10 @@ pub(crate) struct RequestInfoInner {
     method: Method,
     uri: Uri,
     version: Version,
+    remote_addr: Option<SocketAddr>,
+    // When the request was received, to measure how long it's been processed.
+    received_at: Instant,
+    matched_route: MatchedRoute,
 }
 
 impl RequestInfo {
@@ -34,6 +46,12 @@ impl RequestInfo {
             method: req.method().clone(),
             uri: req.uri().clone(),
             version: req.version(),
+            remote_addr: req
+                .extensions()
+                .get::<RequestMeta>()
+                .and_then(|meta| meta.remote_addr().copied()),
+            received_at: Instant::now(),
+            matched_route: req.extensions().get::<MatchedRoute>().cloned().unwrap_or_default(),
         };
 
         RequestInfo {
@@ -43,11 +61,54 @@ impl RequestInfo {
         }
     }
 
+    /// Creates a builder to construct a `RequestInfo` directly, e.g. to unit test a
+    /// [`post_with_info`](./struct.Middleware.html#method.post_with_info) middleware handler without a live request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Method, Response};
+    /// use routerify_ng::RequestInfo;
+    /// use std::convert::Infallible;
+    ///
+    /// async fn add_method_header(mut res: Response<Full<Bytes>>, req_info: RequestInfo) -> Result<Response<Full<Bytes>>, Infallible> {
+    ///     res.headers_mut().insert("x-method", req_info.method().as_str().parse().unwrap());
+    ///     Ok(res)
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let req_info = RequestInfo::builder().method(Method::POST).uri("/users".parse().unwrap()).build();
+    ///
+    /// let res = add_method_header(Response::new(Full::new(Bytes::new())), req_info).await.unwrap();
+    /// assert_eq!(res.headers()["x-method"], "POST");
+    /// # }
+    /// ```
+    pub fn builder() -> RequestInfoBuilder {
+        RequestInfoBuilder::default()
+    }
+
     /// Returns the request headers.
     pub fn headers(&self) -> &HeaderMap {
         &self.req_info_inner.headers
     }
 
+    /// Returns the value of the specified header as a string, or `None` if it's missing or not visible ASCII.
+    pub fn header<K: AsHeaderName>(&self, name: K) -> Option<&str> {
+        helpers::header(self.headers(), name)
+    }
+
+    /// Returns the value of the specified header parsed into `T`, or `None` if it's missing or couldn't be parsed.
+    pub fn header_typed<T: FromStr, K: AsHeaderName>(&self, name: K) -> Option<T> {
+        helpers::header_typed(self.headers(), name)
+    }
+
+    /// Returns all the values of a multi-valued header as strings, skipping the ones which aren't visible ASCII.
+    pub fn header_all<K: AsHeaderName>(&self, name: K) -> Vec<&str> {
+        helpers::header_all(self.headers(), name)
+    }
+
     /// Returns the request method type.
     pub fn method(&self) -> &Method {
         &self.req_info_inner.method
@@ -117,6 +178,156 @@ impl RequestInfo {
     pub fn context<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
         self.context.get::<T>()
     }
+
+    /// Access the request context itself, e.g. to remove a value from it.
+    pub fn request_context(&self) -> &RequestContext {
+        &self.context
+    }
+
+    pub(crate) fn remote_addr(&self) -> Option<SocketAddr> {
+        self.req_info_inner.remote_addr
+    }
+
+    pub(crate) fn received_at(&self) -> Instant {
+        self.req_info_inner.received_at
+    }
+
+    /// Returns the path template of the route which handled the request, e.g. `/users/:id` for `/users/42`, or `None`
+    /// if it hasn't been handled by a route yet, e.g. in the error handler of a failed pre middleware. The requests not
+    /// matched by any other route are reported with the catch-all `/*` route.
+    ///
+    /// It's meant for the post middlewares, see [`RequestExt::matched_path`](./ext/trait.RequestExt.html#tymethod.matched_path).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_body_util::Full;
+    /// use hyper::{body::Bytes, Response};
+    /// use routerify_ng::{Middleware, RequestInfo, Router};
+    /// use std::convert::Infallible;
+    ///
+    /// async fn add_route_header(mut res: Response<Full<Bytes>>, req_info: RequestInfo) -> Result<Response<Full<Bytes>>, Infallible> {
+    ///     if let Some(route) = req_info.matched_path() {
+    ///         res.headers_mut().insert("x-route", route.parse().unwrap());
+    ///     }
+    ///     Ok(res)
+    /// }
+    ///
+    /// fn run() -> Router<Infallible> {
+    ///     let router = Router::builder()
+    ///         .get("/users/:id", |_| async move { Ok(Response::new(Full::new(Bytes::from("User")))) })
+    ///         .middleware(Middleware::post_with_info(add_route_header))
+    ///         .build()
+    ///         .unwrap();
+    ///     router
+    /// }
+    /// ```
+    pub fn matched_path(&self) -> Option<&str> {
+        self.req_info_inner.matched_route.get()
+    }
+}
+
+/// A builder for the [`RequestInfo`](./struct.RequestInfo.html), see
+/// [`RequestInfo::builder`](./struct.RequestInfo.html#method.builder).
+///
+/// It defaults to a `GET /` request with no headers, data and context.
+pub struct RequestInfoBuilder {
+    headers: HeaderMap,
+    method: Method,
+    uri: Uri,
+    version: Version,
+    data_map: Option<DataMap>,
+    context: RequestContext,
+}
+
+impl Default for RequestInfoBuilder {
+    fn default() -> Self {
+        RequestInfoBuilder {
+            headers: HeaderMap::new(),
+            method: Method::default(),
+            uri: Uri::default(),
+            version: Version::default(),
+            data_map: None,
+            context: RequestContext::new(),
+        }
+    }
+}
+
+impl RequestInfoBuilder {
+    /// Sets the request method.
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sets the request uri.
+    pub fn uri(mut self, uri: Uri) -> Self {
+        self.uri = uri;
+        self
+    }
+
+    /// Sets the request's HTTP version.
+    pub fn version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Appends a request header.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Replaces the request headers.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Adds a data which is accessible via [`RequestInfo::data`](./struct.RequestInfo.html#method.data), like the
+    /// one shared by the [`RouterBuilder`](./struct.RouterBuilder.html) method [`data`](./struct.RouterBuilder.html#method.data).
+    pub fn data<T: Send + Sync + Clone + 'static>(mut self, data: T) -> Self {
+        self.data_map.get_or_insert_with(DataMap::new).insert(data);
+        self
+    }
+
+    /// Sets a value in the request context, which is accessible via
+    /// [`RequestInfo::context`](./struct.RequestInfo.html#method.context).
+    pub fn context<T: Send + Sync + Clone + 'static>(self, val: T) -> Self {
+        self.context.set(val);
+        self
+    }
+
+    /// Creates the `RequestInfo` instance.
+    pub fn build(self) -> RequestInfo {
+        let inner = RequestInfoInner {
+            headers: self.headers,
+            method: self.method,
+            uri: self.uri,
+            version: self.version,
+            remote_addr: None,
+            received_at: Instant::now(),
+            matched_route: MatchedRoute::default(),
+        };
+
+        RequestInfo {
+            req_info_inner: Arc::new(inner),
+            shared_data_maps: self
+                .data_map
+                .map(|data_map| vec![SharedDataMap::new(Arc::new(data_map))]),
+            context: self.context,
+        }
+    }
+}
+
+impl Debug for RequestInfoBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ method: {:?}, uri: {:?}, version: {:?}, headers: {:?} }}",
+            self.method, self.uri, self.version, self.headers
+        )
+    }
 }
 
 impl Debug for RequestInfo {
@@ -124,3 +335,54 @@ impl Debug for RequestInfo {
         write!(f, "{:?}", self.req_info_inner)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+
+    #[test]
+    fn clone_shares_headers_and_uri() {
+        let mut builder = Request::builder().uri("/users/1?page=2");
+        for i in 0..30 {
+            builder = builder.header(format!("x-header-{}", i), "x".repeat(64));
+        }
+        let req = builder.body(Full::new(Bytes::new())).unwrap();
+
+        let info = RequestInfo::new_from_req(&req, RequestContext::new());
+        let cloned = info.clone();
+
+        assert_eq!(30, cloned.headers().len());
+        assert!(std::ptr::eq(info.headers(), cloned.headers()));
+        assert!(std::ptr::eq(info.uri(), cloned.uri()));
+    }
+
+    #[tokio::test]
+    async fn can_test_post_middleware_handler_with_built_info() {
+        use hyper::{Method, Response};
+
+        #[derive(Clone)]
+        struct Tenant(&'static str);
+
+        async fn tag_response(mut res: Response<Full<Bytes>>, req_info: RequestInfo) -> Response<Full<Bytes>> {
+            let tenant = req_info.data::<Tenant>().map(|t| t.0).unwrap_or("none");
+            let user = req_info.context::<String>().unwrap_or_default();
+            let agent = req_info.header("user-agent").unwrap_or("unknown");
+            let tag = format!("{} {} {} {} {}", req_info.method(), req_info.uri(), tenant, user, agent);
+            res.headers_mut().insert("x-tag", tag.parse().unwrap());
+            res
+        }
+
+        let req_info = RequestInfo::builder()
+            .method(Method::DELETE)
+            .uri("/users/1".parse().unwrap())
+            .header(hyper::header::USER_AGENT, HeaderValue::from_static("test-agent"))
+            .data(Tenant("acme"))
+            .context("alice".to_owned())
+            .build();
+
+        let res = tag_response(Response::new(Full::new(Bytes::new())), req_info).await;
+        assert_eq!("DELETE /users/1 acme alice test-agent", res.headers()["x-tag"]);
+    }
+}
@@ -1,10 +1,15 @@
+use crate::types::remote_addr::RemoteAddr;
 use crate::types::route_params::RouteParams;
 use std::net::SocketAddr;
 
 #[derive(Debug, Clone)]
 pub(crate) struct RequestMeta {
     route_params: Option<RouteParams>,
-    remote_addr: Option<SocketAddr>,
+    remote_addr: Option<RemoteAddr>,
+    local_addr: Option<SocketAddr>,
+    matched_route_index: Option<usize>,
+    matched_path: Option<String>,
+    secure: bool,
 }
 
 impl RequestMeta {
@@ -12,13 +17,65 @@ impl RequestMeta {
         RequestMeta {
             route_params: Some(route_params),
             remote_addr: None,
+            local_addr: None,
+            matched_route_index: None,
+            matched_path: None,
+            secure: false,
         }
     }
 
-    pub fn with_remote_addr(remote_addr: SocketAddr) -> RequestMeta {
+    pub fn with_remote_addr(remote_addr: RemoteAddr) -> RequestMeta {
         RequestMeta {
             route_params: None,
             remote_addr: Some(remote_addr),
+            local_addr: None,
+            matched_route_index: None,
+            matched_path: None,
+            secure: false,
+        }
+    }
+
+    pub fn with_matched_route_index(matched_route_index: usize) -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            remote_addr: None,
+            local_addr: None,
+            matched_route_index: Some(matched_route_index),
+            matched_path: None,
+            secure: false,
+        }
+    }
+
+    pub fn with_matched_path(matched_path: String) -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            remote_addr: None,
+            local_addr: None,
+            matched_route_index: None,
+            matched_path: Some(matched_path),
+            secure: false,
+        }
+    }
+
+    pub fn with_local_addr(local_addr: SocketAddr) -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            remote_addr: None,
+            local_addr: Some(local_addr),
+            matched_route_index: None,
+            matched_path: None,
+            secure: false,
+        }
+    }
+
+    pub fn with_secure() -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            remote_addr: None,
+            local_addr: None,
+            matched_route_index: None,
+            matched_path: None,
+            secure: true,
         }
     }
 
@@ -26,15 +83,52 @@ impl RequestMeta {
         self.route_params.as_ref()
     }
 
+    // A client connected over a Unix domain socket has the unspecified `0.0.0.0:0` address.
     pub fn remote_addr(&self) -> Option<&SocketAddr> {
+        self.remote_addr.as_ref().map(RemoteAddr::socket_addr)
+    }
+
+    pub fn peer_addr(&self) -> Option<&RemoteAddr> {
         self.remote_addr.as_ref()
     }
 
+    pub fn local_addr(&self) -> Option<&SocketAddr> {
+        self.local_addr.as_ref()
+    }
+
+    pub fn matched_route_index(&self) -> Option<usize> {
+        self.matched_route_index
+    }
+
+    pub fn matched_path(&self) -> Option<&str> {
+        self.matched_path.as_deref()
+    }
+
+    pub fn is_secure(&self) -> bool {
+        self.secure
+    }
+
     pub fn extend(&mut self, other_req_meta: RequestMeta) {
         if let Some(other_ra) = other_req_meta.remote_addr {
             self.remote_addr = Some(other_ra)
         }
 
+        if let Some(other_la) = other_req_meta.local_addr {
+            self.local_addr = Some(other_la)
+        }
+
+        if other_req_meta.secure {
+            self.secure = true
+        }
+
+        if let Some(other_mri) = other_req_meta.matched_route_index {
+            self.matched_route_index = Some(other_mri)
+        }
+
+        if let Some(other_mp) = other_req_meta.matched_path {
+            self.matched_path = Some(other_mp)
+        }
+
         if let Some(other_pm) = other_req_meta.route_params {
             if let Some(ref mut existing_pm) = self.route_params {
                 existing_pm.extend(other_pm);
@@ -0,0 +1,41 @@
+/// Represents where the final response of a request came from. It's inserted into the response extensions before the
+/// post middlewares run, so that they can categorize the response, e.g. for the access logs.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Response};
+/// use routerify_ng::{Middleware, ResponseSource, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .middleware(Middleware::post(|res: Response<Full<Bytes>>| async move {
+///             if res.extensions().get::<ResponseSource>() == Some(&ResponseSource::ErrorHandler) {
+///                 println!("Request failed with {}", res.status());
+///             }
+///             Ok(res)
+///         }))
+///         .get("/", |_| async move { Ok(Response::new(Full::new(Bytes::from("Home page")))) })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResponseSource {
+    /// The response was returned by a route handler.
+    Route,
+    /// No route matched and the default `404 Not Found` response was sent.
+    Fallback404,
+    /// The request method isn't allowed, e.g. an unhandled `TRACE` request or one the routes matching the path
+    /// aren't registered for.
+    MethodNotAllowed,
+    /// A handler or a middleware failed and the error handler generated the response.
+    ErrorHandler,
+    /// The route handler didn't respond within its timeout, see
+    /// [`handler_timeout`](./struct.RouterBuilder.html#method.handler_timeout).
+    Timeout,
+}
@@ -0,0 +1,28 @@
+use hyper::Method;
+
+/// Represents the metadata of a route registered in a [Router](./struct.Router.html), as returned by the `Router` method
+/// [`routes`](./struct.Router.html#method.routes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    pub(crate) path: String,
+    pub(crate) methods: Vec<Method>,
+    pub(crate) scope_depth: u32,
+}
+
+impl RouteInfo {
+    /// Returns the path pattern of the route as it was declared, including the prefixes of the scopes it was mounted at,
+    /// e.g. `/api/users/:userId`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the methods the route accepts.
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    /// Returns how deep the route is nested, starting at `1` for the routes added to the root router directly.
+    pub fn scope_depth(&self) -> u32 {
+        self.scope_depth
+    }
+}
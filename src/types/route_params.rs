@@ -1,4 +1,13 @@
+#[cfg(feature = "serde")]
+use crate::Error;
+use crate::ParamError;
+#[cfg(feature = "serde")]
+use crate::de::RouteParamsDeserializer;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
 
 /// Represents a map of the route parameters using the name of the parameter specified in the path as their respective keys.
 ///
@@ -59,6 +68,39 @@ impl RouteParams {
         self.0.get(&param_name.into())
     }
 
+    /// Parses the route parameter value mapped with the specified key into the target type.
+    ///
+    /// It returns a [`ParamError`](./enum.ParamError.html) with the parameter name if the parameter is missing or the
+    /// value couldn't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{ParamError, RouteParams};
+    ///
+    /// let mut params = RouteParams::new();
+    /// params.set("userId", "42");
+    /// params.set("page", "first");
+    ///
+    /// assert_eq!(Ok(42), params.get_as::<u64>("userId"));
+    /// assert!(matches!(params.get_as::<u32>("page"), Err(ParamError::Invalid { .. })));
+    /// ```
+    pub fn get_as<T>(&self, param_name: &str) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let val = self.0.get(param_name).ok_or_else(|| ParamError::Missing {
+            name: param_name.to_owned(),
+        })?;
+
+        val.parse::<T>().map_err(|e| ParamError::Invalid {
+            name: param_name.to_owned(),
+            value: val.clone(),
+            reason: e.to_string(),
+        })
+    }
+
     /// Checks if a route parameter exists.
     ///
     /// # Examples
@@ -113,6 +155,38 @@ impl RouteParams {
         self.0.iter()
     }
 
+    /// Deserializes the route parameters into a type implementing [`serde::Deserialize`](https://docs.rs/serde/1/serde/trait.Deserialize.html),
+    /// mapping each parameter to the field with the same name. It's available with the `serde` feature.
+    ///
+    /// Unlike a strict mapping, the target type can pick only a subset of the parameters as the extra ones are ignored,
+    /// and the fields of type `Option` or marked with `#[serde(default)]` are filled in when the parameter is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::RouteParams;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Pagination {
+    ///     page: u32,
+    ///     #[serde(default)]
+    ///     per_page: Option<u32>,
+    /// }
+    ///
+    /// let mut params = RouteParams::new();
+    /// params.set("userId", "42");
+    /// params.set("page", "3");
+    ///
+    /// let pagination: Pagination = params.deserialize_into().unwrap();
+    /// assert_eq!(3, pagination.page);
+    /// assert_eq!(None, pagination.per_page);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn deserialize_into<'a, T: Deserialize<'a>>(&'a self) -> Result<T, Error> {
+        T::deserialize(RouteParamsDeserializer::new(self))
+    }
+
     /// Extends the current parameters map with other one.
     pub fn extend(&mut self, other_route_params: RouteParams) {
         other_route_params.0.into_iter().for_each(|(key, val)| {
@@ -120,3 +194,77 @@ impl RouteParams {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[derive(Deserialize)]
+    struct BookParams<'a> {
+        book: &'a str,
+        page: Option<u32>,
+        #[serde(default)]
+        lang: String,
+    }
+
+    #[test]
+    fn can_parse_params_into_target_type() {
+        let mut params = RouteParams::new();
+        params.set("userId", "42");
+        params.set("offset", "-7");
+        params.set("addr", "10.0.0.1");
+
+        assert_eq!(Ok(42u64), params.get_as("userId"));
+        assert_eq!(Ok(-7i32), params.get_as("offset"));
+        assert_eq!(Ok(std::net::Ipv4Addr::new(10, 0, 0, 1)), params.get_as("addr"));
+
+        let err = params.get_as::<u64>("offset").unwrap_err();
+        assert_eq!("offset", err.name());
+        assert_eq!(
+            "Couldn't parse the route parameter `offset` from \"-7\": invalid digit found in string",
+            err.to_string()
+        );
+
+        let err = params.get_as::<u64>("bookId").unwrap_err();
+        assert_eq!(ParamError::Missing { name: "bookId".into() }, err);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn can_deserialize_optional_fields_when_present() {
+        let mut params = RouteParams::new();
+        params.set("userId", "42");
+        params.set("book", "HarryPotter");
+        params.set("page", "7");
+        params.set("lang", "en");
+
+        let book: BookParams = params.deserialize_into().unwrap();
+        assert_eq!("HarryPotter", book.book);
+        assert_eq!(Some(7), book.page);
+        assert_eq!("en", book.lang);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn can_deserialize_optional_fields_when_absent() {
+        let mut params = RouteParams::new();
+        params.set("userId", "42");
+        params.set("book", "HarryPotter");
+
+        let book: BookParams = params.deserialize_into().unwrap();
+        assert_eq!("HarryPotter", book.book);
+        assert_eq!(None, book.page);
+        assert_eq!("", book.lang);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn errors_on_missing_required_field() {
+        let mut params = RouteParams::new();
+        params.set("page", "7");
+
+        let err = params.deserialize_into::<BookParams>().err().unwrap();
+        assert!(err.to_string().contains("`book`"), "unexpected error: {}", err);
+    }
+}
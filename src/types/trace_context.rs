@@ -0,0 +1,213 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Represents the [W3C trace context](https://www.w3.org/TR/trace-context/) of a request, parsed from the `traceparent`
+/// and the `tracestate` headers.
+///
+/// It's stored into the request context by the [`Middleware::trace_context`](./enum.Middleware.html#method.trace_context)
+/// middleware and can be accessed as `req.trace_context()`. A new trace is started when the request has no
+/// `traceparent` header or it's malformed.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::Full;
+/// use hyper::{body::Bytes, Request, Response};
+/// use routerify_ng::ext::RequestExt;
+/// use routerify_ng::{Middleware, Router};
+/// use std::convert::Infallible;
+///
+/// fn run() -> Router<Infallible> {
+///     let router = Router::builder()
+///         .middleware(Middleware::trace_context())
+///         .get("/", |req| async move {
+///             let trace_context = req.trace_context().unwrap();
+///
+///             // Propagate the trace to the outgoing call with a new span.
+///             let child = trace_context.child();
+///             let _outgoing = Request::get("http://example.com/")
+///                 .header("traceparent", child.to_traceparent())
+///                 .body(Full::new(Bytes::new()))
+///                 .unwrap();
+///
+///             Ok(Response::new(Full::new(Bytes::from(trace_context.trace_id().to_owned()))))
+///         })
+///         .build()
+///         .unwrap();
+///     router
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: String,
+    span_id: String,
+    flags: u8,
+    trace_state: Option<String>,
+}
+
+impl TraceContext {
+    /// Parses the value of a `traceparent` header, e.g. `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+    ///
+    /// It returns `None` if the value is malformed or it has an all-zero trace ID or span ID.
+    pub fn parse(traceparent: &str) -> Option<TraceContext> {
+        let mut parts = traceparent.trim().split('-');
+
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+
+        if !is_lower_hex(version, 2) || version == "ff" {
+            return None;
+        }
+        // The future versions may append more fields, the version `00` must have exactly four.
+        if version == "00" && parts.next().is_some() {
+            return None;
+        }
+        if !is_lower_hex(trace_id, 32) || is_all_zeros(trace_id) {
+            return None;
+        }
+        if !is_lower_hex(span_id, 16) || is_all_zeros(span_id) {
+            return None;
+        }
+        if !is_lower_hex(flags, 2) {
+            return None;
+        }
+
+        Some(TraceContext {
+            trace_id: trace_id.to_owned(),
+            span_id: span_id.to_owned(),
+            flags: u8::from_str_radix(flags, 16).ok()?,
+            trace_state: None,
+        })
+    }
+
+    /// Starts a new sampled trace with random trace ID and span ID.
+    pub fn new_root() -> TraceContext {
+        TraceContext {
+            trace_id: format!("{:016x}{:016x}", random_u64(), random_u64()),
+            span_id: new_span_id(),
+            flags: 0x01,
+            trace_state: None,
+        }
+    }
+
+    /// Creates the context of a child span in the same trace, having a new span ID. It should be used for the
+    /// outgoing calls made while handling the request.
+    pub fn child(&self) -> TraceContext {
+        TraceContext {
+            trace_id: self.trace_id.clone(),
+            span_id: new_span_id(),
+            flags: self.flags,
+            trace_state: self.trace_state.clone(),
+        }
+    }
+
+    /// Sets the value of the `tracestate` header which is propagated along with the trace.
+    pub fn with_trace_state<S: Into<String>>(mut self, trace_state: S) -> TraceContext {
+        self.trace_state = Some(trace_state.into());
+        self
+    }
+
+    /// Returns the trace ID as 32 lowercase hex characters.
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// Returns the span ID as 16 lowercase hex characters. For a parsed context it's the `parent-id` field of the
+    /// `traceparent` header, i.e. the span of the caller.
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    /// Returns the trace flags.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns `true` if the caller may have recorded the trace.
+    pub fn is_sampled(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Returns the value of the `tracestate` header, if any.
+    pub fn trace_state(&self) -> Option<&str> {
+        self.trace_state.as_deref()
+    }
+
+    /// Formats the context as the value of a `traceparent` header.
+    pub fn to_traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+}
+
+fn is_lower_hex(val: &str, len: usize) -> bool {
+    val.len() == len && val.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn is_all_zeros(val: &str) -> bool {
+    val.bytes().all(|b| b == b'0')
+}
+
+fn new_span_id() -> String {
+    // A zero span ID is invalid, so the least significant bit is always set.
+    format!("{:016x}", random_u64() | 1)
+}
+
+fn random_u64() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(nanos);
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_valid_traceparent() {
+        let ctx = TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+        assert_eq!("4bf92f3577b34da6a3ce929d0e0e4736", ctx.trace_id());
+        assert_eq!("00f067aa0ba902b7", ctx.span_id());
+        assert!(ctx.is_sampled());
+        assert_eq!(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ctx.to_traceparent()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_traceparent() {
+        for val in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert_eq!(None, TraceContext::parse(val), "accepted {:?}", val);
+        }
+    }
+
+    #[test]
+    fn child_keeps_trace_id_with_new_span_id() {
+        let root = TraceContext::new_root();
+        assert!(TraceContext::parse(&root.to_traceparent()).is_some());
+
+        let child = root.child();
+        assert_eq!(root.trace_id(), child.trace_id());
+        assert_ne!(root.span_id(), child.span_id());
+    }
+}
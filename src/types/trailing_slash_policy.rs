@@ -0,0 +1,21 @@
+/// Decides how a trailing slash in the request path is treated when matching the routes, see
+/// [`RouterBuilder::trailing_slash`](./struct.RouterBuilder.html#method.trailing_slash).
+///
+/// A glob route, e.g. `/files/*`, matches the paths with and without a trailing slash under any policy. The root path
+/// `/` is never redirected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrailingSlashPolicy {
+    /// A request path matches the routes declared with and without the trailing slash alike, e.g. both `/about` and
+    /// `/about/` match the `/about` route.
+    #[default]
+    Merge,
+    /// A request path matches only the routes declared with the same trailing slash, e.g. `/about/` doesn't match the
+    /// `/about` route.
+    Strict,
+    /// A request path with a trailing slash is redirected to the one without it via `308 Permanent Redirect`, keeping
+    /// the query.
+    RedirectToNoSlash,
+    /// A request path without a trailing slash is redirected to the one with it via `308 Permanent Redirect`, keeping
+    /// the query.
+    RedirectToSlash,
+}
@@ -0,0 +1,279 @@
+use crate::types::ForwardedHeader;
+use hyper::HeaderMap;
+use hyper::header;
+use std::net::{IpAddr, SocketAddr};
+
+/// Tells which proxies are trusted to report the client address via the `X-Forwarded-For` or the `Forwarded` header,
+/// see [`RouterBuilder::trust_proxy`](./struct.RouterBuilder.html#method.trust_proxy) and
+/// [`RequestExt::client_addr`](./ext/trait.RequestExt.html#tymethod.client_addr).
+///
+/// The header, `X-Forwarded-For` unless set via [`header`](#method.header), is read only if the request comes from a
+/// trusted proxy. Then the addresses it lists are walked from the nearest hop backwards, and the first one which isn't a trusted proxy is the client address, so the entries a client
+/// prepends to spoof its address are ignored.
+///
+/// # Security
+///
+/// Any client can send these headers, so a proxy must be trusted only if it overwrites or appends to the configured one.
+/// Trusting a
+/// range which includes the clients, e.g. `0.0.0.0/0`, lets every client choose the address the app sees, defeating the
+/// rate limiting, the IP allowlists and the audit logs relying on it.
+///
+/// # Examples
+///
+/// ```
+/// use routerify_ng::TrustConfig;
+///
+/// let config = TrustConfig::new().proxy("10.0.0.0/8").proxy("::1");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrustConfig {
+    proxies: Vec<Cidr>,
+    header: ForwardedHeader,
+    // The first proxy range which couldn't be parsed, reported by `RouterBuilder::trust_proxy`.
+    pub(crate) invalid: Option<String>,
+}
+
+impl TrustConfig {
+    /// Creates a config trusting no proxies.
+    pub fn new() -> TrustConfig {
+        TrustConfig::default()
+    }
+
+    /// Trusts the proxies in the specified address range, in the CIDR notation, e.g. `10.0.0.0/8` or `fd00::/8`, or a
+    /// single address, e.g. `127.0.0.1`.
+    ///
+    /// An invalid range makes the [`RouterBuilder`](./struct.RouterBuilder.html) fail to build the router.
+    pub fn proxy<C: AsRef<str>>(mut self, cidr: C) -> Self {
+        let cidr = cidr.as_ref();
+        match Cidr::parse(cidr) {
+            Some(parsed) => self.proxies.push(parsed),
+            None => {
+                self.invalid.get_or_insert_with(|| cidr.to_owned());
+            }
+        }
+        self
+    }
+
+    /// Sets the header which the trusted proxies maintain, `X-Forwarded-For` by default. The other one is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify_ng::{ForwardedHeader, TrustConfig};
+    ///
+    /// let config = TrustConfig::new().proxy("10.0.0.0/8").header(ForwardedHeader::Forwarded);
+    /// ```
+    pub fn header(mut self, header: ForwardedHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Returns whether the address belongs to a trusted proxy.
+    pub fn is_trusted(&self, addr: IpAddr) -> bool {
+        let addr = addr.to_canonical();
+        self.proxies.iter().any(|cidr| cidr.contains(addr))
+    }
+
+    // Resolves the client address of a request received from the `peer` address.
+    pub(crate) fn client_addr(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let mut client = peer;
+        for hop in forwarded_for(headers, self.header).into_iter().rev() {
+            // An obfuscated or malformed hop hides the rest of the chain, so the last known address is kept.
+            let Some(hop) = hop else { break };
+            client = hop;
+            if !self.is_trusted(hop) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cidr {
+    addr: IpAddr,
+    prefix: u32,
+}
+
+impl Cidr {
+    fn parse(val: &str) -> Option<Cidr> {
+        let (addr, prefix) = match val.trim().split_once('/') {
+            Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse::<u32>().ok()?)),
+            None => (val.trim().parse::<IpAddr>().ok()?, None),
+        };
+        let addr = addr.to_canonical();
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max_prefix);
+        (prefix <= max_prefix).then_some(Cidr { addr, prefix })
+    }
+
+    fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix).unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix).unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+// Returns the client addresses the proxies reported in the `header`, from the farthest hop to the nearest one, or `None`
+// for a hop whose address isn't known.
+fn forwarded_for(headers: &HeaderMap, header: ForwardedHeader) -> Vec<Option<IpAddr>> {
+    let values = |name| {
+        headers
+            .get_all(name)
+            .iter()
+            .map(|val| val.to_str().ok())
+            .collect::<Vec<_>>()
+    };
+
+    match header {
+        ForwardedHeader::Forwarded => values(header::FORWARDED)
+            .into_iter()
+            .flat_map(|val| match val {
+                Some(val) => val.split(',').map(forwarded_element_for).collect(),
+                None => vec![None],
+            })
+            .collect(),
+        ForwardedHeader::XForwardedFor => values(header::HeaderName::from_static("x-forwarded-for"))
+            .into_iter()
+            .flat_map(|val| match val {
+                Some(val) => val.split(',').map(parse_node).collect(),
+                None => vec![None],
+            })
+            .collect(),
+    }
+}
+
+// Extracts the `for` parameter of a `Forwarded` header element, e.g. `for=192.0.2.60;proto=http`.
+fn forwarded_element_for(element: &str) -> Option<IpAddr> {
+    element.split(';').find_map(|pair| {
+        let (name, val) = pair.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("for")
+            .then(|| parse_node(val.trim().trim_matches('"')))?
+    })
+}
+
+// Parses a node address, which may have a port, and the IPv6 one may be enclosed in brackets, e.g. `[2001:db8::17]:4711`.
+fn parse_node(val: &str) -> Option<IpAddr> {
+    let val = val.trim();
+    if let Ok(addr) = val.parse::<IpAddr>() {
+        return Some(addr);
+    }
+    if let Ok(addr) = val.parse::<SocketAddr>() {
+        return Some(addr.ip());
+    }
+    val.strip_prefix('[')
+        .and_then(|val| val.strip_suffix(']'))
+        .and_then(|val| val.parse::<IpAddr>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, val) in pairs {
+            headers.append(*name, val.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn resolves_rightmost_untrusted_hop() {
+        let config = TrustConfig::new().proxy("10.0.0.0/8");
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let xff = headers(&[
+            ("x-forwarded-for", "6.6.6.6, 203.0.113.7"),
+            ("x-forwarded-for", "10.0.0.1"),
+        ]);
+        assert_eq!(
+            config.client_addr(proxy, &xff),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+
+        let forwarded_config = config.clone().header(ForwardedHeader::Forwarded);
+        let forwarded = headers(&[
+            (
+                "forwarded",
+                r#"for="[2001:db8:cafe::17]:4711";proto=https, for=10.0.0.1"#,
+            ),
+            ("x-forwarded-for", "6.6.6.6"),
+        ]);
+        assert_eq!(
+            forwarded_config.client_addr(proxy, &forwarded),
+            "2001:db8:cafe::17".parse::<IpAddr>().unwrap()
+        );
+
+        let untrusted: IpAddr = "198.51.100.1".parse().unwrap();
+        assert_eq!(config.client_addr(untrusted, &xff), untrusted);
+
+        let obfuscated = headers(&[("forwarded", "for=203.0.113.7, for=_hidden, for=10.0.0.1")]);
+        assert_eq!(
+            forwarded_config.client_addr(proxy, &obfuscated),
+            "10.0.0.1".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn ignores_header_not_maintained_by_proxies() {
+        let config = TrustConfig::new().proxy("10.0.0.0/8");
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+
+        // The proxy appended the client address to `X-Forwarded-For` and passed the spoofed `Forwarded` through.
+        let spoofed = headers(&[("forwarded", "for=1.2.3.4"), ("x-forwarded-for", "203.0.113.7")]);
+        assert_eq!(
+            config.client_addr(proxy, &spoofed),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+
+        // And the other way around.
+        let spoofed = headers(&[("forwarded", "for=203.0.113.7"), ("x-forwarded-for", "1.2.3.4")]);
+        assert_eq!(
+            config.header(ForwardedHeader::Forwarded).client_addr(proxy, &spoofed),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_proxy_ranges() {
+        let config = TrustConfig::new()
+            .proxy("192.168.0.0/16")
+            .proxy("fd00::/8")
+            .proxy("127.0.0.1");
+        assert!(config.invalid.is_none());
+        assert!(config.is_trusted("192.168.4.2".parse().unwrap()));
+        assert!(config.is_trusted("::ffff:192.168.4.2".parse().unwrap()));
+        assert!(config.is_trusted("fd12::1".parse().unwrap()));
+        assert!(config.is_trusted("127.0.0.1".parse().unwrap()));
+        assert!(!config.is_trusted("127.0.0.2".parse().unwrap()));
+        assert!(!config.is_trusted("192.169.0.1".parse().unwrap()));
+
+        assert!(
+            TrustConfig::new()
+                .proxy("0.0.0.0/0")
+                .is_trusted("8.8.8.8".parse().unwrap())
+        );
+        assert_eq!(
+            TrustConfig::new().proxy("10.0.0.0/33").invalid.as_deref(),
+            Some("10.0.0.0/33")
+        );
+        assert_eq!(
+            TrustConfig::new().proxy("localhost").invalid.as_deref(),
+            Some("localhost")
+        );
+    }
+}
@@ -525,3 +525,4465 @@ async fn can_handle_pre_middleware_errors() {
         .unwrap();
     serve.shutdown();
 }
+
+#[tokio::test]
+async fn can_report_connection_errors_to_callback() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::{mpsc, oneshot};
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/", |_| async move { Ok(Response::new(Full::from("Hello world"))) })
+        .build()
+        .unwrap();
+    let service = routerify_ng::RouterService::new(router).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (err_tx, mut err_rx) = mpsc::unbounded_channel::<String>();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let server = tokio::spawn(routerify_ng::serve_with_graceful_shutdown(
+        listener,
+        service,
+        move |err: &RouteError| {
+            err_tx.send(err.to_string()).unwrap();
+        },
+        async move {
+            shutdown_rx.await.ok();
+        },
+    ));
+
+    // The client sends garbage instead of a valid HTTP request.
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"NOT AN HTTP REQUEST\r\n\r\n").await.unwrap();
+    let mut buf = Vec::new();
+    let _ = stream.read_to_end(&mut buf).await;
+
+    let err = tokio::time::timeout(std::time::Duration::from_secs(5), err_rx.recv())
+        .await
+        .expect("The connection error callback was not called")
+        .unwrap();
+    assert!(!err.is_empty());
+
+    shutdown_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn can_strip_prefix_from_scope() {
+    fn api_router(strip: bool) -> Router<routerify_ng::Error> {
+        Router::builder()
+            .strip_prefix_from_scope(strip)
+            .get("/users/:id", |req| async move {
+                Ok(Response::new(Full::from(req.uri().path().to_owned())))
+            })
+            .build()
+            .unwrap()
+    }
+
+    for (strip, expected_path) in [(false, "/api/users/1"), (true, "/users/1")] {
+        let router: Router<routerify_ng::Error> = Router::builder().scope("/api", api_router(strip)).build().unwrap();
+        let serve = serve(router).await;
+
+        let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+        let resp = client
+            .request(
+                serve
+                    .new_request("GET", "/api/users/1")
+                    .body(Full::new(Bytes::new()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(200, resp.status().as_u16());
+        assert_eq!(expected_path, into_text(resp.into_body()).await);
+
+        serve.shutdown();
+    }
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn can_deserialize_path_params() {
+    #[derive(serde::Deserialize)]
+    struct BookParams {
+        user_id: u64,
+        book: String,
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/users/:user_id/books/:book", |req| async move {
+            let resp = match req.path_params::<BookParams>() {
+                Ok(params) => Response::new(Full::from(format!("{} {}", params.user_id, params.book))),
+                Err(err) => Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Full::from(err.to_string()))
+                    .unwrap(),
+            };
+            Ok(resp)
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/users/42/books/HarryPotter")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!("42 HarryPotter", into_text(resp.into_body()).await);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/users/alice/books/HarryPotter")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(400, resp.status().as_u16());
+    let err = into_text(resp.into_body()).await;
+    assert!(err.contains("`user_id`"), "unexpected error: {}", err);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_reach_root_route_of_scope_with_or_without_trailing_slash() {
+    let app_router: Router<routerify_ng::Error> = Router::builder()
+        .strip_prefix_from_scope(true)
+        .get("/", |req| async move {
+            Ok(Response::new(Full::from(format!("root {}", req.uri().path()))))
+        })
+        .get("/foo", |req| async move {
+            Ok(Response::new(Full::from(format!("foo {}", req.uri().path()))))
+        })
+        .build()
+        .unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder().scope("/app", app_router).build().unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, expected) in [("/app", "root /"), ("/app/", "root /"), ("/app/foo", "foo /foo")] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(200, resp.status().as_u16(), "unexpected status for {}", path);
+        assert_eq!(expected, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_map_error_status_in_default_err_handler() {
+    #[derive(Debug)]
+    struct NotFound;
+    impl std::error::Error for NotFound {}
+    impl std::fmt::Display for NotFound {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "Not found")
+        }
+    }
+    impl routerify_ng::ErrorStatus for NotFound {
+        fn status(&self) -> StatusCode {
+            StatusCode::NOT_FOUND
+        }
+    }
+
+    let router: Router<NotFound> = Router::builder()
+        .get("/users/:id", |_| async move { Err(NotFound) })
+        .err_status::<NotFound>()
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/users/1")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_order_middleware_by_priority() {
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let record = |name: &'static str, priority: i32| {
+        let order = order.clone();
+        Middleware::pre_with_priority("/*", priority, move |req| {
+            let order = order.clone();
+            async move {
+                order.lock().unwrap().push(name);
+                Ok(req)
+            }
+        })
+        .unwrap()
+    };
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(record("rate-limit", 20))
+        .middleware(record("log", 30))
+        .middleware(record("auth", 10))
+        .get("/", |_| async move { Ok(Response::new(Full::from("Hello world"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!(*order.lock().unwrap(), vec!["auth", "rate-limit", "log"]);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_report_build_timings() {
+    let mut builder = Router::<routerify_ng::Error>::builder();
+    for i in 0..100 {
+        builder = builder
+            .middleware(Middleware::pre_with_path(format!("/abc-{}", i), |req| async move { Ok(req) }).unwrap())
+            .get(format!("/abc-{}/:id", i), |_| async move {
+                Ok(Response::new(Full::from("Hello world")))
+            });
+    }
+    let api_router = Router::builder()
+        .get("/users", |_| async move { Ok(Response::new(Full::from("User list"))) })
+        .middleware(Middleware::post(|res| async move { Ok(res) }))
+        .middleware(Middleware::around(|req, next| async move { Ok(next.run(req).await) }))
+        .build()
+        .unwrap();
+
+    let (router, report) = builder.scope("/api", api_router).build_timed().unwrap();
+
+    assert_eq!(report.route_count(), 101);
+    assert_eq!(report.pre_middleware_count(), 100);
+    assert_eq!(report.post_middleware_count(), 1);
+    assert_eq!(report.around_middleware_count(), 1);
+    assert!(report.regex_compilation() > std::time::Duration::ZERO);
+    assert!(report.total() >= report.regex_compilation() + report.regex_set_construction());
+
+    // The prepared router is served as it is, with the default routes.
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (method, path, status) in [
+        ("GET", "/api/users", 200),
+        ("GET", "/missing", 404),
+        ("OPTIONS", "/api/users", 204),
+    ] {
+        let resp = client
+            .request(serve.new_request(method, path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            status,
+            resp.status().as_u16(),
+            "unexpected status for {} {}",
+            method,
+            path
+        );
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_match_routes_by_guard() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get_guarded(
+            "/x",
+            |req| req.headers().get("x-variant").is_some_and(|v| v == "a"),
+            |_| async move { Ok(Response::new(Full::from("variant a"))) },
+        )
+        .get_guarded(
+            "/x",
+            |req| req.headers().get("x-variant").is_some_and(|v| v == "b"),
+            |_| async move { Ok(Response::new(Full::from("variant b"))) },
+        )
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for variant in ["a", "b"] {
+        let resp = client
+            .request(
+                serve
+                    .new_request("GET", "/x")
+                    .header("x-variant", variant)
+                    .body(Full::new(Bytes::new()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(200, resp.status().as_u16());
+        assert_eq!(format!("variant {}", variant), into_text(resp.into_body()).await);
+    }
+
+    let resp = client
+        .request(serve.new_request("GET", "/x").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(404, resp.status().as_u16());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_list_closest_routes_in_debug_404() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .debug_404(true)
+        .get("/users", |_| async move { Ok(Response::new(Full::from("User list"))) })
+        .get(
+            "/books/:bookId",
+            |_| async move { Ok(Response::new(Full::from("Book"))) },
+        )
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/usres")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(404, resp.status().as_u16());
+    let body = into_text(resp.into_body()).await;
+    assert!(
+        body.contains("No route matches GET /usres"),
+        "unexpected body: {}",
+        body
+    );
+    assert!(body.contains("GET /users\n"), "unexpected body: {}", body);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_accumulate_vary_header_across_middleware() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::post(|mut res| async move {
+            routerify_ng::append_vary(&mut res, "Accept-Encoding");
+            Ok(res)
+        }))
+        .middleware(Middleware::post(|mut res| async move {
+            routerify_ng::append_vary(&mut res, "Accept-Language");
+            routerify_ng::append_vary(&mut res, "accept-encoding");
+            Ok(res)
+        }))
+        .get("/", |_| async move { Ok(Response::new(Full::from("Hello world"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    let vary = resp.headers().get_all("vary").iter().collect::<Vec<_>>();
+    assert_eq!(vary, vec!["Accept-Encoding, Accept-Language"]);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_reject_trace_requests_by_default() {
+    async fn request_status(router: Router<routerify_ng::Error>, path: &str) -> (u16, Option<String>) {
+        let serve = serve(router).await;
+        let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+        let resp = client
+            .request(serve.new_request("TRACE", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        serve.shutdown();
+        let allow = resp.headers().get("allow").map(|val| val.to_str().unwrap().to_owned());
+        (resp.status().as_u16(), allow)
+    }
+
+    let router = Router::builder()
+        .get("/", |_| async move { Ok(Response::new(Full::from("Hello world"))) })
+        .post("/", |_| async move { Ok(Response::new(Full::from("Posted"))) })
+        .build()
+        .unwrap();
+    assert_eq!((405, Some("GET, POST".to_owned())), request_status(router, "/").await);
+
+    let router = Router::builder()
+        .any_method("/any", |_| async move { Ok(Response::new(Full::from("Any"))) })
+        .build()
+        .unwrap();
+    let (status, allow) = request_status(router, "/any").await;
+    assert_eq!(405, status);
+    assert!(!allow.unwrap().contains("TRACE"));
+
+    let router = Router::builder()
+        .trace("/debug", |_| async move { Ok(Response::new(Full::from("Traced"))) })
+        .build()
+        .unwrap();
+    assert_eq!(200, request_status(router, "/debug").await.0);
+
+    let router = Router::builder()
+        .allow_trace()
+        .get("/", |_| async move { Ok(Response::new(Full::from("Hello world"))) })
+        .build()
+        .unwrap();
+    assert_eq!(404, request_status(router, "/missing").await.0);
+}
+
+#[tokio::test]
+async fn can_map_every_response() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .map_response(|mut res| {
+            res.headers_mut().insert("x-mapped", "1".parse().unwrap());
+            res
+        })
+        .get("/", |_| async move { Ok(Response::new(Full::from("Hello world"))) })
+        .get("/error", |_| async move { Err(routerify_ng::Error::new("Error!")) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, status) in [("/", 200), ("/not-found", 404), ("/error", 500)] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status().as_u16());
+        assert_eq!(resp.headers()["x-mapped"], "1", "response to {} is not mapped", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_defer_body_reading() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .defer_body_reading(true)
+        .post("/ignore", |_| async move { Ok(Response::new(Full::from("Ignored"))) })
+        .post("/echo", |req| async move {
+            let body = req.body_bytes().await?;
+            // The body is cached after the first read.
+            assert_eq!(body, req.body_bytes().await?);
+            Ok(Response::new(Full::new(body)))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    // The body is announced but never sent, so the handler would hang if it waited for it.
+    let mut stream = TcpStream::connect(serve.addr()).await.unwrap();
+    stream
+        .write_all(b"POST /ignore HTTP/1.1\r\nHost: localhost\r\nContent-Length: 1024\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = [0u8; 1024];
+    let n = tokio::time::timeout(std::time::Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("the handler waited for the body")
+        .unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).starts_with("HTTP/1.1 200 OK"));
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/echo")
+                .body(Full::new(Bytes::from("Hello world")))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("Hello world", into_text(resp.into_body()).await);
+
+    serve.shutdown();
+}
+
+#[test]
+fn can_display_route_table() {
+    let api: Router<routerify_ng::Error> = Router::builder()
+        .get(
+            "/users/:userId",
+            |_| async move { Ok(Response::new(Full::from("User"))) },
+        )
+        .add(
+            "/users",
+            vec![hyper::Method::GET, hyper::Method::POST],
+            |_| async move { Ok(Response::new(Full::from("Users"))) },
+        )
+        .build()
+        .unwrap();
+    let legacy: Router<routerify_ng::Error> = Router::builder()
+        .get("/legacy/", |_| async move { Ok(Response::new(Full::from("Legacy"))) })
+        .build()
+        .unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::pre(|req| async move { Ok(req) }))
+        .get("/", |_| async move { Ok(Response::new(Full::from("Home"))) })
+        .scope("/api", api)
+        .any(|_| async move { Ok(Response::new(Full::from("Fallback"))) })
+        .middleware(Middleware::post(|res| async move { Ok(res) }))
+        .fallback_router(legacy)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        router.to_string(),
+        "Pre-Middlewares:\n  \
+           /*\n\
+         Routes:\n  \
+           GET        /\n  \
+           *          /*\n  \
+           Scope /api:\n    \
+             GET        /api/users/:userId\n    \
+             GET, POST  /api/users\n\
+         Post-Middlewares:\n  \
+           /*\n\
+         Fallback Router:\n  \
+           Pre-Middlewares:\n  \
+           Routes:\n    \
+             GET  /legacy/\n  \
+           Post-Middlewares:\n"
+    );
+}
+
+#[tokio::test]
+async fn can_observe_request_cancellation_on_client_disconnect() {
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+    use tokio::sync::mpsc;
+
+    let (started_tx, mut started_rx) = mpsc::unbounded_channel::<()>();
+    let (cancelled_tx, mut cancelled_rx) = mpsc::unbounded_channel::<()>();
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/slow", move |req| {
+            let started_tx = started_tx.clone();
+            let cancelled_tx = cancelled_tx.clone();
+            async move {
+                let cancelled = req.cancelled();
+                tokio::spawn(async move {
+                    cancelled.await;
+                    cancelled_tx.send(()).unwrap();
+                });
+                started_tx.send(()).unwrap();
+
+                std::future::pending::<()>().await;
+                Ok(Response::new(Full::from("Never sent")))
+            }
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let mut stream = TcpStream::connect(serve.addr()).await.unwrap();
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    started_rx.recv().await.unwrap();
+    drop(stream);
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), cancelled_rx.recv())
+        .await
+        .expect("the cancellation future didn't resolve")
+        .unwrap();
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_keep_private_data_within_scope() {
+    #[derive(Clone)]
+    struct Secret(&'static str);
+
+    async fn read_secret(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, routerify_ng::Error> {
+        let secret = req.data::<Secret>().map(|s| s.0).unwrap_or("None");
+        Ok(Response::new(Full::from(secret)))
+    }
+
+    let admin: Router<routerify_ng::Error> = Router::builder()
+        .data_private(Secret("admin"))
+        .get("/secret", read_secret)
+        .middleware(Middleware::post_with_info(|mut res, req_info| async move {
+            let secret = req_info.data::<Secret>().map(|s| s.0).unwrap_or("None");
+            res.headers_mut().insert("x-secret", secret.parse().unwrap());
+            Ok(res)
+        }))
+        .build()
+        .unwrap();
+    let public: Router<routerify_ng::Error> = Router::builder().get("/secret", read_secret).build().unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .scope("/admin", admin)
+        .scope("/public", public)
+        .get("/admin/other", read_secret)
+        .get("/secret", read_secret)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, expected) in [
+        ("/admin/secret", "admin"),
+        ("/admin/other", "None"),
+        ("/public/secret", "None"),
+        ("/secret", "None"),
+    ] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            expected,
+            into_text(resp.into_body()).await,
+            "unexpected data for {}",
+            path
+        );
+    }
+
+    // The post middlewares of the scope see its private data too.
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/admin/secret")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("admin", resp.headers()["x-secret"]);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_limit_request_headers() {
+    async fn request_status(router: Router<routerify_ng::Error>, headers: &[(&str, &str)]) -> u16 {
+        let serve = serve(router).await;
+        let mut req = serve.new_request("GET", "/");
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let resp = Client::builder(TokioExecutor::new())
+            .build_http::<Full<Bytes>>()
+            .request(req.body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        serve.shutdown();
+        resp.status().as_u16()
+    }
+
+    fn router(
+        build: impl FnOnce(
+            routerify_ng::RouterBuilder<routerify_ng::Error>,
+        ) -> routerify_ng::RouterBuilder<routerify_ng::Error>,
+    ) -> Router<routerify_ng::Error> {
+        build(Router::builder())
+            .get("/", |_| async move { Ok(Response::new(Full::from("Home"))) })
+            .build()
+            .unwrap()
+    }
+
+    // The client adds the `host` header on its own.
+    assert_eq!(
+        200,
+        request_status(router(|b| b.max_headers(3)), &[("x-a", "1"), ("x-b", "2")]).await
+    );
+    assert_eq!(
+        431,
+        request_status(
+            router(|b| b.max_headers(3)),
+            &[("x-a", "1"), ("x-b", "2"), ("x-c", "3")]
+        )
+        .await
+    );
+
+    let long_value = "a".repeat(300);
+    assert_eq!(
+        200,
+        request_status(router(|b| b.max_header_bytes(256)), &[("x-a", "1")]).await
+    );
+    assert_eq!(
+        431,
+        request_status(router(|b| b.max_header_bytes(256)), &[("x-a", long_value.as_str())]).await
+    );
+    assert_eq!(
+        431,
+        request_status(
+            router(|b| b.max_header_bytes(256)),
+            &[("x-a", &long_value[..150]), ("x-b", &long_value[..150])]
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn can_fall_back_to_another_router() {
+    let router_b: Router<routerify_ng::Error> = Router::builder()
+        .get("/b", |_| async move { Ok(Response::new(Full::from("B"))) })
+        .build()
+        .unwrap();
+    let router_a: Router<routerify_ng::Error> = Router::builder()
+        .get("/a", |_| async move { Ok(Response::new(Full::from("A"))) })
+        .fallback_router(router_b)
+        .build()
+        .unwrap();
+    let serve = serve(router_a).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, status, body) in [("/a", 200, "A"), ("/b", 200, "B"), ("/c", 404, "")] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status().as_u16());
+        assert_eq!(body, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_fall_back_to_router_requiring_request_info() {
+    let router_b: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::post_with_info(|mut res, req_info| async move {
+            res.headers_mut()
+                .insert("x-fallback-path", req_info.uri().path().parse().unwrap());
+            Ok(res)
+        }))
+        .get("/b", |_| async move { Ok(Response::new(Full::from("B"))) })
+        .build()
+        .unwrap();
+    let router_a: Router<routerify_ng::Error> = Router::builder()
+        .get("/a", |_| async move { Ok(Response::new(Full::from("A"))) })
+        .fallback_router(router_b)
+        .build()
+        .unwrap();
+    let serve = serve(router_a).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(serve.new_request("GET", "/b").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!("/b", resp.headers()["x-fallback-path"]);
+    assert_eq!("B", into_text(resp.into_body()).await);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_headers() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/", |req| async move {
+            let name = req.header("x-name").unwrap_or("None").to_owned();
+            let count = req.header_typed::<u32, _>("x-count").unwrap_or(0);
+            let invalid = req.header_typed::<u32, _>("x-name");
+            let tags = req.header_all("x-tag").join(",");
+            assert!(invalid.is_none());
+            assert!(req.header("x-missing").is_none());
+
+            Ok(Response::new(Full::from(format!("{} {} {}", name, count + 1, tags))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::builder(TokioExecutor::new())
+        .build_http::<Full<Bytes>>()
+        .request(
+            serve
+                .new_request("GET", "/")
+                .header("x-name", "alice")
+                .header("x-count", "41")
+                .header("x-tag", "a")
+                .header("x-tag", "b")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("alice 42 a,b", into_text(resp.into_body()).await);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_canonicalize_path_before_matching() {
+    use std::borrow::Cow;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .canonicalize_path(|path| {
+            // Strips the matrix parameters, e.g. `/users;sid=abc/42` becomes `/users/42`.
+            if !path.contains(';') {
+                return Cow::Borrowed(path);
+            }
+            let segments = path
+                .split('/')
+                .map(|s| s.split(';').next().unwrap())
+                .collect::<Vec<_>>();
+            Cow::Owned(segments.join("/"))
+        })
+        .get("/users/:id", |req| async move {
+            Ok(Response::new(Full::from(req.param("id").unwrap().to_owned())))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for path in ["/users/42", "/users;sid=abc123/42", "/users/42;sid=abc123"] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(200, resp.status().as_u16(), "{} is not routed", path);
+        assert_eq!("42", into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_remove_request_context_values() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Token(&'static str);
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+            req.set_context(Token("secret"));
+            req.set_context(42u32);
+            Ok(req)
+        }))
+        .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+            assert_eq!(req.request_context().remove::<Token>(), Some(Token("secret")));
+            Ok(req)
+        }))
+        .middleware(Middleware::post_with_info(|res, req_info: RequestInfo| async move {
+            assert_eq!(req_info.request_context().len(), 1);
+            req_info.request_context().clear();
+            assert!(req_info.request_context().is_empty());
+            Ok(res)
+        }))
+        .get("/", |req| async move {
+            let token = req.context::<Token>().map(|t| t.0).unwrap_or("None");
+            let count = req.context::<u32>().unwrap();
+            Ok(Response::new(Full::from(format!("{} {}", token, count))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::builder(TokioExecutor::new())
+        .build_http::<Full<Bytes>>()
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!("None 42", into_text(resp.into_body()).await);
+
+    serve.shutdown();
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn can_wrap_request_service_in_tower_layers() {
+    use routerify_ng::RequestServiceBuilder;
+    use tower::{ServiceBuilder, ServiceExt};
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/users/:id", |req| async move {
+            Ok(Response::new(Full::from(req.param("id").unwrap().to_owned())))
+        })
+        .build()
+        .unwrap();
+    let request_service = RequestServiceBuilder::new(router)
+        .unwrap()
+        .build("127.0.0.1:8080".parse().unwrap());
+
+    let service = ServiceBuilder::new()
+        .map_response(|mut res: Response<Full<Bytes>>| {
+            res.headers_mut().insert("x-layer", "1".parse().unwrap());
+            res
+        })
+        .service(request_service);
+
+    let req = Request::builder()
+        .uri("/users/42")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let resp = service.oneshot(req).await.unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!(resp.headers()["x-layer"], "1");
+    assert_eq!("42", into_text(resp.into_body()).await);
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn can_serve_tower_wrapped_request_service_with_hyper() {
+    use hyper::body::Incoming;
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use hyper_util::server::conn::auto::Builder;
+    use routerify_ng::RequestServiceBuilder;
+    use tokio::net::TcpListener;
+    use tower::{Service, ServiceBuilder, ServiceExt};
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .post("/echo", |req| async move { Ok(Response::new(req.into_body())) })
+        .build()
+        .unwrap();
+    let builder = RequestServiceBuilder::new(router).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = async move {
+        let (stream, remote_addr) = listener.accept().await.unwrap();
+        let service = ServiceBuilder::new()
+            .map_response(|mut res: Response<Full<Bytes>>| {
+                res.headers_mut().insert("x-layer", "1".parse().unwrap());
+                res
+            })
+            .service(builder.build(remote_addr));
+        let service = Arc::new(tokio::sync::Mutex::new(service));
+
+        let hyper_service = service_fn(move |req: Request<Incoming>| {
+            let service = service.clone();
+            async move {
+                let mut service = service.lock().await;
+                let fut = ServiceExt::<Request<Incoming>>::ready(&mut *service)
+                    .await
+                    .unwrap()
+                    .call(req);
+                drop(service);
+                fut.await
+            }
+        });
+        Builder::new(TokioExecutor::new())
+            .serve_connection(TokioIo::new(stream), hyper_service)
+            .await
+            .unwrap();
+    };
+
+    let client = async move {
+        let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+        let resp = client
+            .request(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("http://{}/echo", addr))
+                    .header("connection", "close")
+                    .body(Full::from("hello"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.headers()["x-layer"], "1");
+        assert_eq!("hello", into_text(resp.into_body()).await);
+    };
+
+    tokio::join!(server, client);
+}
+
+#[tokio::test]
+async fn can_record_metrics() {
+    use routerify_ng::{Maintenance, MetricsRecorder};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct MockRecorder {
+        started: Arc<Mutex<Vec<String>>>,
+        ended: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MetricsRecorder for MockRecorder {
+        fn on_request_start(&self, method: &hyper::Method, path: &str) {
+            self.started.lock().unwrap().push(format!("{} {}", method, path));
+        }
+
+        fn on_request_end(&self, status: StatusCode, _: Duration, route: Option<&str>) {
+            self.ended
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", status.as_u16(), route.unwrap_or("None")));
+        }
+    }
+
+    let recorder = MockRecorder::default();
+    let maintenance = Maintenance::new();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .metrics(recorder.clone())
+        .maintenance(maintenance.clone())
+        .body_limit(8)
+        .get("/users/:id", |_| async move { Ok(Response::new(Full::from("User"))) })
+        .post("/users", |_| async move { Ok(Response::new(Full::from("Created"))) })
+        .get("/error", |_| async move { Err(routerify_ng::Error::new("Error!")) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for path in ["/users/42", "/error", "/missing"] {
+        client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+    }
+
+    // The requests rejected before routing are recorded without a matched route.
+    client
+        .request(
+            serve
+                .new_request("POST", "/users")
+                .body(Full::from("a body exceeding the limit"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // The maintenance responses are recorded without a matched route.
+    maintenance.enable();
+    client
+        .request(
+            serve
+                .new_request("GET", "/users/42")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *recorder.started.lock().unwrap(),
+        vec![
+            "GET /users/42",
+            "GET /error",
+            "GET /missing",
+            "POST /users",
+            "GET /users/42"
+        ]
+    );
+    assert_eq!(
+        *recorder.ended.lock().unwrap(),
+        vec!["200 /users/:id", "500 /error", "404 /*", "413 None", "503 None"]
+    );
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_build_routes_from_macro() {
+    async fn user_handler(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, routerify_ng::Error> {
+        Ok(Response::new(Full::from(format!("User {}", req.param("id").unwrap()))))
+    }
+
+    async fn create_handler(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, routerify_ng::Error> {
+        Ok(Response::new(Full::from("Created")))
+    }
+
+    let router: Router<routerify_ng::Error> = routerify_ng::routes! {
+        GET "/users/:id" => user_handler,
+        POST "/users" => create_handler,
+    }
+    .build()
+    .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (method, path, body) in [("GET", "/users/42", "User 42"), ("POST", "/users", "Created")] {
+        let resp = client
+            .request(serve.new_request(method, path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(body, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_map_response_body_in_post_middleware() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(
+            Middleware::post_map_body("/shout/*", |body: Bytes| Bytes::from(body.to_ascii_uppercase())).unwrap(),
+        )
+        .get("/shout/hello", |_| async move {
+            Ok(Response::builder()
+                .header("content-length", "11")
+                .body(Full::from("hello world"))
+                .unwrap())
+        })
+        .get(
+            "/hello",
+            |_| async move { Ok(Response::new(Full::from("hello world"))) },
+        )
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, body) in [("/shout/hello", "HELLO WORLD"), ("/hello", "hello world")] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(body, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_register_routes_conditionally() {
+    async fn debug_status(debug: bool) -> u16 {
+        let router: Router<routerify_ng::Error> = Router::builder()
+            .when(debug, |builder| {
+                builder.get("/debug/dump", |_| async move { Ok(Response::new(Full::from("Dump"))) })
+            })
+            .build()
+            .unwrap();
+        let serve = serve(router).await;
+        let resp = Client::builder(TokioExecutor::new())
+            .build_http::<Full<Bytes>>()
+            .request(
+                serve
+                    .new_request("GET", "/debug/dump")
+                    .body(Full::new(Bytes::new()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        serve.shutdown();
+        resp.status().as_u16()
+    }
+
+    assert_eq!(200, debug_status(true).await);
+    assert_eq!(404, debug_status(false).await);
+}
+
+#[tokio::test]
+async fn can_report_matched_route_index() {
+    async fn report_index(req: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, routerify_ng::Error> {
+        Ok(Response::new(Full::from(format!("{:?}", req.matched_route_index()))))
+    }
+
+    let api: Router<routerify_ng::Error> = Router::builder().get("/items/:id", report_index).build().unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+            assert!(req.matched_route_index().is_none());
+            Ok(req)
+        }))
+        .get("/users/me", report_index)
+        .get("/users/:id", report_index)
+        .scope("/api", api)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, index) in [
+        ("/users/me", "Some(0)"),
+        ("/users/42", "Some(1)"),
+        ("/api/items/7", "Some(2)"),
+    ] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            index,
+            into_text(resp.into_body()).await,
+            "unexpected index for {}",
+            path
+        );
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_add_preload_links_to_html_responses() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::preload([("/style.css", "style"), ("/app.js", "script")]))
+        .get("/", |_| async move {
+            Ok(Response::builder()
+                .header("content-type", "text/html; charset=utf-8")
+                .body(Full::from("<html></html>"))
+                .unwrap())
+        })
+        .get("/api", |_| async move {
+            Ok(Response::builder()
+                .header("content-type", "application/json")
+                .body(Full::from("{}"))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        resp.headers()["link"],
+        "</style.css>; rel=preload; as=style, </app.js>; rel=preload; as=script"
+    );
+
+    let resp = client
+        .request(serve.new_request("GET", "/api").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert!(resp.headers().get("link").is_none());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_override_handler_timeout_per_route() {
+    use std::time::Duration;
+
+    async fn slow(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, routerify_ng::Error> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(Response::new(Full::from("Done")))
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .handler_timeout(Duration::from_millis(50))
+        .get("/slow", slow)
+        .get_with_timeout("/export", slow, Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, status) in [("/slow", 503), ("/export", 200)] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status().as_u16(), "unexpected status for {}", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_limit_regex_sizes_on_build() {
+    let handler = |_| async move { Ok(Response::new(Full::new(Bytes::from("OK")))) };
+
+    let pathological_path = (0..2000).map(|i| format!("/:p{}", i)).collect::<String>();
+    let err = Router::<routerify_ng::Error>::builder()
+        .regex_size_limit(64 * 1024)
+        .get("/", handler)
+        .get(pathological_path, handler)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("exceeds the regex size limit"), "{}", err);
+
+    let mut builder = Router::<routerify_ng::Error>::builder().regex_set_size_limit(64 * 1024);
+    for i in 0..500 {
+        builder = builder.get(format!("/resources/{}/:id/items/:item", i), handler);
+    }
+    let err = builder.build().unwrap_err();
+    assert!(err.to_string().contains("Couldn't create router RegexSet"), "{}", err);
+
+    let router = Router::<routerify_ng::Error>::builder()
+        .regex_size_limit(64 * 1024)
+        .regex_set_size_limit(1024 * 1024)
+        .get("/users/:id", handler)
+        .build();
+    assert!(router.is_ok());
+}
+
+#[tokio::test]
+async fn can_send_err_response_without_err_handler() {
+    use hyper::StatusCode;
+    use routerify_ng::or_response;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let err_handler_called = Arc::new(AtomicBool::new(false));
+    let called = err_handler_called.clone();
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get(
+            "/items/:id",
+            or_response(|req| async move {
+                if req.param("id").unwrap() == "0" {
+                    return Err(Response::builder()
+                        .status(StatusCode::BAD_REQUEST)
+                        .body(Full::from("Invalid item"))
+                        .unwrap());
+                }
+                Ok(Response::new(Full::from("Item")))
+            }),
+        )
+        .err_handler(move |_: RouteError| {
+            let called = called.clone();
+            async move {
+                called.store(true, Ordering::SeqCst);
+                Response::new(Full::from("Error"))
+            }
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/items/0")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+    assert_eq!("Invalid item", into_text(resp.into_body()).await);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/items/1")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("Item", into_text(resp.into_body()).await);
+    assert!(!err_handler_called.load(Ordering::SeqCst));
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_serve_with_make_service() {
+    use hyper::service::Service;
+    use hyper_util::rt::TokioIo;
+    use hyper_util::server::conn::auto::Builder;
+    use tokio::net::TcpListener;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/", |req| async move {
+            Ok(Response::new(Full::from(req.remote_addr().ip().to_string())))
+        })
+        .build()
+        .unwrap();
+    let make_service = routerify_ng::RouterService::new(router).unwrap().into_make_service();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        let (stream, remote_addr) = listener.accept().await.unwrap();
+        let request_service = make_service.call(remote_addr).await.unwrap();
+        Builder::new(TokioExecutor::new())
+            .serve_connection(TokioIo::new(stream), request_service)
+            .await
+            .unwrap();
+    });
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/", addr))
+                .header("connection", "close")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("127.0.0.1", into_text(resp.into_body()).await);
+
+    drop(client);
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn can_constrain_params_across_routes() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .param_constraint("org", "[a-z0-9-]+")
+        .get("/orgs/:org", |req| async move {
+            Ok(Response::new(Full::from(format!("org {}", req.param("org").unwrap()))))
+        })
+        .get("/orgs/:org/repos/:repo", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "repo {}/{}",
+                req.param("org").unwrap(),
+                req.param("repo").unwrap()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("/orgs/acme-1", StatusCode::OK, "org acme-1"),
+        ("/orgs/acme/repos/Router_NG", StatusCode::OK, "repo acme/Router_NG"),
+        ("/orgs/Acme", StatusCode::NOT_FOUND, ""),
+        ("/orgs/a_cme/repos/router", StatusCode::NOT_FOUND, ""),
+    ];
+    for (path, status, text) in cases {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status(), "unexpected status for {}", path);
+        if status == StatusCode::OK {
+            assert_eq!(text, into_text(resp.into_body()).await);
+        }
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_expose_response_source() {
+    use routerify_ng::ResponseSource;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::post(|mut res: Response<Full<Bytes>>| async move {
+            let source = format!("{:?}", res.extensions().get::<ResponseSource>().unwrap());
+            res.headers_mut().insert("x-source", source.parse().unwrap());
+            Ok(res)
+        }))
+        .get("/ok", |_| async move { Ok(Response::new(Full::from("OK"))) })
+        .get("/fail", |_| async move { Err(routerify_ng::Error::new("Failed")) })
+        .err_handler(|_: RouteError| async move { Response::builder().status(500).body(Full::from("Error")).unwrap() })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, source) in [("/ok", "Route"), ("/fail", "ErrorHandler"), ("/missing", "Fallback404")] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(source, resp.headers()["x-source"], "unexpected source for {}", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_defer_body_reading_in_request_service() {
+    use hyper_util::rt::TokioIo;
+    use hyper_util::server::conn::auto::Builder;
+    use routerify_ng::RequestServiceBuilder;
+    use tokio::net::TcpListener;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .post("/upload", |req| async move {
+            let buffered = into_text(req.body().clone()).await;
+            let streamed = req.body_bytes().await?;
+            Ok(Response::new(Full::from(format!(
+                "buffered: {:?}, streamed: {:?}",
+                buffered,
+                String::from_utf8_lossy(&streamed)
+            ))))
+        })
+        .build()
+        .unwrap();
+    let builder = RequestServiceBuilder::new(router).unwrap().defer_body_reading(true);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        let (stream, remote_addr) = listener.accept().await.unwrap();
+        let request_service = builder.build(remote_addr);
+        Builder::new(TokioExecutor::new())
+            .serve_connection(TokioIo::new(stream), request_service)
+            .await
+            .unwrap();
+    });
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri(format!("http://{}/upload", addr))
+                .header("connection", "close")
+                .body(Full::from("streamed content"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        r#"buffered: "", streamed: "streamed content""#,
+        into_text(resp.into_body()).await
+    );
+
+    drop(client);
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn can_set_content_length_automatically() {
+    use hyper::header::{CONTENT_LENGTH, TRANSFER_ENCODING};
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/text", |_| async move { Ok(Response::new(Full::from("Hello world"))) })
+        .get("/chunked", |_| async move {
+            Ok(Response::builder()
+                .header(TRANSFER_ENCODING, "chunked")
+                .body(Full::from("Hello"))
+                .unwrap())
+        })
+        .get("/empty", |_| async move {
+            Ok(Response::builder().status(204).body(Full::new(Bytes::new())).unwrap())
+        })
+        .build()
+        .unwrap();
+    let service = RequestServiceBuilder::new(router)
+        .unwrap()
+        .build("127.0.0.1:0".parse().unwrap());
+
+    let call = |path: &'static str| service.call(Request::builder().uri(path).body(Full::new(Bytes::new())).unwrap());
+
+    let resp = call("/text").await.unwrap();
+    assert_eq!("11", resp.headers()[CONTENT_LENGTH]);
+    assert_eq!("Hello world", into_text(resp.into_body()).await);
+
+    assert!(!call("/chunked").await.unwrap().headers().contains_key(CONTENT_LENGTH));
+    assert!(!call("/empty").await.unwrap().headers().contains_key(CONTENT_LENGTH));
+}
+
+#[tokio::test]
+async fn can_scope_with_data() {
+    #[derive(Clone)]
+    struct Greeting(&'static str);
+
+    let sub_router: Router<routerify_ng::Error> = Router::builder()
+        .get("/hello", |req| async move {
+            Ok(Response::new(Full::from(req.data::<Greeting>().unwrap().0)))
+        })
+        .build()
+        .unwrap();
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .scope_with_data("/x", Greeting("Hello from x"), sub_router)
+        .get("/hello", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "{}",
+                req.data::<Greeting>().is_some()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, text) in [("/x/hello", "Hello from x"), ("/hello", "false")] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(text, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_toggle_maintenance_mode() {
+    use routerify_ng::{Maintenance, RouterService};
+    use std::time::Duration;
+
+    let make_router = |maintenance: Maintenance| -> Router<routerify_ng::Error> {
+        Router::builder()
+            .maintenance(maintenance)
+            .get("/healthz", |_| async move { Ok(Response::new(Full::from("OK"))) })
+            .get("/users", |_| async move { Ok(Response::new(Full::from("User list"))) })
+            .build()
+            .unwrap()
+    };
+    let maintenance = Maintenance::new()
+        .retry_after(Duration::from_secs(120))
+        .body("Down for maintenance")
+        .exempt("/healthz");
+
+    // The handle exposed by the service shares the flag with the one set on the builder.
+    let service = RouterService::new(make_router(maintenance.clone())).unwrap();
+    service.maintenance().enable();
+    assert!(maintenance.is_enabled());
+    maintenance.disable();
+
+    let serve = serve(make_router(maintenance.clone())).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let get =
+        |path: &'static str| client.request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap());
+
+    assert_eq!(StatusCode::OK, get("/users").await.unwrap().status());
+
+    maintenance.enable();
+    let resp = get("/users").await.unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, resp.status());
+    assert_eq!("120", resp.headers()["retry-after"]);
+    assert_eq!("Down for maintenance", into_text(resp.into_body()).await);
+    assert_eq!(StatusCode::OK, get("/healthz").await.unwrap().status());
+
+    maintenance.disable();
+    assert_eq!(StatusCode::OK, get("/users").await.unwrap().status());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_without_reading_body_when_not_needed() {
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .post("/reject", |_| async move {
+            Ok(Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(Full::from("Rejected"))
+                .unwrap())
+        })
+        .needs_body(false)
+        .post("/upload", |_| async move { Ok(Response::new(Full::from("Uploaded"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    // The body never arrives in full, so only a route which doesn't need it can respond.
+    let mut stream = TcpStream::connect(serve.addr()).await.unwrap();
+    stream
+        .write_all(b"POST /reject HTTP/1.1\r\nhost: localhost\r\ncontent-length: 1000\r\n\r\npartial")
+        .await
+        .unwrap();
+
+    let mut buf = vec![0; 1024];
+    let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("The route didn't respond before the body arrived")
+        .unwrap();
+    let resp = String::from_utf8_lossy(&buf[..n]);
+    assert!(resp.starts_with("HTTP/1.1 403"), "{}", resp);
+    assert!(resp.ends_with("Rejected"), "{}", resp);
+
+    // A route needing the body waits for it.
+    let mut stream = TcpStream::connect(serve.addr()).await.unwrap();
+    stream
+        .write_all(b"POST /upload HTTP/1.1\r\nhost: localhost\r\ncontent-length: 1000\r\n\r\npartial")
+        .await
+        .unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(300), stream.read(&mut buf))
+            .await
+            .is_err()
+    );
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_report_server_timing() {
+    use std::time::Duration;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .server_timing(true)
+        .middleware(Middleware::pre(|req| async move { Ok(req) }).named("mw_auth"))
+        .middleware(Middleware::post(|res| async move { Ok(res) }))
+        .get("/", |_| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(Response::new(Full::from("Home")))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    let server_timing = resp.headers()["server-timing"].to_str().unwrap().to_owned();
+
+    let entries = server_timing.split(", ").collect::<Vec<_>>();
+    assert_eq!(3, entries.len(), "{}", server_timing);
+    assert!(entries[0].starts_with("mw_auth;dur="), "{}", server_timing);
+    assert!(entries[2].starts_with("mw_post_0;dur="), "{}", server_timing);
+
+    let handler_dur = entries[1].strip_prefix("handler;dur=").unwrap().parse::<f64>().unwrap();
+    assert!(handler_dur >= 20.0, "{}", server_timing);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_construct_err_handler_from_data() {
+    #[derive(Clone)]
+    struct ErrorFormat {
+        prefix: &'static str,
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .data(ErrorFormat { prefix: "Oops" })
+        .get(
+            "/",
+            |_| async move { Err(routerify_ng::Error::new("Something failed")) },
+        )
+        .err_handler_with_data(|format: &ErrorFormat| {
+            let prefix = format.prefix;
+            move |err: RouteError| {
+                let body = format!("{}: {}", prefix, err);
+                async move { Response::builder().status(500).body(Full::from(body)).unwrap() }
+            }
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, resp.status());
+    assert_eq!(
+        "Oops: routerify_ng::Error: Something failed",
+        into_text(resp.into_body()).await
+    );
+
+    serve.shutdown();
+
+    let err = Router::<routerify_ng::Error>::builder()
+        .err_handler_with_data(|_: &ErrorFormat| |_: RouteError| async move { Response::new(Full::from("")) })
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("No data of type"), "{}", err);
+}
+
+#[tokio::test]
+async fn can_guard_route_by_local_port() {
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get_guarded(
+            "/admin",
+            |req| req.local_addr().map(|addr| addr.port()) == Some(9000),
+            |_| async move { Ok(Response::new(Full::from("Admin panel"))) },
+        )
+        .build()
+        .unwrap();
+    let builder = RequestServiceBuilder::new(router).unwrap();
+    let remote_addr = "10.0.0.1:50000".parse().unwrap();
+
+    let admin_service = builder.build_with_local_addr(remote_addr, "127.0.0.1:9000".parse().unwrap());
+    let public_service = builder.build_with_local_addr(remote_addr, "127.0.0.1:8080".parse().unwrap());
+    let unknown_service = builder.build(remote_addr);
+
+    let req = || Request::builder().uri("/admin").body(Full::new(Bytes::new())).unwrap();
+    let resp = admin_service.call(req()).await.unwrap();
+    assert_eq!(StatusCode::OK, resp.status());
+    assert_eq!("Admin panel", into_text(resp.into_body()).await);
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        public_service.call(req()).await.unwrap().status()
+    );
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        unknown_service.call(req()).await.unwrap().status()
+    );
+}
+
+#[tokio::test]
+async fn can_extract_trace_context_from_headers() {
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::trace_context())
+        .get("/", |req| async move {
+            let ctx = req.trace_context().unwrap();
+            Ok(Response::new(Full::from(format!(
+                "{} {} {}",
+                ctx.trace_id(),
+                ctx.span_id(),
+                ctx.trace_state().unwrap_or("-")
+            ))))
+        })
+        .build()
+        .unwrap();
+    let service = RequestServiceBuilder::new(router)
+        .unwrap()
+        .build("127.0.0.1:50000".parse().unwrap());
+
+    let req = Request::builder()
+        .uri("/")
+        .header("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        .header("tracestate", "congo=t61rcWkgMzE")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let resp = service.call(req).await.unwrap();
+    assert_eq!(
+        "4bf92f3577b34da6a3ce929d0e0e4736 00f067aa0ba902b7 congo=t61rcWkgMzE",
+        into_text(resp.into_body()).await
+    );
+
+    let req = Request::builder()
+        .uri("/")
+        .header("traceparent", "garbage")
+        .header("tracestate", "congo=t61rcWkgMzE")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let resp = service.call(req).await.unwrap();
+    let text = into_text(resp.into_body()).await;
+    let ids: Vec<&str> = text.split(' ').collect();
+    assert_eq!(32, ids[0].len());
+    assert_eq!(16, ids[1].len());
+    assert_eq!("-", ids[2]);
+}
+
+#[tokio::test]
+async fn can_chain_timeout_after_route() {
+    use std::time::Duration;
+
+    async fn slow(_: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, routerify_ng::Error> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(Response::new(Full::from("Done")))
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/limited", slow)
+        .with_timeout(Duration::from_millis(50))
+        .get_many(["/batch/a", "/batch/b"], slow)
+        .with_timeout(Duration::from_millis(50))
+        .get("/unlimited", slow)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, status) in [
+        ("/limited", 503),
+        ("/batch/a", 503),
+        ("/batch/b", 503),
+        ("/unlimited", 200),
+    ] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status().as_u16(), "unexpected status for {}", path);
+    }
+
+    serve.shutdown();
+
+    let err = Router::<routerify_ng::Error>::builder()
+        .with_timeout(Duration::from_millis(50))
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("must directly follow a call adding routes"),
+        "{}",
+        err
+    );
+
+    // A scope mounts routes with their own options, so that the timeout can't apply to them.
+    let api = Router::<routerify_ng::Error>::builder()
+        .get("/users", slow)
+        .get("/orders", slow)
+        .build()
+        .unwrap();
+    let err = Router::<routerify_ng::Error>::builder()
+        .get("/limited", slow)
+        .scope("/api", api)
+        .with_timeout(Duration::from_millis(50))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("`with_timeout`"), "{}", err);
+
+    // Only the immediately preceding call counts.
+    let err = Router::<routerify_ng::Error>::builder()
+        .get("/limited", slow)
+        .middleware(Middleware::pre(|req| async move { Ok(req) }))
+        .with_timeout(Duration::from_millis(50))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("`with_timeout`"), "{}", err);
+}
+
+#[tokio::test]
+async fn can_chain_meta_and_err_handler_after_route() {
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct Label(&'static str);
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .data(Label("router"))
+        .err_handler(|_| async move {
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Full::from("router error"))
+                .unwrap()
+        })
+        .get("/labeled", |req| async move {
+            Ok(Response::new(Full::from(req.data::<Label>().unwrap().0)))
+        })
+        .with_timeout(Duration::from_secs(5))
+        .with_meta(Label("route"))
+        .get("/unlabeled", |req| async move {
+            Ok(Response::new(Full::from(req.data::<Label>().unwrap().0)))
+        })
+        .get("/failing", |_| async move {
+            Err(routerify_ng::Error::new("Invalid signature"))
+        })
+        .with_err_handler(|err| async move {
+            Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Full::from(err.to_string()))
+                .unwrap()
+        })
+        .get("/broken", |_| async move { Err(routerify_ng::Error::new("Broken")) })
+        .get_many(["/hooks/a", "/hooks/b"], |_| async move {
+            Err(routerify_ng::Error::new("Invalid signature"))
+        })
+        .with_err_handler_with_info(|err, req_info| async move {
+            Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Full::from(format!("{} {}: {}", req_info.method(), req_info.uri(), err)))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (method, path, status, body) in [
+        ("GET", "/labeled", 200, "route"),
+        ("GET", "/unlabeled", 200, "router"),
+        ("GET", "/failing", 401, "routerify_ng::Error: Invalid signature"),
+        ("GET", "/broken", 500, "router error"),
+        (
+            "GET",
+            "/hooks/a",
+            401,
+            "GET /hooks/a: routerify_ng::Error: Invalid signature",
+        ),
+        (
+            "GET",
+            "/hooks/b",
+            401,
+            "GET /hooks/b: routerify_ng::Error: Invalid signature",
+        ),
+    ] {
+        let resp = client
+            .request(serve.new_request(method, path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status().as_u16(), "unexpected status for {}", path);
+        assert_eq!(body, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+
+    let err = Router::<routerify_ng::Error>::builder()
+        .with_meta(Label("orphan"))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("`with_meta`"), "{}", err);
+}
+
+#[cfg(feature = "json-schema")]
+#[tokio::test]
+async fn can_validate_json_body_against_schema() {
+    use serde_json::json;
+
+    #[derive(serde::Deserialize)]
+    struct NewUser {
+        name: String,
+        age: u32,
+    }
+
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 1 },
+            "age": { "type": "integer", "minimum": 18 }
+        },
+        "required": ["name", "age"]
+    });
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .post_json_validated("/users", schema, |_, user: NewUser| async move {
+            Ok(Response::new(Full::from(format!("{} {}", user.name, user.age))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/users")
+                .body(Full::from(r#"{"name": "alice", "age": 30}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!("alice 30", into_text(resp.into_body()).await);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/users")
+                .body(Full::from(r#"{"name": "", "age": 12}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(422, resp.status().as_u16());
+    assert_eq!("application/json", resp.headers()["content-type"]);
+    let body: serde_json::Value = serde_json::from_str(&into_text(resp.into_body()).await).unwrap();
+    let paths = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|err| err["path"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert!(paths.contains(&"/name"), "{}", body);
+    assert!(paths.contains(&"/age"), "{}", body);
+
+    let resp = client
+        .request(serve.new_request("POST", "/users").body(Full::from("{")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(400, resp.status().as_u16());
+
+    serve.shutdown();
+
+    let err = Router::<routerify_ng::Error>::builder()
+        .post_json_validated("/users", json!({ "type": 42 }), |_, _: NewUser| async move {
+            Ok(Response::new(Full::from("unreachable")))
+        })
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid JSON schema"), "{}", err);
+}
+
+#[tokio::test]
+async fn can_remove_labeled_middleware() {
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let auth_calls = calls.clone();
+    let logger_calls = calls.clone();
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware_labeled(
+            "auth",
+            Middleware::pre(move |req| {
+                let calls = auth_calls.clone();
+                async move {
+                    calls.lock().unwrap().push("auth");
+                    Ok(req)
+                }
+            }),
+        )
+        .middleware_labeled(
+            "logger",
+            Middleware::post_with_priority("/*", 5, move |res| {
+                let calls = logger_calls.clone();
+                async move {
+                    calls.lock().unwrap().push("logger");
+                    Ok(res)
+                }
+            })
+            .unwrap(),
+        )
+        .get("/", |_| async move { Ok(Response::new(Full::from("Home"))) })
+        .remove_middleware("auth")
+        .build()
+        .unwrap();
+
+    assert!(router.pre_middleware().is_empty());
+    let post = router.post_middleware();
+    assert_eq!(1, post.len());
+    assert_eq!(Some("logger"), post[0].label());
+    assert_eq!("/*", post[0].path());
+    assert_eq!(5, post[0].priority());
+
+    let service = RequestServiceBuilder::new(router)
+        .unwrap()
+        .build("127.0.0.1:50000".parse().unwrap());
+    let resp = service
+        .call(Request::builder().uri("/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!("Home", into_text(resp.into_body()).await);
+    assert_eq!(vec!["logger"], *calls.lock().unwrap());
+
+    let err = Router::<routerify_ng::Error>::builder()
+        .remove_middleware("missing")
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("`missing`"), "{}", err);
+}
+
+#[cfg(feature = "decompression")]
+#[tokio::test]
+async fn can_decompress_request_bodies() {
+    use flate2::Compression;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .decompress_requests(1024)
+        .post("/echo", |req| async move {
+            let encoding = req.header("content-encoding").unwrap_or("none").to_owned();
+            let length = req.header("content-length").unwrap_or("none").to_owned();
+            let body = req.body_bytes().await?;
+            Ok(Response::new(Full::from(format!(
+                "{} {} {}",
+                encoding,
+                length,
+                String::from_utf8_lossy(&body)
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (encoding, body) in [("gzip", gzip(b"Hello world")), ("deflate", deflate(b"Hello world"))] {
+        let resp = client
+            .request(
+                serve
+                    .new_request("POST", "/echo")
+                    .header("content-encoding", encoding)
+                    .body(Full::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(200, resp.status().as_u16(), "unexpected status for {}", encoding);
+        assert_eq!("none 11 Hello world", into_text(resp.into_body()).await);
+    }
+
+    // A few compressed bytes expanding beyond the limit are rejected.
+    let bomb = gzip(&vec![0; 1024 * 1024]);
+    assert!(bomb.len() < 2048);
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/echo")
+                .header("content-encoding", "gzip")
+                .body(Full::from(bomb))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(413, resp.status().as_u16());
+
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/echo")
+                .header("content-encoding", "gzip")
+                .body(Full::from("not gzip"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(400, resp.status().as_u16());
+
+    serve.shutdown();
+}
+
+#[cfg(feature = "decompression")]
+#[tokio::test]
+async fn can_decompress_deferred_request_bodies() {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .decompress_requests(1024)
+        .defer_body_reading(true)
+        .post("/echo", |req| async move {
+            let encoding = req.header("content-encoding").unwrap_or("none").to_owned();
+            let length = req.header("content-length").unwrap_or("none").to_owned();
+            let body = match req.body_string().await {
+                Ok(body) => body,
+                Err(err) => err.to_string(),
+            };
+            Ok(Response::new(Full::from(format!("{} {} {}", encoding, length, body))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let send = async |body: Vec<u8>| {
+        let resp = client
+            .request(
+                serve
+                    .new_request("POST", "/echo")
+                    .header("content-encoding", "gzip")
+                    .body(Full::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        into_text(resp.into_body()).await
+    };
+
+    assert_eq!("none none Hello world", send(gzip(b"Hello world")).await);
+
+    // The failures of the decoding are reported by the read.
+    let resp = send(gzip(&vec![b'a'; 1024 * 1024])).await;
+    assert!(resp.contains("exceeds the limit of 1024 bytes"), "{}", resp);
+    let resp = send(b"not gzip".to_vec()).await;
+    assert!(resp.contains("Invalid content coding"), "{}", resp);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_tell_secure_requests() {
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+
+    let router = || -> Router<routerify_ng::Error> {
+        Router::builder()
+            .get("/", |req| async move {
+                Ok(Response::new(Full::from(req.is_secure().to_string())))
+            })
+            .build()
+            .unwrap()
+    };
+    let remote_addr = "10.0.0.1:50000".parse().unwrap();
+    let req = |forwarded_proto: Option<&str>| {
+        let mut builder = Request::builder().uri("/");
+        if let Some(proto) = forwarded_proto {
+            builder = builder.header("x-forwarded-proto", proto);
+        }
+        builder.body(Full::new(Bytes::new())).unwrap()
+    };
+
+    let builder = RequestServiceBuilder::new(router()).unwrap();
+    let tls_service = builder.clone().secure(true).build(remote_addr);
+    let plain_service = builder.build(remote_addr);
+
+    let resp = tls_service.call(req(None)).await.unwrap();
+    assert_eq!("true", into_text(resp.into_body()).await);
+    let resp = plain_service.call(req(None)).await.unwrap();
+    assert_eq!("false", into_text(resp.into_body()).await);
+    // The header is ignored unless it's trusted.
+    let resp = plain_service.call(req(Some("https"))).await.unwrap();
+    assert_eq!("false", into_text(resp.into_body()).await);
+
+    let trusting_router = Router::builder()
+        .trust_forwarded_proto(true)
+        .scope("/", router())
+        .build()
+        .unwrap();
+    let service = RequestServiceBuilder::new(trusting_router).unwrap().build(remote_addr);
+    for (proto, expected) in [(Some("https"), "true"), (Some("http, https"), "false"), (None, "false")] {
+        let resp = service.call(req(proto)).await.unwrap();
+        assert_eq!(
+            expected,
+            into_text(resp.into_body()).await,
+            "unexpected result for {:?}",
+            proto
+        );
+    }
+}
+
+#[tokio::test]
+async fn can_add_route_in_finalize_hook() {
+    use hyper::Method;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .param_constraint("id", "[0-9]+")
+        .get("/users", |_| async move { Ok(Response::new(Full::from("Users"))) })
+        .finalize(|router| {
+            router.add_route("/computed", vec![Method::GET], |_| async move {
+                Ok(Response::new(Full::from("Computed")))
+            })?;
+            // The params of the added routes are constrained like the others.
+            router.add_route("/computed/:id", vec![Method::GET], |req| async move {
+                Ok(Response::new(Full::from(format!(
+                    "Computed {}",
+                    req.param("id").unwrap()
+                ))))
+            })
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, status, expected) in [
+        ("/users", 200, "Users"),
+        ("/computed", 200, "Computed"),
+        ("/computed/42", 200, "Computed 42"),
+        ("/computed/abc", 404, ""),
+    ] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status().as_u16(), "unexpected status for {}", path);
+        if status == 200 {
+            assert_eq!(expected, into_text(resp.into_body()).await);
+        }
+    }
+
+    serve.shutdown();
+
+    let err = Router::<routerify_ng::Error>::builder()
+        .finalize(|router| {
+            router.add_route("/users/:id(", vec![Method::GET], |_| async move {
+                Ok(Response::new(Full::from("unreachable")))
+            })
+        })
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("route path"), "{}", err);
+}
+
+#[tokio::test]
+async fn can_send_retry_after_date_in_maintenance() {
+    use hyper::service::Service;
+    use routerify_ng::{Maintenance, RequestServiceBuilder};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let maintenance = Maintenance::new().retry_after(UNIX_EPOCH + Duration::from_secs(1_445_412_480));
+    maintenance.enable();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .maintenance(maintenance)
+        .get("/", |_| async move { Ok(Response::new(Full::from("Home"))) })
+        .build()
+        .unwrap();
+    let service = RequestServiceBuilder::new(router)
+        .unwrap()
+        .build("127.0.0.1:50000".parse().unwrap());
+
+    let resp = service
+        .call(Request::builder().uri("/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, resp.status());
+    assert_eq!("Wed, 21 Oct 2015 07:28:00 GMT", resp.headers()["retry-after"]);
+}
+
+#[tokio::test]
+async fn can_run_task_after_response() {
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+    use std::time::Duration;
+    use tokio::sync::{Notify, oneshot};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let release = Arc::new(Notify::new());
+    let (done_tx, done_rx) = oneshot::channel::<()>();
+    let done_tx = Arc::new(Mutex::new(Some(done_tx)));
+
+    let handler_events = events.clone();
+    let handler_release = release.clone();
+    let post_events = events.clone();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/", move |req| {
+            let events = handler_events.clone();
+            let release = handler_release.clone();
+            let done_tx = done_tx.lock().unwrap().take().unwrap();
+            async move {
+                req.spawn_after_response(async move {
+                    // It would block the response if the task ran before it.
+                    release.notified().await;
+                    events.lock().unwrap().push("task");
+                    let _ = done_tx.send(());
+                });
+                Ok(Response::new(Full::from("Home")))
+            }
+        })
+        .middleware(Middleware::post(move |res| {
+            let events = post_events.clone();
+            async move {
+                events.lock().unwrap().push("post");
+                Ok(res)
+            }
+        }))
+        .build()
+        .unwrap();
+    let service = RequestServiceBuilder::new(router)
+        .unwrap()
+        .build("127.0.0.1:50000".parse().unwrap());
+
+    let resp = tokio::time::timeout(
+        Duration::from_secs(5),
+        service.call(Request::builder().uri("/").body(Full::new(Bytes::new())).unwrap()),
+    )
+    .await
+    .expect("The deferred task delayed the response")
+    .unwrap();
+    assert_eq!("Home", into_text(resp.into_body()).await);
+    assert_eq!(vec!["post"], *events.lock().unwrap());
+
+    release.notify_one();
+    tokio::time::timeout(Duration::from_secs(5), done_rx)
+        .await
+        .expect("The deferred task didn't run")
+        .unwrap();
+    assert_eq!(vec!["post", "task"], *events.lock().unwrap());
+}
+
+#[tokio::test]
+async fn can_share_handler_between_paths() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get_many(["/", "/home"], move |req| {
+            let count = hits.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { Ok(Response::new(Full::from(format!("{} {}", req.uri().path(), count)))) }
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, expected) in [("/", "/ 1"), ("/home", "/home 2"), ("/", "/ 3")] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(200, resp.status().as_u16(), "unexpected status for {}", path);
+        assert_eq!(expected, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_peek_body_in_pre_middleware() {
+    fn sign(body: &[u8]) -> String {
+        body.iter()
+            .fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(*b as u32))
+            .to_string()
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::pre(|req| async move {
+            let body = req.peek_body().expect("The body isn't buffered");
+            if req.header("x-signature") != Some(sign(body).as_str()) {
+                return Err(routerify_ng::Error::new("Invalid signature"));
+            }
+            Ok(req)
+        }))
+        .post("/", |req| async move {
+            let body = req.body_bytes().await?;
+            Ok(Response::new(Full::new(body)))
+        })
+        .err_handler(|err| async move {
+            Response::builder()
+                .status(401)
+                .body(Full::from(err.to_string()))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/")
+                .header("x-signature", sign(b"payload"))
+                .body(Full::from("payload"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!("payload", into_text(resp.into_body()).await);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/")
+                .header("x-signature", sign(b"other"))
+                .body(Full::from("payload"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(401, resp.status().as_u16());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_override_method_of_post_requests() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::method_override())
+        .get("/posts/1", |_| async move { Ok(Response::new(Full::from("Show"))) })
+        .post("/posts/1", |_| async move { Ok(Response::new(Full::from("Create"))) })
+        .delete("/posts/1", |req| async move {
+            Ok(Response::new(Full::from(format!("Delete {}", req.method()))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let requests = [
+        ("POST", Some(("x-http-method-override", "DELETE")), "", "Delete DELETE"),
+        (
+            "POST",
+            Some(("content-type", "application/x-www-form-urlencoded")),
+            "title=a&_method=DELETE",
+            "Delete DELETE",
+        ),
+        ("GET", Some(("x-http-method-override", "DELETE")), "", "Show"),
+        ("POST", None, "_method=DELETE", "Create"),
+    ];
+    for (method, header, body, expected) in requests {
+        let mut builder = serve.new_request(method, "/posts/1");
+        if let Some((name, value)) = header {
+            builder = builder.header(name, value);
+        }
+        let resp = client.request(builder.body(Full::from(body)).unwrap()).await.unwrap();
+        assert_eq!(expected, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_not_acceptable_when_negotiation_fails() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/hello", |req| async move {
+            match req.preferred_language(&["en", "fr"]) {
+                Some(lang) => Ok(Response::new(Full::from(lang.to_owned()))),
+                None => Ok(routerify_ng::not_acceptable(["en", "fr"])),
+            }
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/hello")
+                .header("accept-language", "ja, de;q=0.5")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(406, resp.status().as_u16());
+    assert_eq!("text/plain; charset=utf-8", resp.headers()["content-type"]);
+    assert_eq!("en\nfr\n", into_text(resp.into_body()).await);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_close_connection_after_max_requests() {
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .max_requests_per_connection(2)
+        .get("/", |_| async move { Ok(Response::new(Full::from("Home"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    // The requests are pipelined, so the ones after the limit are sent before the connection closes.
+    let mut stream = tokio::net::TcpStream::connect(serve.addr()).await.unwrap();
+    let request = "GET / HTTP/1.1\r\nhost: localhost\r\n\r\n";
+    stream.write_all(request.repeat(3).as_bytes()).await.unwrap();
+
+    let mut received = String::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut received))
+        .await
+        .expect("The connection wasn't closed")
+        .unwrap();
+    assert_eq!(2, received.matches("HTTP/1.1 200 OK").count());
+    assert_eq!(1, received.matches("connection: close").count());
+    assert!(received.trim_end().ends_with("Home"));
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_proxy_unmatched_requests_to_upstream() {
+    let upstream: Router<routerify_ng::Error> = Router::builder()
+        .post("/api/echo", |req| async move {
+            let body = format!(
+                "{} {} {} {}",
+                req.method(),
+                req.uri(),
+                req.header("x-custom").unwrap_or_default(),
+                String::from_utf8_lossy(&req.body_bytes().await?)
+            );
+            Ok(Response::builder()
+                .status(201)
+                .header("x-upstream", "1")
+                .body(Full::from(body))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+    let upstream = serve(upstream).await;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/local", |_| async move { Ok(Response::new(Full::from("Local"))) })
+        .proxy_fallback(format!("http://{}/api", upstream.addr()))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/local")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("Local", into_text(resp.into_body()).await);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/echo?page=2")
+                .header("x-custom", "value")
+                .body(Full::from("payload"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(201, resp.status().as_u16());
+    assert_eq!("1", resp.headers()["x-upstream"]);
+    assert_eq!("POST /api/echo?page=2 value payload", into_text(resp.into_body()).await);
+
+    upstream.shutdown();
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_bad_gateway_when_upstream_is_down() {
+    // Reserve a port nothing listens on.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let upstream_addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .proxy_fallback(format!("http://{}", upstream_addr))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/anything")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(502, resp.status().as_u16());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_not_modified_since_last_modified() {
+    use std::time::{Duration, SystemTime};
+
+    let last_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/report", move |req| async move {
+            if req.not_modified_since(last_modified) {
+                return Ok(routerify_ng::not_modified(last_modified));
+            }
+            Ok(Response::new(Full::from("Report")))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("Tue, 14 Nov 2023 22:13:20 GMT", 304, ""),
+        ("Wed, 15 Nov 2023 00:00:00 GMT", 304, ""),
+        ("Tue, 14 Nov 2023 22:13:19 GMT", 200, "Report"),
+        ("not a date", 200, "Report"),
+    ];
+    for (if_modified_since, status, body) in cases {
+        let resp = client
+            .request(
+                serve
+                    .new_request("GET", "/report")
+                    .header("if-modified-since", if_modified_since)
+                    .body(Full::new(Bytes::new()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            status,
+            resp.status().as_u16(),
+            "unexpected status for {}",
+            if_modified_since
+        );
+        if status == 304 {
+            assert_eq!("Tue, 14 Nov 2023 22:13:20 GMT", resp.headers()["last-modified"]);
+        }
+        assert_eq!(body, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_set_not_found_handler_per_scope() {
+    let api: Router<routerify_ng::Error> = Router::builder()
+        .get("/users", |_| async move { Ok(Response::new(Full::from("[]"))) })
+        .not_found(|_| async move {
+            Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header("content-type", "application/json")
+                .body(Full::from(r#"{"error":"Not Found"}"#))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .not_found(|_| async move {
+            Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header("content-type", "text/html")
+                .body(Full::from("<h1>Not Found</h1>"))
+                .unwrap())
+        })
+        .scope("/api", api)
+        // A route registered after the scope still takes precedence over its not found handler.
+        .get("/api/health", |_| async move { Ok(Response::new(Full::from("OK"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("/api/users", 200, "[]"),
+        ("/api/health", 200, "OK"),
+        ("/api/missing", 404, r#"{"error":"Not Found"}"#),
+        ("/missing", 404, "<h1>Not Found</h1>"),
+    ];
+    for (path, status, body) in cases {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status().as_u16(), "unexpected status for {}", path);
+        assert_eq!(body, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_reject_method_not_allowed_with_scoped_not_found_handler() {
+    fn api() -> Router<routerify_ng::Error> {
+        Router::builder()
+            .get("/users", |_| async move { Ok(Response::new(Full::from("[]"))) })
+            .not_found(|_| async move {
+                Ok(Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Full::from("api not found"))
+                    .unwrap())
+            })
+            .build()
+            .unwrap()
+    }
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::pre(|req: Request<Full<Bytes>>| async move {
+            req.set_context(req.matched_path().unwrap_or("unmatched").to_owned());
+            Ok(req)
+        }))
+        .scope("/api", api())
+        .middleware(Middleware::post_with_info(
+            |mut res: Response<Full<Bytes>>, req_info: RequestInfo| async move {
+                let pre_route = req_info.context::<String>().unwrap();
+                res.headers_mut().insert("x-pre-route", pre_route.parse().unwrap());
+                Ok(res)
+            },
+        ))
+        .build()
+        .unwrap();
+    let api_serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let resp = client
+        .request(
+            api_serve
+                .new_request("DELETE", "/api/users")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers()["allow"], "GET");
+
+    let resp = client
+        .request(
+            api_serve
+                .new_request("GET", "/api/missing")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()["x-pre-route"], "unmatched");
+    assert_eq!(into_text(resp.into_body()).await, "api not found");
+    api_serve.shutdown();
+
+    // The unmatched requests under the scope are handed over to the fallback router too.
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .scope("/api", api())
+        .fallback_router(
+            Router::builder()
+                .any(|_| async move { Ok(Response::new(Full::from("fallback"))) })
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let fallback_serve = serve(router).await;
+
+    let resp = client
+        .request(
+            fallback_serve
+                .new_request("GET", "/api/missing")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "fallback");
+    fallback_serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_body_as_string() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .post("/", |req| async move {
+            match req.body_string().await {
+                Ok(body) => Ok(Response::new(Full::from(body.to_uppercase()))),
+                Err(err) => Ok(Response::builder()
+                    .status(400)
+                    .body(Full::from(err.to_string()))
+                    .unwrap()),
+            }
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(serve.new_request("POST", "/").body(Full::from("héllo")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(200, resp.status().as_u16());
+    assert_eq!("HÉLLO", into_text(resp.into_body()).await);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/")
+                .body(Full::from(Bytes::from_static(b"h\xffllo")))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(400, resp.status().as_u16());
+    assert!(
+        into_text(resp.into_body())
+            .await
+            .contains("Couldn't decode the request body as UTF-8")
+    );
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_emit_structured_log_records() {
+    use routerify_ng::LogField;
+    use std::collections::HashMap;
+
+    let records = Arc::new(Mutex::new(Vec::<HashMap<String, String>>::new()));
+    let sink_records = records.clone();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::structured_log(
+            [
+                LogField::Method,
+                LogField::MatchedRoute,
+                LogField::Status,
+                LogField::Duration,
+                LogField::RequestId,
+            ],
+            move |record| {
+                let record = record
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.clone()))
+                    .collect();
+                sink_records.lock().unwrap().push(record);
+            },
+        ))
+        .get("/users/:id", |_| async move { Ok(Response::new(Full::from("User"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/users/42")
+                .header("x-request-id", "abc-123")
+                .header("user-agent", "test-agent")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("User", into_text(resp.into_body()).await);
+
+    let records = records.lock().unwrap();
+    assert_eq!(1, records.len());
+    let record = &records[0];
+    assert_eq!("GET", record["method"]);
+    assert_eq!("/users/:id", record["matched_route"]);
+    assert_eq!("200", record["status"]);
+    assert_eq!("abc-123", record["request_id"]);
+    assert!(record["duration_ms"].parse::<f64>().is_ok());
+    assert!(!record.contains_key("path"));
+    assert!(!record.contains_key("user_agent"));
+    assert!(!record.contains_key("remote_addr"));
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_run_blocking_handlers_without_stalling_others() {
+    use std::time::{Duration, Instant};
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get_blocking("/slow", |_| {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(Response::new(Full::from("Slow")))
+        })
+        .get("/fast", |_| async move { Ok(Response::new(Full::from("Fast"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let slow = tokio::spawn(client.request(serve.new_request("GET", "/slow").body(Full::new(Bytes::new())).unwrap()));
+    // Let the blocking handler start first.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = Instant::now();
+    let resp = client
+        .request(serve.new_request("GET", "/fast").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!("Fast", into_text(resp.into_body()).await);
+    assert!(
+        started.elapsed() < Duration::from_millis(400),
+        "the fast handler was stalled"
+    );
+
+    let resp = slow.await.unwrap().unwrap();
+    assert_eq!("Slow", into_text(resp.into_body()).await);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_render_problem_json_errors() {
+    #[derive(Debug)]
+    struct NotFound(String);
+
+    impl std::fmt::Display for NotFound {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "No user \"{}\"", self.0)
+        }
+    }
+
+    impl std::error::Error for NotFound {}
+
+    impl routerify_ng::ErrorStatus for NotFound {
+        fn status(&self) -> StatusCode {
+            StatusCode::NOT_FOUND
+        }
+    }
+
+    let router: Router<NotFound> = Router::builder()
+        .problem_json_errors()
+        .err_status::<NotFound>()
+        .get("/users/:id", |req| async move {
+            Err(NotFound(req.param("id").unwrap().to_owned()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/users/42")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(404, resp.status().as_u16());
+    assert_eq!("application/problem+json", resp.headers()["content-type"]);
+
+    let problem: serde_json::Value = serde_json::from_str(&into_text(resp.into_body()).await).unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "type": "about:blank",
+            "title": "Not Found",
+            "status": 404,
+            "detail": "No user \"42\"",
+        }),
+        problem
+    );
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_match_custom_routes() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .custom_route(
+            |req| {
+                req.headers().contains_key("x-tenant") && req.uri().query().is_some_and(|query| query.contains("v=2"))
+            },
+            |req| async move { Ok(Response::new(Full::from(format!("Custom {}", req.uri().path())))) },
+        )
+        .get("/items", |_| async move { Ok(Response::new(Full::from("Items"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("/anything?v=2", Some("acme"), 200, "Custom /anything"),
+        ("/anything?v=2", None, 404, ""),
+        ("/anything", Some("acme"), 404, ""),
+        // The regular routes are tried first.
+        ("/items?v=2", Some("acme"), 200, "Items"),
+    ];
+    for (uri, tenant, status, body) in cases {
+        let mut builder = serve.new_request("GET", uri);
+        if let Some(tenant) = tenant {
+            builder = builder.header("x-tenant", tenant);
+        }
+        let resp = client
+            .request(builder.body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            status,
+            resp.status().as_u16(),
+            "unexpected status for {} {:?}",
+            uri,
+            tenant
+        );
+        assert_eq!(body, into_text(resp.into_body()).await);
+    }
+
+    serve.shutdown();
+}
+
+#[cfg(feature = "client-cert")]
+#[tokio::test]
+async fn can_read_client_cert_of_mtls_connections() {
+    use hyper_util::rt::TokioIo;
+    use hyper_util::server::conn::auto::Builder;
+    use routerify_ng::{ClientCert, RequestServiceBuilder};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+    use tokio_rustls::rustls::server::WebPkiClientVerifier;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig, crypto};
+    use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+    let fixture = |name: &str| format!("{}/tests/fixtures/mtls/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let cert = |name: &str| CertificateDer::from_pem_file(fixture(name)).unwrap();
+    let key = |name: &str| PrivateKeyDer::from_pem_file(fixture(name)).unwrap();
+
+    let provider = Arc::new(crypto::ring::default_provider());
+    let mut roots = RootCertStore::empty();
+    roots.add(cert("ca.pem")).unwrap();
+    let roots = Arc::new(roots);
+
+    let server_config = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(
+            WebPkiClientVerifier::builder_with_provider(roots.clone(), provider.clone())
+                .build()
+                .unwrap(),
+        )
+        .with_single_cert(vec![cert("server.pem")], key("server.key"))
+        .unwrap();
+    let client_config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_client_auth_cert(vec![cert("client.pem")], key("client.key"))
+        .unwrap();
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/whoami", |req| async move {
+            let cert = req.client_cert().expect("No client certificate");
+            Ok(Response::new(Full::from(format!(
+                "{} {}",
+                cert.subject(),
+                cert.subject_alt_names().join(",")
+            ))))
+        })
+        .build()
+        .unwrap();
+    let builder = RequestServiceBuilder::new(router).unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(server_config));
+    tokio::spawn(async move {
+        let (stream, remote_addr) = listener.accept().await.unwrap();
+        let stream = acceptor.accept(stream).await.unwrap();
+
+        let mut service = builder.build(remote_addr);
+        if let Some(certs) = stream.get_ref().1.peer_certificates() {
+            let client_cert = ClientCert::from_der_chain(certs.iter().map(|cert| cert.to_vec())).unwrap();
+            service = service.with_client_cert(client_cert);
+        }
+
+        Builder::new(TokioExecutor::new())
+            .serve_connection(TokioIo::new(stream), service)
+            .await
+            .unwrap();
+    });
+
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let mut stream = TlsConnector::from(Arc::new(client_config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    stream
+        .write_all(b"GET /whoami HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut received = Vec::new();
+    // The server may close the connection without the TLS close notification.
+    let _ = stream.read_to_end(&mut received).await;
+
+    let received = String::from_utf8(received).unwrap();
+    assert!(received.starts_with("HTTP/1.1 200 OK"), "{}", received);
+    assert!(received.ends_with("CN=billing-service, O=Routerify billing.internal,spiffe://example.org/billing"));
+}
+
+#[tokio::test]
+async fn can_skip_automatic_routes_with_minimal() {
+    use hyper::Method;
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .minimal()
+        .get("/", |_| async move { Ok(Response::new(Full::from("home"))) })
+        .get("/fail", |_| async move {
+            Err(routerify_ng::Error::new("Something went wrong"))
+        })
+        .build()
+        .unwrap();
+    let service = RequestServiceBuilder::new(router)
+        .unwrap()
+        .build("127.0.0.1:0".parse().unwrap());
+
+    let call = |method: Method, path: &'static str| {
+        service.call(
+            Request::builder()
+                .method(method)
+                .uri(path)
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+    };
+
+    let resp = call(Method::GET, "/").await.unwrap();
+    assert_eq!("home", into_text(resp.into_body()).await);
+
+    // Neither the default 404 route nor the global OPTIONS route answers them, so that they get a bare 404.
+    for (method, path) in [(Method::GET, "/missing"), (Method::OPTIONS, "/")] {
+        let resp = call(method, path).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+        assert!(resp.headers().get(hyper::header::CONTENT_TYPE).is_none());
+        assert_eq!("", into_text(resp.into_body()).await);
+    }
+
+    // The error isn't turned into a 500 response by the default error handler.
+    let err = call(Method::GET, "/fail").await.unwrap_err();
+    assert!(err.to_string().contains("Something went wrong"));
+}
+
+#[tokio::test]
+async fn can_merge_path_and_query_params() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/users/:id", |req| async move {
+            let params = req.all_params();
+            Ok(Response::new(Full::from(format!(
+                "{} {}",
+                params.get("id").unwrap(),
+                params.get("extra").unwrap()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/users/42?id=query&extra=1")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("42 1", into_text(resp.into_body()).await);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_parse_typed_route_params() {
+    let router: Router<routerify_ng::ParamError> = Router::builder()
+        .get("/users/:userId", |req| async move {
+            let user_id = req.param_as::<u64>("userId")?;
+            Ok(Response::new(Full::from(format!("User ID: {}", user_id))))
+        })
+        .get("/books/:bookId", |req| async move {
+            let user_id = req.param_as::<u64>("userId")?;
+            Ok(Response::new(Full::from(format!("User ID: {}", user_id))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let get =
+        |path: &'static str| client.request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap());
+
+    let resp = get("/users/42").await.unwrap();
+    assert_eq!("User ID: 42", into_text(resp.into_body()).await);
+
+    assert_eq!(StatusCode::BAD_REQUEST, get("/users/alice").await.unwrap().status());
+    assert_eq!(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        get("/books/7").await.unwrap().status()
+    );
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_method_not_allowed_with_allow_header() {
+    use hyper::header::ALLOW;
+
+    let api = Router::builder()
+        .put(
+            "/users/:id",
+            |_| async move { Ok(Response::new(Full::from("Updated"))) },
+        )
+        .delete(
+            "/users/:id",
+            |_| async move { Ok(Response::new(Full::from("Deleted"))) },
+        )
+        .build()
+        .unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get(
+            "/api/users/:id",
+            |_| async move { Ok(Response::new(Full::from("User"))) },
+        )
+        .post(
+            "/api/users",
+            |_| async move { Ok(Response::new(Full::from("Created"))) },
+        )
+        .scope("/api", api)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/api/users/7")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::METHOD_NOT_ALLOWED, resp.status());
+    assert_eq!("GET, PUT, DELETE", resp.headers()[ALLOW]);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/api/users")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::METHOD_NOT_ALLOWED, resp.status());
+    assert_eq!("POST", resp.headers()[ALLOW]);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/api/books")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, resp.status());
+    assert!(!resp.headers().contains_key(ALLOW));
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_apply_trailing_slash_policies() {
+    use hyper::header::LOCATION;
+    use hyper::service::Service;
+    use routerify_ng::{RequestServiceBuilder, TrailingSlashPolicy};
+
+    async fn call(policy: TrailingSlashPolicy, path: &'static str) -> (StatusCode, Option<String>, String) {
+        let router: Router<routerify_ng::Error> = Router::builder()
+            .trailing_slash(policy)
+            .get("/about", |_| async move { Ok(Response::new(Full::from("about"))) })
+            .get("/docs/", |_| async move { Ok(Response::new(Full::from("docs"))) })
+            .get("/files/*", |req| async move {
+                Ok(Response::new(Full::from(req.param("*").unwrap().clone())))
+            })
+            .build()
+            .unwrap();
+        let service = RequestServiceBuilder::new(router)
+            .unwrap()
+            .build("127.0.0.1:0".parse().unwrap());
+
+        let resp = service
+            .call(Request::builder().uri(path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        let location = resp.headers().get(LOCATION).map(|val| val.to_str().unwrap().to_owned());
+        (resp.status(), location, into_text(resp.into_body()).await)
+    }
+
+    let ok = |body: &str| (StatusCode::OK, None, body.to_owned());
+    let not_found = (StatusCode::NOT_FOUND, None, String::new());
+    let redirect = |location: &str| (StatusCode::PERMANENT_REDIRECT, Some(location.to_owned()), String::new());
+
+    assert_eq!(ok("about"), call(TrailingSlashPolicy::Merge, "/about/").await);
+    assert_eq!(ok("docs"), call(TrailingSlashPolicy::Merge, "/docs").await);
+
+    assert_eq!(ok("about"), call(TrailingSlashPolicy::Strict, "/about").await);
+    assert_eq!(not_found, call(TrailingSlashPolicy::Strict, "/about/").await);
+    assert_eq!(ok("docs"), call(TrailingSlashPolicy::Strict, "/docs/").await);
+    assert_eq!(not_found, call(TrailingSlashPolicy::Strict, "/docs").await);
+    assert_eq!(ok("a.txt/"), call(TrailingSlashPolicy::Strict, "/files/a.txt/").await);
+
+    assert_eq!(
+        redirect("/about?lang=en"),
+        call(TrailingSlashPolicy::RedirectToNoSlash, "/about/?lang=en").await
+    );
+    assert_eq!(
+        ok("about"),
+        call(TrailingSlashPolicy::RedirectToNoSlash, "/about").await
+    );
+    assert_eq!(
+        redirect("/docs/"),
+        call(TrailingSlashPolicy::RedirectToSlash, "/docs").await
+    );
+    assert_eq!(
+        redirect("/files/a.txt/"),
+        call(TrailingSlashPolicy::RedirectToSlash, "/files/a.txt").await
+    );
+    assert_eq!(not_found, call(TrailingSlashPolicy::RedirectToSlash, "/").await);
+}
+
+#[tokio::test]
+async fn can_override_global_options_route_per_path() {
+    let api = Router::builder()
+        .options("/users", |_| async move {
+            Ok(Response::builder()
+                .header("access-control-allow-methods", "GET, POST")
+                .body(Full::from("preflight"))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/users", |_| async move { Ok(Response::new(Full::from("users"))) })
+        .scope("/api", api)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let resp = client
+        .request(
+            serve
+                .new_request("OPTIONS", "/api/users")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, resp.status());
+    assert_eq!("GET, POST", resp.headers()["access-control-allow-methods"]);
+    assert_eq!("preflight", into_text(resp.into_body()).await);
+
+    let resp = client
+        .request(
+            serve
+                .new_request("OPTIONS", "/users")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::NO_CONTENT, resp.status());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_handle_errors_with_nearest_scope_err_handler() {
+    fn err_handler(
+        name: &'static str,
+    ) -> impl Fn(RouteError) -> std::future::Ready<Response<Full<Bytes>>> + Send + Sync + 'static {
+        move |err| {
+            std::future::ready(
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Full::from(format!("{}: {}", name, err)))
+                    .unwrap(),
+            )
+        }
+    }
+
+    let v1 = Router::builder()
+        .get("/fail", |_| async move { Err(routerify_ng::Error::new("v1")) })
+        .build()
+        .unwrap();
+    let api = Router::builder()
+        .middleware(Middleware::pre(|req| async move {
+            if req.uri().path().ends_with("/pre") {
+                return Err(routerify_ng::Error::new("pre"));
+            }
+            Ok(req)
+        }))
+        .get("/fail", |_| async move { Err(routerify_ng::Error::new("api")) })
+        .get("/pre", |_| async move { Ok(Response::new(Full::from("unreachable"))) })
+        .scope("/v1", v1)
+        .err_handler(err_handler("json"))
+        .build()
+        .unwrap();
+    let admin = Router::builder()
+        .get("/fail", |_| async move { Err(routerify_ng::Error::new("admin")) })
+        .err_handler(err_handler("html"))
+        .build()
+        .unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/fail", |_| async move { Err(routerify_ng::Error::new("root")) })
+        .scope("/api", api)
+        .scope("/admin", admin)
+        .err_handler(err_handler("root"))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    for (path, expected) in [
+        ("/fail", "root: routerify_ng::Error: root"),
+        ("/api/fail", "json: routerify_ng::Error: api"),
+        ("/api/pre", "json: routerify_ng::Error: pre"),
+        ("/api/v1/fail", "json: routerify_ng::Error: v1"),
+        ("/admin/fail", "html: routerify_ng::Error: admin"),
+    ] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, resp.status());
+        assert_eq!(expected, into_text(resp.into_body()).await, "{}", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_wrap_route_handler_with_around_middleware() {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let record = |order: &Arc<Mutex<Vec<&'static str>>>, step: &'static str| order.lock().unwrap().push(step);
+
+    let (o1, o2, o3, o4, o5) = (
+        order.clone(),
+        order.clone(),
+        order.clone(),
+        order.clone(),
+        order.clone(),
+    );
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::pre(move |req| {
+            record(&o1, "pre");
+            async move { Ok(req) }
+        }))
+        .middleware(Middleware::around(move |req, next| {
+            let order = o2.clone();
+            async move {
+                record(&order, "outer before");
+                let mut res = next.run(req).await;
+                record(&order, "outer after");
+                res.headers_mut()
+                    .insert("x-wrapped", hyper::header::HeaderValue::from_static("outer"));
+                Ok(res)
+            }
+        }))
+        .middleware(Middleware::around(move |req, next| {
+            let order = o3.clone();
+            async move {
+                if req.uri().path() == "/blocked" {
+                    return Ok(Response::builder()
+                        .status(StatusCode::FORBIDDEN)
+                        .body(Full::default())
+                        .unwrap());
+                }
+                record(&order, "inner before");
+                let res = next.run(req).await;
+                record(&order, "inner after");
+                Ok(res)
+            }
+        }))
+        .middleware(Middleware::post(move |res| {
+            record(&o4, "post");
+            async move { Ok(res) }
+        }))
+        .get("/", move |_| {
+            record(&o5, "handler");
+            async move { Ok(Response::new(Full::from("home"))) }
+        })
+        .get("/fail", |_| async move { Err(routerify_ng::Error::new("failed")) })
+        .get(
+            "/blocked",
+            |_| async move { Ok(Response::new(Full::from("unreachable"))) },
+        )
+        .err_handler(|err| async move {
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Full::from(err.to_string()))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, resp.status());
+    assert_eq!("outer", resp.headers()["x-wrapped"]);
+    assert_eq!("home", into_text(resp.into_body()).await);
+    assert_eq!(
+        vec![
+            "pre",
+            "outer before",
+            "inner before",
+            "handler",
+            "inner after",
+            "outer after",
+            "post"
+        ],
+        *order.lock().unwrap()
+    );
+
+    // The handler error is rendered by the error handler before reaching the around middlewares.
+    let resp = client
+        .request(serve.new_request("GET", "/fail").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, resp.status());
+    assert_eq!("outer", resp.headers()["x-wrapped"]);
+    assert_eq!("routerify_ng::Error: failed", into_text(resp.into_body()).await);
+
+    // An around middleware can respond without running the rest of the chain.
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/blocked")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::FORBIDDEN, resp.status());
+    assert_eq!("outer", resp.headers()["x-wrapped"]);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_query_params() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/search", |req| async move {
+            let mut tags = req.queries().remove("tag").unwrap_or_default();
+            tags.sort();
+            Ok(Response::new(Full::from(format!(
+                "{:?} {:?} {:?}",
+                req.query("q"),
+                req.query("missing"),
+                tags
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/search?q=hyper%20router+rs&tag=b&tag=a")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        r#"Some("hyper router rs") None ["a", "b"]"#,
+        into_text(resp.into_body()).await
+    );
+
+    serve.shutdown();
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn can_deserialize_query_string() {
+    #[derive(serde::Deserialize)]
+    struct Pagination {
+        page: u32,
+        per_page: Option<u32>,
+    }
+
+    let router: Router<routerify_ng::QueryError> = Router::builder()
+        .get("/posts", |req| async move {
+            let pagination = req.query_as::<Pagination>()?;
+            Ok(Response::new(Full::from(format!(
+                "{} {:?}",
+                pagination.page, pagination.per_page
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    for (path, status, body) in [
+        ("/posts?page=2&per_page=20", StatusCode::OK, "2 Some(20)"),
+        ("/posts?page=3", StatusCode::OK, "3 None"),
+        ("/posts", StatusCode::BAD_REQUEST, ""),
+        ("/posts?page=two", StatusCode::BAD_REQUEST, ""),
+    ] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status(), "{}", path);
+        assert_eq!(body, into_text(resp.into_body()).await, "{}", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_cookies() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/", |req| async move {
+            let session = req.cookie("session").map(|c| c.value().to_owned());
+            let names = req.cookies().iter().map(|c| c.name().to_owned()).collect::<Vec<_>>();
+            Ok(Response::new(Full::from(format!("{:?} {:?}", session, names))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/")
+                .header("cookie", "session=abc%3D%3D; theme=dark")
+                .header("cookie", "lang=en")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        r#"Some("abc==") ["session", "theme", "lang"]"#,
+        into_text(resp.into_body()).await
+    );
+
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!("None []", into_text(resp.into_body()).await);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_limit_request_body_size() {
+    use hyper::service::Service;
+    use routerify_ng::RequestServiceBuilder;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    let router = || -> Router<routerify_ng::Error> {
+        Router::builder()
+            .body_limit(8)
+            .post("/echo", |req| async move {
+                Ok(Response::new(Full::new(req.body_bytes().await?)))
+            })
+            .build()
+            .unwrap()
+    };
+    let serve = serve(router()).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    for (body, status) in [
+        ("12345678", StatusCode::OK),
+        ("123456789", StatusCode::PAYLOAD_TOO_LARGE),
+    ] {
+        let resp = client
+            .request(serve.new_request("POST", "/echo").body(Full::from(body)).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status(), "{}", body);
+    }
+
+    // A chunked body without the `Content-Length` is rejected once it's read past the limit.
+    let mut stream = TcpStream::connect(serve.addr()).await.unwrap();
+    stream
+        .write_all(b"POST /echo HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n5\r\n12345\r\n5\r\n67890\r\n0\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await.unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).starts_with("HTTP/1.1 413 Payload Too Large"));
+
+    serve.shutdown();
+
+    // The buffered body of the request service is checked too.
+    let service = RequestServiceBuilder::new(router())
+        .unwrap()
+        .build("127.0.0.1:0".parse().unwrap());
+    let resp = service
+        .call(
+            Request::builder()
+                .method("POST")
+                .uri("/echo")
+                .body(Full::from("123456789"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, resp.status());
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn can_read_json_body() {
+    #[derive(serde::Deserialize)]
+    struct NewUser {
+        name: String,
+    }
+
+    let router: Router<routerify_ng::BodyError> = Router::builder()
+        .body_limit(32)
+        .defer_body_reading(true)
+        .post("/users", |req| async move {
+            let user = req.json::<NewUser>().await?;
+            Ok(Response::new(Full::from(user.name)))
+        })
+        .err_handler(|err| async move {
+            let body = err.downcast_ref::<routerify_ng::BodyError>().unwrap().to_string();
+            Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Full::from(body))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    for (body, status, expected) in [
+        (r#"{"name":"Alice"}"#, StatusCode::OK, "Alice"),
+        ("", StatusCode::BAD_REQUEST, "The request body is empty"),
+        (
+            r#"{"id":1}"#,
+            StatusCode::BAD_REQUEST,
+            "Couldn't deserialize the request body: missing field `name` at line 1 column 8",
+        ),
+    ] {
+        let resp = client
+            .request(serve.new_request("POST", "/users").body(Full::from(body)).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status(), "{}", body);
+        assert_eq!(expected, into_text(resp.into_body()).await, "{}", body);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn does_not_limit_request_body_size_by_default() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .post("/echo", |req| async move {
+            Ok(Response::new(Full::new(req.body_bytes().await?)))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let body = "x".repeat(4 * 1024 * 1024);
+    let resp = client
+        .request(
+            serve
+                .new_request("POST", "/echo")
+                .body(Full::from(body.clone()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, resp.status());
+    assert_eq!(body.len(), into_text(resp.into_body()).await.len());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_wait_for_in_flight_requests_on_shutdown() {
+    use hyper::service::Service;
+    use std::time::Duration;
+    use tokio::sync::Notify;
+
+    let release = Arc::new(Notify::new());
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/slow", {
+            let release = release.clone();
+            move |_| {
+                let release = release.clone();
+                async move {
+                    release.notified().await;
+                    Ok(Response::new(Full::from("done")))
+                }
+            }
+        })
+        .build()
+        .unwrap();
+    let (service, shutdown) = routerify_ng::RouterService::new(router).unwrap().graceful();
+    let request_service = service
+        .into_make_service()
+        .call("127.0.0.1:8080".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(0, shutdown.in_flight());
+
+    let req = Request::builder().uri("/slow").body(Full::new(Bytes::new())).unwrap();
+    let resp = tokio::spawn(request_service.call(req));
+    while shutdown.in_flight() == 0 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(1, shutdown.in_flight());
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), shutdown.shutdown())
+            .await
+            .is_err()
+    );
+
+    release.notify_one();
+    tokio::time::timeout(Duration::from_secs(1), shutdown.shutdown())
+        .await
+        .expect("in-flight requests weren't drained");
+    assert_eq!(0, shutdown.in_flight());
+    assert_eq!("done", into_text(resp.await.unwrap().unwrap().into_body()).await);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn can_serve_over_unix_socket() {
+    use hyper::service::Service;
+    use hyper_util::rt::TokioIo;
+    use hyper_util::server::conn::auto::Builder;
+    use routerify_ng::{RemoteAddr, RouterService};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{UnixListener, UnixStream};
+
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/", |req| async move {
+            let body = match req.peer_addr() {
+                RemoteAddr::Unix(path) => format!("unix {:?} {}", path, req.remote_addr()),
+                RemoteAddr::Tcp(addr) => format!("tcp {}", addr),
+            };
+            Ok(Response::new(Full::from(body)))
+        })
+        .build()
+        .unwrap();
+    let service = RouterService::new(router).unwrap();
+
+    let path = std::env::temp_dir().join(format!("routerify-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let request_service = service.call(&stream).await.unwrap();
+        Builder::new(TokioExecutor::new())
+            .serve_connection(TokioIo::new(stream), request_service)
+            .await
+            .unwrap();
+    });
+
+    let mut stream = UnixStream::connect(&path).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).await.unwrap();
+    let resp = String::from_utf8(buf).unwrap();
+    assert!(resp.starts_with("HTTP/1.1 200 OK"));
+    assert!(resp.ends_with("unix None 0.0.0.0:0"));
+
+    server.await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn can_resolve_client_addr_behind_trusted_proxy() {
+    use routerify_ng::TrustConfig;
+
+    fn router(config: Option<TrustConfig>) -> Router<routerify_ng::Error> {
+        let mut builder = Router::builder();
+        if let Some(config) = config {
+            builder = builder.trust_proxy(config);
+        }
+        builder
+            .get("/", |req| async move {
+                Ok(Response::new(Full::from(req.client_addr().to_string())))
+            })
+            .build()
+            .unwrap()
+    }
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        (Some(TrustConfig::new().proxy("127.0.0.0/8")), "203.0.113.7"),
+        (Some(TrustConfig::new().proxy("10.0.0.0/8")), "127.0.0.1"),
+        (None, "127.0.0.1"),
+    ];
+    for (config, expected) in cases {
+        let serve = serve(router(config)).await;
+        let resp = client
+            .request(
+                serve
+                    .new_request("GET", "/")
+                    .header("x-forwarded-for", "198.51.100.1, 203.0.113.7")
+                    // The proxies maintain only `X-Forwarded-For`, so that the client's own `Forwarded` is ignored.
+                    .header("forwarded", "for=1.2.3.4")
+                    .body(Full::new(Bytes::new()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(expected, into_text(resp.into_body()).await);
+        serve.shutdown();
+    }
+
+    let err = Router::<routerify_ng::Error>::builder()
+        .trust_proxy(TrustConfig::new().proxy("10.0.0.0/40"))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("10.0.0.0/40"));
+}
+
+#[tokio::test]
+async fn can_map_streamed_response_frames_in_post_middleware() {
+    use futures::stream;
+    use http_body_util::StreamBody;
+    use hyper::body::Frame;
+    use routerify_ng::body::{BoxBody, Event, Sse};
+    use std::convert::Infallible;
+
+    let router: Router<Infallible, BoxBody> = Router::builder()
+        .middleware(
+            Middleware::post_map_frames("/mapped/*", |chunk: Bytes| {
+                Bytes::from(format!("[{}]", String::from_utf8_lossy(&chunk)))
+            })
+            .unwrap(),
+        )
+        .get("/mapped/chunks", |_| async move {
+            let chunks =
+                stream::iter(["first", "second"].map(|chunk| Ok::<_, Infallible>(Frame::data(Bytes::from(chunk)))));
+            Ok(Response::new(BoxBody::new(StreamBody::new(chunks))))
+        })
+        .get("/mapped/events", |_| async move {
+            let events = stream::iter(vec![Event::default().data("first")]);
+            Ok(Sse::new(events).into_response().map(BoxBody::new))
+        })
+        .get("/plain", |_| async move {
+            Ok(Response::new(BoxBody::from(Bytes::from("plain"))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    for (path, body) in [
+        ("/mapped/chunks", "[first][second]"),
+        ("/mapped/events", "[data: first\n\n]"),
+        ("/plain", "plain"),
+    ] {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(body, into_text(resp.into_body()).await, "unexpected body for {}", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_stream_responses_with_boxed_bodies() {
+    use futures::stream;
+    use routerify_ng::body::{BoxBody, Event, Sse};
+    use std::convert::Infallible;
+
+    let router: Router<Infallible, BoxBody> = Router::builder()
+        .get("/", |_| async move {
+            Ok(Response::new(BoxBody::from(Bytes::from("Home page"))))
+        })
+        .get("/events", |_| async move {
+            let events = stream::iter(vec![Event::default().data("first"), Event::default().data("second")]);
+            Ok(Sse::new(events).into_response().map(BoxBody::new))
+        })
+        .middleware(Middleware::post(|mut res: Response<BoxBody>| async move {
+            res.headers_mut().insert("x-post", "1".parse().unwrap());
+            Ok(res)
+        }))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/events")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    assert_eq!(resp.headers()["x-post"], "1");
+    assert_eq!(into_text(resp.into_body()).await, "data: first\n\ndata: second\n\n");
+
+    let resp = client
+        .request(serve.new_request("GET", "/").body(Full::new(Bytes::new())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["content-length"], "9");
+    assert_eq!(into_text(resp.into_body()).await, "Home page");
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/missing")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_constrain_params_inline() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .param_constraint("id", "[a-z]+")
+        .get(r"/users/:id(\d+)/books/:book", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "user {} book {}",
+                req.param("id").unwrap(),
+                req.param("book").unwrap()
+            ))))
+        })
+        .get("/users/:name", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "name {}",
+                req.param("name").unwrap()
+            ))))
+        })
+        .get(r"/files/:file([^/]+\.(?:txt|md))", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "file {}",
+                req.param("file").unwrap()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("/users/42/books/dune", StatusCode::OK, "user 42 book dune"),
+        ("/users/abc/books/dune", StatusCode::NOT_FOUND, ""),
+        ("/users/abc", StatusCode::OK, "name abc"),
+        ("/files/notes.md", StatusCode::OK, "file notes.md"),
+        ("/files/notes.rs", StatusCode::NOT_FOUND, ""),
+    ];
+    for (path, status, text) in cases {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status(), "unexpected status for {}", path);
+        if status == StatusCode::OK {
+            assert_eq!(text, into_text(resp.into_body()).await);
+        }
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_match_optional_params() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get(r"/files/:name?", |req| async move {
+            Ok(Response::new(Full::from(format!("file {:?}", req.param("name")))))
+        })
+        .get(r"/users/:id([0-9]+)?/books/:book", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "user {:?} book {}",
+                req.param("id"),
+                req.param("book").unwrap()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("/files", StatusCode::OK, "file None"),
+        ("/files/report.pdf", StatusCode::OK, "file Some(\"report.pdf\")"),
+        ("/files/a/b", StatusCode::NOT_FOUND, ""),
+        ("/users/42/books/dune", StatusCode::OK, "user Some(\"42\") book dune"),
+        ("/users/books/dune", StatusCode::OK, "user None book dune"),
+        ("/users/abc/books/dune", StatusCode::NOT_FOUND, ""),
+    ];
+    for (path, status, text) in cases {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status(), "unexpected status for {}", path);
+        if status == StatusCode::OK {
+            assert_eq!(text, into_text(resp.into_body()).await);
+        }
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_capture_named_globs() {
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .get("/assets/*path", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "asset {}",
+                req.param("path").unwrap()
+            ))))
+        })
+        .get("/repos/:repo/*rest/raw", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "repo {} file {}",
+                req.param("repo").unwrap(),
+                req.param("rest").unwrap()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("/assets/css/main.css", StatusCode::OK, "asset css/main.css"),
+        ("/assets/", StatusCode::OK, "asset "),
+        ("/assets/css/", StatusCode::OK, "asset css"),
+        (
+            "/repos/routerify/src/lib.rs/raw",
+            StatusCode::OK,
+            "repo routerify file src/lib.rs",
+        ),
+        ("/repos/routerify/src/lib.rs", StatusCode::NOT_FOUND, ""),
+    ];
+    for (path, status, text) in cases {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, resp.status(), "unexpected status for {}", path);
+        if status == StatusCode::OK {
+            assert_eq!(text, into_text(resp.into_body()).await);
+        }
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_serve_static_files() {
+    use routerify_ng::body::BoxBody;
+
+    let dir = std::env::temp_dir().join(format!("routerify_ng_static_files_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("css")).unwrap();
+    std::fs::write(dir.join("index.html"), "<h1>Home</h1>").unwrap();
+    std::fs::write(dir.join("css/main.css"), "body {}").unwrap();
+
+    let router: Router<routerify_ng::Error, BoxBody> =
+        Router::builder().static_files("/assets/", dir.clone()).build().unwrap();
+    let serve = serve(router).await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let request = |method: &str, path: &str| serve.new_request(method, path).body(Full::new(Bytes::new())).unwrap();
+
+    let resp = client.request(request("GET", "/assets/css/main.css")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/css; charset=utf-8");
+    assert_eq!(resp.headers()["content-length"], "7");
+    let etag = resp.headers()["etag"].clone();
+    let last_modified = resp.headers()["last-modified"].clone();
+    assert_eq!(into_text(resp.into_body()).await, "body {}");
+
+    let resp = client.request(request("GET", "/assets/")).await.unwrap();
+    assert_eq!(resp.headers()["content-type"], "text/html; charset=utf-8");
+    assert_eq!(into_text(resp.into_body()).await, "<h1>Home</h1>");
+
+    let resp = client.request(request("HEAD", "/assets/css/main.css")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-length"], "7");
+
+    let mut req = request("GET", "/assets/css/main.css");
+    req.headers_mut().insert("if-none-match", etag);
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+
+    let mut req = request("GET", "/assets/css/main.css");
+    req.headers_mut().insert("if-modified-since", last_modified);
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+
+    let mut req = request("GET", "/assets/css/main.css");
+    req.headers_mut().insert("if-none-match", "\"stale\"".parse().unwrap());
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = client.request(request("GET", "/assets/%2e%2e/secret")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = client.request(request("GET", "/assets/missing.js")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = client.request(request("POST", "/assets/css/main.css")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    serve.shutdown();
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn can_expose_matched_path() {
+    use routerify_ng::RequestInfo;
+    use routerify_ng::ext::RequestExt;
+
+    let api = Router::builder()
+        .get("/books/:bookId", |req| async move {
+            Ok(Response::new(Full::from(req.matched_path().unwrap().to_owned())))
+        })
+        .build()
+        .unwrap();
+    let router: Router<routerify_ng::Error> = Router::builder()
+        .middleware(Middleware::pre(|mut req: Request<Full<Bytes>>| async move {
+            let matched_path = req.matched_path().unwrap_or("unmatched").to_owned();
+            req.headers_mut().insert("x-pre-route", matched_path.parse().unwrap());
+            Ok(req)
+        }))
+        .get("/users/:id", |req| async move {
+            Ok(Response::new(Full::from(format!(
+                "{} {}",
+                req.headers()["x-pre-route"].to_str().unwrap(),
+                req.matched_path().unwrap()
+            ))))
+        })
+        .get("/docs/", |req| async move {
+            Ok(Response::new(Full::from(req.matched_path().unwrap().to_owned())))
+        })
+        .get("/assets/*path", |req| async move {
+            Ok(Response::new(Full::from(req.matched_path().unwrap().to_owned())))
+        })
+        .scope("/api", api)
+        .middleware(Middleware::post_with_info(
+            |mut res: Response<Full<Bytes>>, req_info: RequestInfo| async move {
+                let matched_path = req_info.matched_path().unwrap_or("unmatched").to_owned();
+                res.headers_mut().insert("x-route", matched_path.parse().unwrap());
+                Ok(res)
+            },
+        ))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let cases = [
+        ("/users/42", "/users/:id", "/users/:id /users/:id"),
+        ("/docs", "/docs", "/docs"),
+        ("/assets/css/main.css", "/assets/*path", "/assets/*path"),
+        ("/api/books/7", "/api/books/:bookId", "/api/books/:bookId"),
+    ];
+    for (path, route, text) in cases {
+        let resp = client
+            .request(serve.new_request("GET", path).body(Full::new(Bytes::new())).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK, "unexpected status for {}", path);
+        assert_eq!(resp.headers()["x-route"], route, "unexpected route for {}", path);
+        assert_eq!(into_text(resp.into_body()).await, text);
+    }
+
+    let resp = client
+        .request(
+            serve
+                .new_request("GET", "/missing")
+                .body(Full::new(Bytes::new()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()["x-route"], "/*");
+
+    serve.shutdown();
+}
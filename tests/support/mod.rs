@@ -1,4 +1,4 @@
-use hyper::body::Bytes;
+use hyper::body::{Body, Bytes};
 use hyper::service::Service;
 use hyper_util::rt::TokioExecutor;
 use hyper_util::rt::TokioIo;
@@ -30,9 +30,11 @@ impl Serve {
     }
 }
 
-pub async fn serve<E>(router: Router<E>) -> Serve
+pub async fn serve<E, B>(router: Router<E, B>) -> Serve
 where
     E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B: Body<Data = Bytes> + From<Bytes> + Send + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
     // Bind a TCP listener to an available port.
     let listener = Arc::new(TcpListener::bind("127.0.0.1:0").await.unwrap());